@@ -0,0 +1,112 @@
+// ping_2_pong/crates/pong_sim/src/codec.rs
+//! Minimal varint / zigzag byte codec for compact binary frame streams.
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CodecError {
+    UnexpectedEnd,
+    VarintOverflow,
+    OutOfRange,
+}
+
+impl fmt::Display for CodecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            CodecError::UnexpectedEnd => "unexpected end of data",
+            CodecError::VarintOverflow => "varint overflow",
+            CodecError::OutOfRange => "value out of range",
+        })
+    }
+}
+
+#[derive(Default, Debug)]
+pub struct ByteWriter {
+    bytes: Vec<u8>,
+}
+
+impl ByteWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+
+    pub fn put_u8(&mut self, value: u8) {
+        self.bytes.push(value);
+    }
+
+    /// LEB128 unsigned varint.
+    pub fn put_uvarint(&mut self, mut value: u64) {
+        while value >= 0x80 {
+            self.bytes.push((value as u8) | 0x80);
+            value >>= 7;
+        }
+        self.bytes.push(value as u8);
+    }
+
+    /// Zigzag-encoded signed varint, so small negative deltas stay one byte.
+    pub fn put_ivarint(&mut self, value: i64) {
+        self.put_uvarint(((value << 1) ^ (value >> 63)) as u64);
+    }
+}
+
+#[derive(Debug)]
+pub struct ByteReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, pos: 0 }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pos >= self.bytes.len()
+    }
+
+    pub fn get_u8(&mut self) -> Result<u8, CodecError> {
+        let value = *self.bytes.get(self.pos).ok_or(CodecError::UnexpectedEnd)?;
+        self.pos += 1;
+        Ok(value)
+    }
+
+    pub fn get_uvarint(&mut self) -> Result<u64, CodecError> {
+        let mut value: u64 = 0;
+        let mut shift = 0;
+        loop {
+            let byte = self.get_u8()?;
+            if shift >= 64 {
+                return Err(CodecError::VarintOverflow);
+            }
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+            shift += 7;
+        }
+    }
+
+    pub fn get_ivarint(&mut self) -> Result<i64, CodecError> {
+        let raw = self.get_uvarint()?;
+        Ok(((raw >> 1) as i64) ^ -((raw & 1) as i64))
+    }
+
+    pub fn get_i32(&mut self) -> Result<i32, CodecError> {
+        i32::try_from(self.get_ivarint()?).map_err(|_| CodecError::OutOfRange)
+    }
+
+    pub fn get_u32(&mut self) -> Result<u32, CodecError> {
+        u32::try_from(self.get_uvarint()?).map_err(|_| CodecError::OutOfRange)
+    }
+}
@@ -0,0 +1,27 @@
+// ping_2_pong/crates/pong_sim/src/fixed.rs
+//! 16.16 signed fixed-point helpers.
+
+pub type Fixed = i32;
+
+pub const FRAC_BITS: u32 = 16;
+pub const ONE: Fixed = 1 << FRAC_BITS;
+
+/// Whole pixels to fixed point.
+pub const fn to_fixed(value: i32) -> Fixed {
+    value << FRAC_BITS
+}
+
+/// Fixed point to whole pixels, rounding half away from zero.
+pub const fn from_fixed(value: Fixed) -> i32 {
+    let half = ONE / 2;
+    if value >= 0 {
+        (value + half) >> FRAC_BITS
+    } else {
+        -((-value + half) >> FRAC_BITS)
+    }
+}
+
+/// Fixed-point multiply with an i64 intermediate, truncating toward zero.
+pub fn mul(a: Fixed, b: Fixed) -> Fixed {
+    ((a as i64 * b as i64) / ONE as i64) as Fixed
+}
@@ -0,0 +1,319 @@
+// ping_2_pong/crates/pong_sim/src/lib.rs
+//! Deterministic fixed-point Pong simulation, and the byte codec its frame streams use.
+//!
+//! All state is integer (16.16 fixed point), so every peer stepping the same
+//! inputs from the same state reaches bit-identical results regardless of
+//! platform. The standard geometry and tuning mirror `PongGame.svelte`; a game
+//! with its own rules steps under the matching `SimConfig`.
+use serde::{Deserialize, Serialize};
+
+pub mod codec;
+mod fixed;
+mod rollback;
+pub use fixed::{from_fixed, to_fixed, Fixed, FRAC_BITS, ONE};
+pub use rollback::{InputRejection, Rollback, MAX_ROLLBACK_FRAMES};
+
+// --- Standard field geometry (pixels) ---
+pub const FIELD_WIDTH: i32 = 800;
+pub const FIELD_HEIGHT: i32 = 600;
+pub const PADDLE_WIDTH: i32 = 10;
+pub const PADDLE_HEIGHT: i32 = 100;
+pub const BALL_RADIUS: i32 = 10;
+pub const PADDLE_SPEED: i32 = 25; // Pixels per frame while a key is held
+pub const WINNING_SCORE: u32 = 10;
+
+// --- Tuning (fixed point) ---
+const HITBOX_GRACE: Fixed = 18 * ONE;      // Extra vertical reach of a paddle
+const PADDLE_FACE_MARGIN: Fixed = 8 * ONE; // Extra horizontal reach of a paddle face
+const MAX_BALL_SPEED: Fixed = 12 * ONE;
+const SPEED_UP: Fixed = 70_779;            // ~1.08x per paddle hit
+const ANGLE_FACTOR: Fixed = 22_938;        // ~0.35 px/frame of dy per px off paddle centre
+const SERVE_DX: Fixed = 5 * ONE / 2;       // 2.5 px/frame
+const SERVE_DY: Fixed = 2 * ONE;
+
+/// The per-game rules the simulation follows. `Default` is the standard game.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SimConfig {
+    pub field_width: i32,  // Pixels
+    pub field_height: i32, // Pixels
+    pub paddle_height: i32, // Pixels
+    pub points_to_win: u32,
+    pub win_by_two: bool,  // Play on past points_to_win until one player leads by two
+    pub serve_dx: Fixed,   // Horizontal serve speed
+    pub max_ball_speed: Fixed,
+    pub speed_up: Fixed,   // Factor applied to the ball speed per paddle hit
+}
+
+impl Default for SimConfig {
+    fn default() -> Self {
+        SimConfig {
+            field_width: FIELD_WIDTH,
+            field_height: FIELD_HEIGHT,
+            paddle_height: PADDLE_HEIGHT,
+            points_to_win: WINNING_SCORE,
+            win_by_two: false,
+            serve_dx: SERVE_DX,
+            max_ball_speed: MAX_BALL_SPEED,
+            speed_up: SPEED_UP,
+        }
+    }
+}
+
+impl SimConfig {
+    /// True once a score ends the game under these rules.
+    pub fn is_won(&self, score_1: u32, score_2: u32) -> bool {
+        let (high, low) = (score_1.max(score_2), score_1.min(score_2));
+        let lead = if self.win_by_two { 2 } else { 1 };
+        high >= self.points_to_win && high - low >= lead
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Side {
+    Player1,
+    Player2,
+}
+
+/// Paddle control for one player on one frame.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum PaddleInput {
+    #[default]
+    Idle,
+    Up,
+    Down,
+}
+
+/// Both players' inputs for one simulation frame.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct FrameInput {
+    pub player_1: PaddleInput,
+    pub player_2: PaddleInput,
+}
+
+/// Something noteworthy that happened during a step.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StepEvent {
+    PaddleHit(Side),
+    Scored(Side),
+    GameOver(Side),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GameState {
+    pub frame: u32, // Keeps counting after game over (see `step`)
+    pub paddle_1_y: Fixed, // Top edge of each paddle
+    pub paddle_2_y: Fixed,
+    pub ball_x: Fixed, // Ball centre
+    pub ball_y: Fixed,
+    pub ball_dx: Fixed, // Pixels per frame
+    pub ball_dy: Fixed,
+    pub score_1: u32,
+    pub score_2: u32,
+    pub winner: Option<Side>,
+    #[serde(default)] // States saved before per-game rules follow the standard ones
+    pub config: SimConfig,
+}
+
+impl Default for GameState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GameState {
+    /// Kick-off state under the standard rules: paddles centred, ball served towards player 2.
+    pub fn new() -> Self {
+        Self::with_config(SimConfig::default())
+    }
+
+    /// Kick-off state under `config`.
+    pub fn with_config(config: SimConfig) -> Self {
+        let paddle_y = to_fixed((config.field_height - config.paddle_height) / 2);
+        let mut state = GameState {
+            frame: 0,
+            paddle_1_y: paddle_y,
+            paddle_2_y: paddle_y,
+            ball_x: 0,
+            ball_y: 0,
+            ball_dx: 0,
+            ball_dy: 0,
+            score_1: 0,
+            score_2: 0,
+            winner: None,
+            config,
+        };
+        state.serve(Side::Player2);
+        state
+    }
+
+    /// State at the start of a point when resuming an interrupted game: paddles
+    /// centred, ball served towards `serve_towards`, frame numbering from 0.
+    pub fn resume(score_1: u32, score_2: u32, serve_towards: Side) -> Self {
+        Self::resume_with(SimConfig::default(), score_1, score_2, serve_towards)
+    }
+
+    /// `resume` under `config`.
+    pub fn resume_with(config: SimConfig, score_1: u32, score_2: u32, serve_towards: Side) -> Self {
+        let mut state = GameState { score_1, score_2, ..GameState::with_config(config) };
+        state.serve(serve_towards);
+        state
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.winner.is_some()
+    }
+
+    /// Advances one frame. Returns the events of this frame in order.
+    ///
+    /// Once the game is over nothing moves or scores, but `frame` still advances:
+    /// rollback numbers inputs from the confirmed frame, so peers exchanging inputs
+    /// after the final point stay in step. Two states compared across game over
+    /// therefore differ in `frame` (and `checksum`) only.
+    pub fn step(&mut self, input: FrameInput) -> Vec<StepEvent> {
+        let mut events = Vec::new();
+        self.frame = self.frame.wrapping_add(1);
+        if self.is_over() {
+            return events;
+        }
+        let config = self.config;
+        self.paddle_1_y = move_paddle(&config, self.paddle_1_y, input.player_1);
+        self.paddle_2_y = move_paddle(&config, self.paddle_2_y, input.player_2);
+
+        self.ball_x += self.ball_dx;
+        self.ball_y += self.ball_dy;
+
+        // Walls
+        let radius = to_fixed(BALL_RADIUS);
+        if self.ball_y + radius > to_fixed(config.field_height) || self.ball_y - radius < 0 {
+            self.ball_dy = -self.ball_dy;
+            self.ball_y = self.ball_y.clamp(radius, to_fixed(config.field_height - BALL_RADIUS));
+        }
+
+        // Paddles
+        let paddle_face = to_fixed(PADDLE_WIDTH);
+        let far_face = to_fixed(config.field_width - PADDLE_WIDTH);
+        if self.ball_dx < 0
+            && self.ball_x - radius <= paddle_face + PADDLE_FACE_MARGIN
+            && self.ball_x >= radius - PADDLE_FACE_MARGIN
+            && self.in_paddle_reach(self.paddle_1_y)
+        {
+            self.ball_dx = speed_up(&config, self.ball_dx);
+            self.ball_x = paddle_face + radius;
+            self.ball_dy = deflect(&config, self.ball_y, self.paddle_1_y);
+            events.push(StepEvent::PaddleHit(Side::Player1));
+        } else if self.ball_dx > 0
+            && self.ball_x + radius >= far_face - PADDLE_FACE_MARGIN
+            && self.ball_x <= to_fixed(config.field_width) - radius + PADDLE_FACE_MARGIN
+            && self.in_paddle_reach(self.paddle_2_y)
+        {
+            self.ball_dx = -speed_up(&config, self.ball_dx);
+            self.ball_x = far_face - radius;
+            self.ball_dy = deflect(&config, self.ball_y, self.paddle_2_y);
+            events.push(StepEvent::PaddleHit(Side::Player2));
+        }
+
+        // Goals
+        let scorer = if self.ball_x + radius < 0 {
+            Some(Side::Player2)
+        } else if self.ball_x - radius > to_fixed(config.field_width) {
+            Some(Side::Player1)
+        } else {
+            None
+        };
+        if let Some(side) = scorer {
+            match side {
+                Side::Player1 => self.score_1 += 1,
+                Side::Player2 => self.score_2 += 1,
+            }
+            events.push(StepEvent::Scored(side));
+            if config.is_won(self.score_1, self.score_2) {
+                self.winner = Some(side);
+                events.push(StepEvent::GameOver(side));
+            } else {
+                // Serve towards the player who lost the point.
+                self.serve(match side {
+                    Side::Player1 => Side::Player2,
+                    Side::Player2 => Side::Player1,
+                });
+            }
+        }
+        events
+    }
+
+    /// Steps through a sequence of inputs, collecting all events.
+    pub fn run(&mut self, inputs: &[FrameInput]) -> Vec<StepEvent> {
+        inputs.iter().flat_map(|input| self.step(*input)).collect()
+    }
+
+    /// Ball position and velocity rounded to whole pixels, as carried by `BallUpdate`.
+    pub fn ball_pixels(&self) -> [i32; 4] {
+        [from_fixed(self.ball_x), from_fixed(self.ball_y), from_fixed(self.ball_dx), from_fixed(self.ball_dy)]
+    }
+
+    /// Paddle top edges rounded to whole pixels, as carried by `PaddleUpdate`.
+    pub fn paddle_pixels(&self) -> [i32; 2] {
+        [from_fixed(self.paddle_1_y), from_fixed(self.paddle_2_y)]
+    }
+
+    /// FNV-1a digest of the full state, for cheap desync detection between peers.
+    pub fn checksum(&self) -> u64 {
+        let winner = match self.winner {
+            None => 0,
+            Some(Side::Player1) => 1,
+            Some(Side::Player2) => 2,
+        };
+        let words: [u32; 10] = [
+            self.frame,
+            self.paddle_1_y as u32,
+            self.paddle_2_y as u32,
+            self.ball_x as u32,
+            self.ball_y as u32,
+            self.ball_dx as u32,
+            self.ball_dy as u32,
+            self.score_1,
+            self.score_2,
+            winner,
+        ];
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        for byte in words.iter().flat_map(|w| w.to_le_bytes()) {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+        hash
+    }
+
+    fn in_paddle_reach(&self, paddle_y: Fixed) -> bool {
+        self.ball_y >= paddle_y - HITBOX_GRACE && self.ball_y <= paddle_y + to_fixed(self.config.paddle_height) + HITBOX_GRACE
+    }
+
+    // Centre the ball and serve towards `towards`; vertical direction alternates by points played.
+    fn serve(&mut self, towards: Side) {
+        self.ball_x = to_fixed(self.config.field_width / 2);
+        self.ball_y = to_fixed(self.config.field_height / 2);
+        self.ball_dx = match towards {
+            Side::Player1 => -self.config.serve_dx,
+            Side::Player2 => self.config.serve_dx,
+        };
+        self.ball_dy = if (self.score_1 + self.score_2).is_multiple_of(2) { SERVE_DY } else { -SERVE_DY };
+    }
+}
+
+fn move_paddle(config: &SimConfig, paddle_y: Fixed, input: PaddleInput) -> Fixed {
+    let moved = match input {
+        PaddleInput::Idle => paddle_y,
+        PaddleInput::Up => paddle_y - to_fixed(PADDLE_SPEED),
+        PaddleInput::Down => paddle_y + to_fixed(PADDLE_SPEED),
+    };
+    moved.clamp(0, to_fixed(config.field_height - config.paddle_height))
+}
+
+// Speed up a horizontal velocity after a hit, returning its new magnitude.
+fn speed_up(config: &SimConfig, dx: Fixed) -> Fixed {
+    fixed::mul(dx.abs(), config.speed_up).min(config.max_ball_speed)
+}
+
+// Vertical velocity from how far off the paddle centre the ball struck.
+fn deflect(config: &SimConfig, ball_y: Fixed, paddle_y: Fixed) -> Fixed {
+    let offset = ball_y - (paddle_y + to_fixed(config.paddle_height / 2));
+    fixed::mul(offset, ANGLE_FACTOR)
+}
@@ -0,0 +1,146 @@
+// ping_2_pong/crates/pong_sim/src/rollback.rs
+//! Input-only lockstep with rollback.
+//!
+//! Only paddle inputs travel between peers. Each peer keeps the last state
+//! both players' inputs are known for (`confirmed`) and the inputs received
+//! since. Missing remote inputs are predicted by repeating the last known one;
+//! when a late input disagrees with the prediction the peer rolls back to
+//! `confirmed` and re-simulates. Since the prediction is always rebuilt from
+//! `confirmed`, a rollback is just a re-run with the corrected input.
+use serde::{Deserialize, Serialize};
+use crate::{FrameInput, GameState, PaddleInput, Side, StepEvent};
+
+/// How far the local player may run ahead of the last confirmed frame before
+/// the peer stalls waiting for remote inputs (~240 ms at 50 fps).
+pub const MAX_ROLLBACK_FRAMES: usize = 12;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Rollback {
+    pub local_side: Side,
+    pub confirmed: GameState,
+    // Inputs for frames after `confirmed.frame`; index 0 is frame confirmed.frame + 1.
+    pub local_inputs: Vec<PaddleInput>,
+    pub remote_inputs: Vec<Option<PaddleInput>>,
+    pub last_remote_input: PaddleInput, // Basis for predicting missing remote inputs
+}
+
+/// Why a local input was not accepted.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputRejection {
+    /// Not the next frame after the last local input.
+    OutOfOrder { expected: u32 },
+    /// Too far ahead of the remote player; wait for their inputs.
+    Stalled,
+}
+
+impl Rollback {
+    pub fn new(local_side: Side) -> Self {
+        Self::from_state(local_side, GameState::new())
+    }
+
+    /// Starts from a state both peers agree on, e.g. `GameState::resume` after a
+    /// reconnect; the next local input is for frame `confirmed.frame + 1`.
+    pub fn from_state(local_side: Side, confirmed: GameState) -> Self {
+        Rollback {
+            local_side,
+            confirmed,
+            local_inputs: Vec::new(),
+            remote_inputs: Vec::new(),
+            last_remote_input: PaddleInput::Idle,
+        }
+    }
+
+    /// Frame the next local input is for.
+    pub fn next_local_frame(&self) -> u32 {
+        self.confirmed.frame + self.local_inputs.len() as u32 + 1
+    }
+
+    /// Records the local player's input for `frame`.
+    pub fn add_local_input(&mut self, frame: u32, input: PaddleInput) -> Result<(), InputRejection> {
+        let expected = self.next_local_frame();
+        if frame != expected {
+            return Err(InputRejection::OutOfOrder { expected });
+        }
+        if self.local_inputs.len() >= MAX_ROLLBACK_FRAMES {
+            return Err(InputRejection::Stalled);
+        }
+        self.local_inputs.push(input);
+        self.confirm();
+        Ok(())
+    }
+
+    /// Records remote inputs for consecutive frames starting at `first_frame`.
+    /// Frames already confirmed or too far ahead are ignored. Returns the
+    /// earliest already-simulated frame whose prediction turned out wrong.
+    pub fn add_remote_inputs(&mut self, first_frame: u32, inputs: &[PaddleInput]) -> Option<u32> {
+        let mut rolled_back_from = None;
+        for (offset, input) in inputs.iter().enumerate() {
+            let frame = first_frame.saturating_add(offset as u32);
+            if frame <= self.confirmed.frame {
+                continue;
+            }
+            let index = (frame - self.confirmed.frame - 1) as usize;
+            if index >= MAX_ROLLBACK_FRAMES * 2 {
+                break;
+            }
+            if index >= self.remote_inputs.len() {
+                self.remote_inputs.resize(index + 1, None);
+            }
+            if self.remote_inputs[index].is_some() {
+                continue;
+            }
+            let simulated = index < self.local_inputs.len();
+            if simulated && rolled_back_from.is_none() && self.predicted_remote_input(index) != *input {
+                rolled_back_from = Some(frame);
+            }
+            self.remote_inputs[index] = Some(*input);
+        }
+        self.confirm();
+        rolled_back_from
+    }
+
+    /// Best current view of the game: confirmed state plus predicted frames.
+    pub fn predicted_state(&self) -> (GameState, Vec<StepEvent>) {
+        let mut state = self.confirmed.clone();
+        let mut events = Vec::new();
+        for index in 0..self.local_inputs.len() {
+            events.extend(state.step(self.frame_input(index)));
+        }
+        (state, events)
+    }
+
+    // Remote input assumed for `index`: the nearest known earlier input.
+    fn predicted_remote_input(&self, index: usize) -> PaddleInput {
+        self.remote_inputs[..index.min(self.remote_inputs.len())]
+            .iter()
+            .rev()
+            .find_map(|input| *input)
+            .unwrap_or(self.last_remote_input)
+    }
+
+    fn frame_input(&self, index: usize) -> FrameInput {
+        let local = self.local_inputs[index];
+        let remote = self.remote_inputs.get(index).copied().flatten().unwrap_or_else(|| self.predicted_remote_input(index));
+        match self.local_side {
+            Side::Player1 => FrameInput { player_1: local, player_2: remote },
+            Side::Player2 => FrameInput { player_1: remote, player_2: local },
+        }
+    }
+
+    // Advances `confirmed` over every frame both inputs are known for.
+    fn confirm(&mut self) {
+        let mut count = 0;
+        while count < self.local_inputs.len() && matches!(self.remote_inputs.get(count), Some(Some(_))) {
+            count += 1;
+        }
+        for index in 0..count {
+            let input = self.frame_input(index);
+            self.confirmed.step(input);
+        }
+        if count > 0 {
+            self.last_remote_input = self.remote_inputs[count - 1].unwrap_or(self.last_remote_input);
+            self.local_inputs.drain(..count);
+            self.remote_inputs.drain(..count);
+        }
+    }
+}
@@ -0,0 +1,71 @@
+// ping_2_pong/crates/pong_sim/tests/codec.rs
+use pong_sim::codec::{ByteReader, ByteWriter, CodecError};
+
+#[test]
+fn unsigned_varints_round_trip() {
+    let values = [0, 1, 127, 128, 300, 16_383, 16_384, u32::MAX as u64, u64::MAX];
+    let mut writer = ByteWriter::new();
+    for value in values {
+        writer.put_uvarint(value);
+    }
+    let bytes = writer.into_bytes();
+    let mut reader = ByteReader::new(&bytes);
+    for value in values {
+        assert_eq!(reader.get_uvarint(), Ok(value));
+    }
+    assert!(reader.is_empty());
+}
+
+#[test]
+fn signed_varints_round_trip() {
+    let values = [0, -1, 1, -64, 63, -65, 64, i32::MIN as i64, i32::MAX as i64, i64::MIN, i64::MAX];
+    let mut writer = ByteWriter::new();
+    for value in values {
+        writer.put_ivarint(value);
+    }
+    let bytes = writer.into_bytes();
+    let mut reader = ByteReader::new(&bytes);
+    for value in values {
+        assert_eq!(reader.get_ivarint(), Ok(value));
+    }
+    assert!(reader.is_empty());
+}
+
+#[test]
+fn small_deltas_stay_one_byte() {
+    for value in [-64, -1, 0, 1, 63] {
+        let mut writer = ByteWriter::new();
+        writer.put_ivarint(value);
+        assert_eq!(writer.len(), 1, "zigzag({}) should fit one byte", value);
+    }
+}
+
+#[test]
+fn mixed_fields_round_trip() {
+    let mut writer = ByteWriter::new();
+    writer.put_u8(7);
+    writer.put_uvarint(u32::MAX as u64);
+    writer.put_ivarint(i32::MIN as i64);
+    let bytes = writer.into_bytes();
+    let mut reader = ByteReader::new(&bytes);
+    assert_eq!(reader.get_u8(), Ok(7));
+    assert_eq!(reader.get_u32(), Ok(u32::MAX));
+    assert_eq!(reader.get_i32(), Ok(i32::MIN));
+    assert!(reader.is_empty());
+}
+
+#[test]
+fn malformed_input_is_rejected() {
+    // Truncated: continuation bit set on the last byte.
+    assert_eq!(ByteReader::new(&[0x80]).get_uvarint(), Err(CodecError::UnexpectedEnd));
+    // More than ten continuation bytes cannot fit in 64 bits.
+    assert_eq!(ByteReader::new(&[0xff; 11]).get_uvarint(), Err(CodecError::VarintOverflow));
+    // Valid varints outside the requested width.
+    let mut writer = ByteWriter::new();
+    writer.put_uvarint(u32::MAX as u64 + 1);
+    writer.put_ivarint(i32::MAX as i64 + 1);
+    let bytes = writer.into_bytes();
+    let mut reader = ByteReader::new(&bytes);
+    assert_eq!(reader.get_u32(), Err(CodecError::OutOfRange));
+    assert_eq!(reader.get_i32(), Err(CodecError::OutOfRange));
+}
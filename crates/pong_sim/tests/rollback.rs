@@ -0,0 +1,135 @@
+// ping_2_pong/crates/pong_sim/tests/rollback.rs
+use pong_sim::*;
+
+fn input(n: u32) -> PaddleInput {
+    match n % 3 {
+        0 => PaddleInput::Idle,
+        1 => PaddleInput::Up,
+        _ => PaddleInput::Down,
+    }
+}
+
+fn player_1_input(frame: u32) -> PaddleInput {
+    input(frame / 5)
+}
+
+fn player_2_input(frame: u32) -> PaddleInput {
+    input(frame / 3 + 2)
+}
+
+// Reference run without any networking, frames 1..=frames.
+fn simulate(start: GameState, frames: u32) -> GameState {
+    let mut state = start;
+    let first = state.frame + 1;
+    for frame in first..first + frames {
+        state.step(FrameInput { player_1: player_1_input(frame), player_2: player_2_input(frame) });
+    }
+    state
+}
+
+#[test]
+fn peers_with_delayed_inputs_converge_on_the_reference_state() {
+    const DELAY: u32 = 4; // Frames before an input reaches the other peer
+    const FRAMES: u32 = 300;
+    let mut peer_1 = Rollback::new(Side::Player1);
+    let mut peer_2 = Rollback::new(Side::Player2);
+    for frame in 1..=FRAMES + DELAY {
+        if frame <= FRAMES {
+            peer_1.add_local_input(frame, player_1_input(frame)).unwrap();
+            peer_2.add_local_input(frame, player_2_input(frame)).unwrap();
+        }
+        if frame > DELAY {
+            let arrived = frame - DELAY;
+            peer_1.add_remote_inputs(arrived, &[player_2_input(arrived)]);
+            peer_2.add_remote_inputs(arrived, &[player_1_input(arrived)]);
+        }
+    }
+    let reference = simulate(GameState::new(), FRAMES);
+    assert_eq!(peer_1.confirmed, reference);
+    assert_eq!(peer_2.confirmed, reference);
+    assert_eq!(peer_1.predicted_state().0, reference);
+}
+
+#[test]
+fn late_input_that_contradicts_the_prediction_rolls_back() {
+    let mut peer = Rollback::new(Side::Player1);
+    for frame in 1..=6 {
+        peer.add_local_input(frame, PaddleInput::Idle).unwrap();
+    }
+    // Nothing received yet: the remote player is predicted idle.
+    let (predicted, _) = peer.predicted_state();
+    assert_eq!(predicted.paddle_2_y, GameState::new().paddle_2_y);
+
+    // The remote player actually moved down from frame 3.
+    let remote = [PaddleInput::Idle, PaddleInput::Idle, PaddleInput::Down, PaddleInput::Down];
+    assert_eq!(peer.add_remote_inputs(1, &remote), Some(3));
+    assert_eq!(peer.confirmed.frame, 4);
+
+    // Frames 5-6 are re-simulated with the corrected input repeated as the prediction.
+    let mut expected = GameState::new();
+    for frame in 1..=6 {
+        let player_2 = if frame >= 3 { PaddleInput::Down } else { PaddleInput::Idle };
+        expected.step(FrameInput { player_1: PaddleInput::Idle, player_2 });
+    }
+    assert_eq!(peer.predicted_state().0, expected);
+}
+
+#[test]
+fn input_matching_the_prediction_does_not_roll_back() {
+    let mut peer = Rollback::new(Side::Player2);
+    for frame in 1..=3 {
+        peer.add_local_input(frame, PaddleInput::Up).unwrap();
+    }
+    assert_eq!(peer.add_remote_inputs(1, &[PaddleInput::Idle, PaddleInput::Idle]), None);
+    assert_eq!(peer.confirmed.frame, 2);
+}
+
+#[test]
+fn repeated_and_stale_remote_inputs_are_ignored() {
+    let mut peer = Rollback::new(Side::Player1);
+    for frame in 1..=4 {
+        peer.add_local_input(frame, PaddleInput::Idle).unwrap();
+    }
+    peer.add_remote_inputs(1, &[PaddleInput::Idle, PaddleInput::Idle]);
+    let before = peer.clone();
+    // Resent window overlapping confirmed frames, with a conflicting value for a confirmed one.
+    assert_eq!(peer.add_remote_inputs(1, &[PaddleInput::Down]), None);
+    assert_eq!(peer, before);
+}
+
+#[test]
+fn local_inputs_must_be_consecutive() {
+    let mut peer = Rollback::new(Side::Player1);
+    assert_eq!(peer.add_local_input(2, PaddleInput::Idle), Err(InputRejection::OutOfOrder { expected: 1 }));
+    peer.add_local_input(1, PaddleInput::Idle).unwrap();
+    assert_eq!(peer.add_local_input(1, PaddleInput::Idle), Err(InputRejection::OutOfOrder { expected: 2 }));
+}
+
+#[test]
+fn local_player_stalls_too_far_ahead_of_the_remote_one() {
+    let mut peer = Rollback::new(Side::Player1);
+    for frame in 1..=MAX_ROLLBACK_FRAMES as u32 {
+        peer.add_local_input(frame, PaddleInput::Idle).unwrap();
+    }
+    let next = MAX_ROLLBACK_FRAMES as u32 + 1;
+    assert_eq!(peer.add_local_input(next, PaddleInput::Idle), Err(InputRejection::Stalled));
+    peer.add_remote_inputs(1, &[PaddleInput::Idle]);
+    assert_eq!(peer.add_local_input(next, PaddleInput::Idle), Ok(()));
+}
+
+#[test]
+fn peers_resuming_from_the_same_state_stay_in_step() {
+    let start = GameState::resume(4, 2, Side::Player2);
+    let mut peer_1 = Rollback::from_state(Side::Player1, start.clone());
+    let mut peer_2 = Rollback::from_state(Side::Player2, start.clone());
+    assert_eq!(peer_1.next_local_frame(), 1);
+    for frame in 1..=50 {
+        peer_1.add_local_input(frame, player_1_input(frame)).unwrap();
+        peer_2.add_local_input(frame, player_2_input(frame)).unwrap();
+        peer_1.add_remote_inputs(frame, &[player_2_input(frame)]);
+        peer_2.add_remote_inputs(frame, &[player_1_input(frame)]);
+    }
+    let reference = simulate(start, 50);
+    assert_eq!(peer_1.confirmed, reference);
+    assert_eq!(peer_2.confirmed, reference);
+}
@@ -0,0 +1,135 @@
+// ping_2_pong/crates/pong_sim/tests/simulation.rs
+use pong_sim::*;
+
+// A fixed, varied input script: both paddles move in overlapping patterns.
+fn scripted_inputs(frames: u32) -> Vec<FrameInput> {
+    (0..frames)
+        .map(|frame| {
+            let pick = |n: u32| match n % 3 {
+                0 => PaddleInput::Idle,
+                1 => PaddleInput::Up,
+                _ => PaddleInput::Down,
+            };
+            FrameInput { player_1: pick(frame / 7), player_2: pick(frame / 11 + 1) }
+        })
+        .collect()
+}
+
+// State with the ball about to cross the goal line behind player 2, whose paddle is out of reach.
+fn about_to_score_for_player_1() -> GameState {
+    let mut state = GameState::new();
+    state.paddle_2_y = 0;
+    state.ball_x = to_fixed(FIELD_WIDTH + BALL_RADIUS - 1);
+    state.ball_y = to_fixed(FIELD_HEIGHT - 50);
+    state.ball_dx = 5 * ONE;
+    state.ball_dy = 0;
+    state
+}
+
+#[test]
+fn same_inputs_give_the_same_checksum() {
+    let inputs = scripted_inputs(2_000);
+    let mut a = GameState::new();
+    let mut b = GameState::new();
+    let events_a = a.run(&inputs);
+    let events_b = b.run(&inputs);
+    assert_eq!(a, b);
+    assert_eq!(a.checksum(), b.checksum());
+    assert_eq!(events_a, events_b);
+}
+
+#[test]
+fn different_inputs_change_the_checksum() {
+    let inputs = scripted_inputs(200);
+    let mut altered = inputs.clone();
+    altered[10].player_1 = PaddleInput::Down;
+    altered[11].player_1 = PaddleInput::Down;
+    let mut a = GameState::new();
+    let mut b = GameState::new();
+    a.run(&inputs);
+    b.run(&altered);
+    assert_ne!(a.checksum(), b.checksum());
+}
+
+#[test]
+fn missed_ball_scores_for_the_other_player_and_reserves() {
+    let mut state = about_to_score_for_player_1();
+    let events = state.step(FrameInput::default());
+    assert_eq!(events, vec![StepEvent::Scored(Side::Player1)]);
+    assert_eq!((state.score_1, state.score_2), (1, 0));
+    assert!(!state.is_over());
+    // Served from the centre towards the player who lost the point.
+    assert_eq!(state.ball_pixels()[..2], [FIELD_WIDTH / 2, FIELD_HEIGHT / 2]);
+    assert!(state.ball_dx > 0);
+}
+
+#[test]
+fn reaching_the_winning_score_ends_the_game() {
+    let mut state = about_to_score_for_player_1();
+    state.score_1 = WINNING_SCORE - 1;
+    let events = state.step(FrameInput::default());
+    assert_eq!(events, vec![StepEvent::Scored(Side::Player1), StepEvent::GameOver(Side::Player1)]);
+    assert_eq!(state.winner, Some(Side::Player1));
+    assert!(state.is_over());
+
+    // Nothing moves or scores once the game is over; only the frame counter runs on.
+    let over = state.clone();
+    let events = state.run(&scripted_inputs(100));
+    assert!(events.is_empty());
+    assert_eq!(state.frame, over.frame + 100);
+    assert_eq!((state.score_1, state.score_2), (over.score_1, over.score_2));
+    assert_eq!(state.ball_pixels(), over.ball_pixels());
+    assert_eq!(state.paddle_pixels(), over.paddle_pixels());
+}
+
+#[test]
+fn paddle_in_the_way_returns_the_ball() {
+    let mut state = about_to_score_for_player_1();
+    state.ball_x = to_fixed(FIELD_WIDTH - PADDLE_WIDTH - BALL_RADIUS - 2);
+    state.paddle_2_y = state.ball_y - to_fixed(PADDLE_HEIGHT / 2);
+    let events = state.step(FrameInput::default());
+    assert_eq!(events, vec![StepEvent::PaddleHit(Side::Player2)]);
+    assert!(state.ball_dx < 0);
+}
+
+#[test]
+fn resume_serves_from_the_centre_with_the_score_kept() {
+    let state = GameState::resume(3, 7, Side::Player1);
+    assert_eq!((state.frame, state.score_1, state.score_2, state.winner), (0, 3, 7, None));
+    assert_eq!(state.ball_pixels()[..2], [FIELD_WIDTH / 2, FIELD_HEIGHT / 2]);
+    assert!(state.ball_dx < 0);
+    assert_eq!(state.paddle_pixels(), GameState::new().paddle_pixels());
+}
+
+#[test]
+fn custom_rules_shape_the_field_and_the_win() {
+    let config = SimConfig { field_width: 400, field_height: 300, points_to_win: 3, win_by_two: true, ..SimConfig::default() };
+    let mut state = GameState::with_config(config);
+    assert_eq!(state.ball_pixels()[..2], [200, 150]);
+    assert_eq!(state.paddle_pixels(), [100, 100]);
+
+    // 3-2 is not a two-point lead, so play goes on; 4-2 ends it.
+    state.score_1 = 2;
+    state.score_2 = 2;
+    state.paddle_2_y = 0;
+    state.ball_x = to_fixed(400 + BALL_RADIUS - 1);
+    state.ball_y = to_fixed(250);
+    state.ball_dx = 5 * ONE;
+    state.ball_dy = 0;
+    assert_eq!(state.step(FrameInput::default()), vec![StepEvent::Scored(Side::Player1)]);
+    state.ball_x = to_fixed(400 + BALL_RADIUS - 1);
+    state.ball_y = to_fixed(250);
+    state.ball_dx = 5 * ONE;
+    state.ball_dy = 0;
+    let events = state.step(FrameInput::default());
+    assert_eq!(events, vec![StepEvent::Scored(Side::Player1), StepEvent::GameOver(Side::Player1)]);
+    assert_eq!((state.score_1, state.score_2), (4, 2));
+}
+
+#[test]
+fn resume_keeps_the_rules() {
+    let config = SimConfig { field_width: 1000, ..SimConfig::default() };
+    let state = GameState::resume_with(config, 1, 0, Side::Player2);
+    assert_eq!(state.config, config);
+    assert_eq!(state.ball_pixels()[0], 500);
+}
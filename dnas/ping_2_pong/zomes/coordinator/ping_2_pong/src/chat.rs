@@ -1,102 +1,314 @@
 use hdk::prelude::*;
+use hdk::x_salsa20_poly1305::{ed_25519_x_salsa20_poly1305_decrypt, ed_25519_x_salsa20_poly1305_encrypt};
 use crate::Signal;
-use ping_2_pong_integrity::{ChatMessage, EntryTypes, LinkTypes};
+use crate::game_index::{collect_bucket_links, collect_bucket_links_after, hour_bucket_path, IndexCursor};
+use ping_2_pong_integrity::{ChatChannel, ChatMessage, EntryTypes, LinkTypes};
+use ping_2_pong_integrity::chat_validation::reaction_emoji;
+use ping_2_pong_integrity::properties::dna_properties;
+use ping_2_pong_integrity::time_index::time_tag;
+use std::collections::BTreeMap;
 
+const LATEST_MESSAGES: u32 = 100;
+const MAX_PAGE_SIZE: u32 = 100;
+
+/// Input for `send_chat_message`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SendChatMessageInput {
+    pub channel: ChatChannel,
+    pub content: String,
+}
+
+/// Input for `get_chat_messages`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GetChatMessagesInput {
+    pub channel: ChatChannel,
+    pub before: Option<IndexCursor>, // Cursor: only messages ordered strictly before this one
+    pub limit: u32,
+}
+
+/// Input for `catch_up_chat`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CatchUpChatInput {
+    pub channel: Option<ChatChannel>, // None catches up on every channel from get_my_chat_channels
+    pub since: Timestamp,             // E.g. the newest message seen before going away; included
+    pub after: Option<IndexCursor>,   // Cursor from the previous page: only messages ordered strictly after it
+    pub limit: u32,
+}
+
+/// A page of missed messages, oldest first.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ChatCatchUpPage {
+    pub messages: Vec<ChatMessageView>,
+    pub next_cursor: Option<IndexCursor>, // Pass as `after` to fetch the next page
+}
+
+/// Input for `edit_chat_message`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct EditChatMessageInput {
+    pub message_hash: ActionHash,
+    pub content: String,
+}
+
+/// Input for `add_chat_reaction` and `remove_chat_reaction`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ChatReactionInput {
+    pub message_hash: ActionHash,
+    pub emoji: String,
+}
+
+/// How many agents reacted to a message with one emoji.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ReactionCount {
+    pub emoji: String,
+    pub count: u32,
+    pub reacted_by_me: bool,
+}
+
+/// A chat message as shown: its latest edit, or a tombstone once deleted.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ChatMessageView {
+    pub message_hash: ActionHash, // Original ChatMessage ActionHash
+    pub channel: ChatChannel,
+    pub sender: AgentPubKey,
+    pub timestamp: Timestamp,         // When it was first sent
+    pub content: String,              // Empty once deleted
+    pub edited_at: Option<Timestamp>, // Time of the latest edit
+    pub deleted: bool,
+    pub reactions: Vec<ReactionCount>,
+}
+
+/// A page of chat messages, oldest first so it can be prepended to what is shown.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ChatMessagesPage {
+    pub messages: Vec<ChatMessageView>,
+    pub next_cursor: Option<IndexCursor>, // Pass as `before` to fetch older messages
+}
+
+/// Posts to the global room. Kept for callers from before channels existed.
 #[hdk_extern]
 pub fn send_global_chat_message(content: String) -> ExternResult<()> {
-    let _ = crate::signals::grant_remote_signal_cap();
-    let my_agent_info = agent_info()?;
-    let my_pub_key = my_agent_info.agent_initial_pubkey.clone();
+    send_chat_message(SendChatMessageInput { channel: ChatChannel::Global, content })
+}
+
+/// Posts a message to a channel and signals it to the channel's members:
+/// recently present agents for the global room, the players and spectators for
+/// a game, and the other agent for a direct message. Delivery is fire-and-forget,
+/// so this returns right away; agents who were away use `catch_up_chat`.
+#[hdk_extern]
+pub fn send_chat_message(mut input: SendChatMessageInput) -> ExternResult<()> {
+    if let ChatChannel::Direct(a, b) = input.channel {
+        input.channel = ChatChannel::direct(a, b);
+    }
+    let max_length = dna_properties()?.max_chat_message_length;
+    if input.content.chars().count() > max_length {
+        return Err(wasm_error!(WasmErrorInner::Guest(format!("Chat messages are limited to {} characters", max_length))));
+    }
+    let my_pub_key = agent_info()?.agent_initial_pubkey;
     let now_timestamp = sys_time()?;
+    let recipients = channel_members(&input.channel)?;
+    if !matches!(input.channel, ChatChannel::Global) && !recipients.contains(&my_pub_key) {
+        return Err(wasm_error!(WasmErrorInner::Guest("You are not a member of this chat channel".into())));
+    }
 
     // 1. Persist ChatMessage entry on DHT chain
-    let chat_entry = ChatMessage {
+    let chat_entry = seal(ChatMessage {
         sender: my_pub_key.clone(),
-        content: content.clone(),
+        content: input.content.clone(),
         timestamp: now_timestamp,
-    };
+        channel: input.channel.clone(),
+        sealed: None,
+    })?;
     let action_hash = create_entry(&EntryTypes::ChatMessage(chat_entry))?;
+    let bucket = hour_bucket_path(&input.channel.history_root(), now_timestamp, LinkTypes::ChatTimePath)?;
+    bucket.ensure()?;
+    create_link(bucket.path_entry_hash()?, action_hash.clone(), LinkTypes::TimeBucketToChatMessage, time_tag(now_timestamp))?;
+    // List a new direct conversation for both agents.
+    if let ChatChannel::Direct(a, b) = &input.channel {
+        let other = if *a == my_pub_key { b } else { a };
+        if get_direct_chat_partners(my_pub_key.clone())?.iter().all(|partner| partner != other) {
+            create_link(my_pub_key.clone(), other.clone(), LinkTypes::AgentToDirectChats, ())?;
+            create_link(other.clone(), my_pub_key.clone(), LinkTypes::AgentToDirectChats, ())?;
+        }
+    }
 
-    let anchor_path = Path::from("all_chat_messages");
-    let anchor_hash = anchor_path.path_entry_hash()?;
-    create_link(
-        anchor_hash,
-        action_hash,
-        LinkTypes::AllChatMessagesAnchorToMessage,
-        (),
-    )?;
-
-    // 2. Prepare signal for real-time delivery
-    let signal = Signal::GlobalChatMessage {
+    // 2. Deliver in real time
+    let signal = Signal::ChatMessage {
+        channel: input.channel,
+        message_hash: action_hash,
         timestamp: now_timestamp,
-        sender: my_pub_key.clone(),
-        content,
+        sender: my_pub_key,
+        content: input.content,
     };
+    broadcast_chat_signal(recipients, &signal)
+}
 
-    // Emit locally for sender's UI
-    emit_signal(&signal)?;
+/// Replaces the content of one of the caller's messages.
+#[hdk_extern]
+pub fn edit_chat_message(input: EditChatMessageInput) -> ExternResult<ChatMessageView> {
+    let max_length = dna_properties()?.max_chat_message_length;
+    if input.content.chars().count() > max_length {
+        return Err(wasm_error!(WasmErrorInner::Guest(format!("Chat messages are limited to {} characters", max_length))));
+    }
+    let message = get_own_live_message(&input.message_hash)?;
+    let channel = message.channel.clone();
+    update_entry(input.message_hash.clone(), &seal(ChatMessage { content: input.content, sealed: None, ..message })?)?;
+    notify_message_changed(&channel, &input.message_hash)?;
+    get_chat_message(input.message_hash)?
+        .ok_or(wasm_error!(WasmErrorInner::Guest("Edited chat message could not be read back".into())))
+}
 
-    let signal_io = ExternIO::encode(&signal).map_err(|e| wasm_error!(WasmErrorInner::Guest(e.to_string())))?;
+/// Deletes one of the caller's messages; it stays in the history as a tombstone.
+#[hdk_extern]
+pub fn delete_chat_message(message_hash: ActionHash) -> ExternResult<()> {
+    let message = get_own_live_message(&message_hash)?;
+    delete_entry(message_hash.clone())?;
+    notify_message_changed(&message.channel, &message_hash)
+}
 
-    // 3. Get all online & registered players for P2P delivery
-    let mut recipients = crate::game::get_online_users(())?;
-    if let Ok(all_players) = crate::player::get_all_player_pubkeys(()) {
-        for agent_pubkey in all_players {
-            if !recipients.contains(&agent_pubkey) {
-                recipients.push(agent_pubkey);
-            }
-        }
+/// Reacts to a message with an emoji. Reacting twice with the same emoji does nothing.
+#[hdk_extern]
+pub fn add_chat_reaction(input: ChatReactionInput) -> ExternResult<()> {
+    let tag = LinkTag::new(input.emoji.as_bytes());
+    if reaction_emoji(&tag).is_none() {
+        return Err(wasm_error!(WasmErrorInner::Guest("A reaction must be a single emoji".into())));
+    }
+    let me = agent_info()?.agent_initial_pubkey;
+    let message = get_message(&input.message_hash)?;
+    if message.channel != ChatChannel::Global && !channel_members(&message.channel)?.contains(&me) {
+        return Err(wasm_error!(WasmErrorInner::Guest("You are not a member of this chat channel".into())));
     }
+    if !my_reaction_links(&me, &input)?.is_empty() {
+        return Ok(());
+    }
+    create_link(me.clone(), input.message_hash.clone(), LinkTypes::Reaction, tag.clone())?;
+    create_link(input.message_hash.clone(), me, LinkTypes::ChatMessageToReactions, tag)?;
+    notify_message_changed(&message.channel, &input.message_hash)
+}
 
-    // 4. Send to all other players via call_remote
-    for target_agent_key in recipients {
-        if target_agent_key != my_pub_key {
-            let mut attempt = 0;
-            while attempt < 3 {
-                match call_remote(
-                    target_agent_key.clone(),
-                    "ping_2_pong",
-                    "receive_remote_signal".into(),
-                    None,
-                    signal_io.clone()
-                ) {
-                    Ok(ZomeCallResponse::Ok(_io)) => {
-                        info!("Successfully sent global chat message to {:?}", target_agent_key);
-                        break;
-                    }
-                    Ok(ZomeCallResponse::Unauthorized(_cell_id, _zome, _func, _agent)) => {
-                        warn!("Unauthorized remote call to {:?}: cap grant missing", target_agent_key);
-                        break;
-                    }
-                    Ok(ZomeCallResponse::NetworkError(err)) => {
-                        warn!("Attempt {} network error calling {:?}: {}", attempt + 1, target_agent_key, err);
-                        attempt += 1;
-                    }
-                    Err(e) => {
-                        warn!("Attempt {} failed to send global chat message to {:?}: {:?}", attempt + 1, target_agent_key, e);
-                        attempt += 1;
-                    }
-                    _ => { break; }
-                }
-            }
+/// Takes back the caller's reaction with this emoji.
+#[hdk_extern]
+pub fn remove_chat_reaction(input: ChatReactionInput) -> ExternResult<()> {
+    let me = agent_info()?.agent_initial_pubkey;
+    let message = get_message(&input.message_hash)?;
+    for link in my_reaction_links(&me, &input)? {
+        delete_link(link.create_link_hash, GetOptions::default())?;
+    }
+    let reactions = get_links(
+        LinkQuery::try_new(input.message_hash.clone(), LinkTypes::ChatMessageToReactions)?,
+        GetStrategy::default(),
+    )?;
+    for link in reactions {
+        if link.author == me && link.tag.0 == input.emoji.as_bytes() {
+            delete_link(link.create_link_hash, GetOptions::default())?;
         }
     }
-    Ok(())
+    notify_message_changed(&message.channel, &input.message_hash)
 }
 
+/// One message as currently shown, or None if it is hidden, filtered or not a chat message.
 #[hdk_extern]
-pub fn get_latest_chat_messages(_: ()) -> ExternResult<Vec<Signal>> {
-    let anchor_path = Path::from("all_chat_messages");
-    let anchor_hash = anchor_path.path_entry_hash()?;
+pub fn get_chat_message(message_hash: ActionHash) -> ExternResult<Option<ChatMessageView>> {
+    Ok(get_message_views(None, vec![message_hash])?.pop())
+}
 
-    let links = get_links(
-        LinkQuery::try_new(anchor_hash, LinkTypes::AllChatMessagesAnchorToMessage)?,
-        GetStrategy::default(),
+/// Messages posted since `since`, oldest first and a page at a time, for agents
+/// who missed the real-time signals while offline. Messages sent at exactly
+/// `since` are included again, so the UI drops the ones it already shows.
+#[hdk_extern]
+pub fn catch_up_chat(input: CatchUpChatInput) -> ExternResult<ChatCatchUpPage> {
+    let limit = input.limit.clamp(1, MAX_PAGE_SIZE) as usize;
+    let channels = match input.channel {
+        Some(ChatChannel::Direct(a, b)) => vec![ChatChannel::direct(a, b)],
+        Some(channel) => vec![channel],
+        None => get_my_chat_channels(())?,
+    };
+    // Each channel is read up to the limit; the oldest `limit` of them form the page.
+    let mut positions: Vec<IndexCursor> = Vec::new();
+    let mut has_more = false;
+    for channel in channels {
+        let links = collect_bucket_links_after(
+            &channel.history_root(),
+            LinkTypes::ChatTimePath,
+            LinkTypes::TimeBucketToChatMessage,
+            input.since,
+            input.after.as_ref(),
+            Some(limit),
+        )?;
+        has_more |= links.len() == limit;
+        positions.extend(links.iter().filter_map(IndexCursor::of));
+    }
+    positions.sort();
+    has_more |= positions.len() > limit;
+    positions.truncate(limit);
+
+    let next_cursor = if has_more { positions.last().cloned() } else { None };
+    // Links are only valid in their own channel's buckets, so the channel is already known.
+    let hashes = positions.into_iter().map(|position| position.hash).collect();
+    Ok(ChatCatchUpPage { messages: get_message_views(None, hashes)?, next_cursor })
+}
+
+/// The latest (up to 100) messages of a channel, oldest first, without hidden,
+/// blocked or muted ones.
+#[hdk_extern]
+pub fn get_latest_chat_messages(channel: ChatChannel) -> ExternResult<Vec<ChatMessageView>> {
+    let page = get_chat_messages(GetChatMessagesInput { channel, before: None, limit: LATEST_MESSAGES })?;
+    Ok(page.messages)
+}
+
+/// Pages backwards through a channel's history for infinite scroll. Only the hour
+/// buckets up to the cursor are read. Hidden, blocked and muted messages are left
+/// out, so a page can be shorter than `limit`.
+#[hdk_extern]
+pub fn get_chat_messages(input: GetChatMessagesInput) -> ExternResult<ChatMessagesPage> {
+    let limit = input.limit.clamp(1, MAX_PAGE_SIZE) as usize;
+    let links = collect_bucket_links(
+        &input.channel.history_root(),
+        LinkTypes::ChatTimePath,
+        LinkTypes::TimeBucketToChatMessage,
+        input.before.as_ref(),
+        Some(limit),
     )?;
 
-    let get_inputs: Vec<GetInput> = links
+    let next_cursor = if links.len() == limit { links.last().and_then(IndexCursor::of) } else { None };
+    let hashes = links.into_iter().filter_map(|link| link.target.into_action_hash()).collect();
+    let mut messages = get_message_views(Some(&input.channel), hashes)?;
+    messages.reverse();
+    Ok(ChatMessagesPage { messages, next_cursor })
+}
+
+/// Channels the caller can chat in: the global room and their direct conversations.
+/// Game channels are reached through the game itself (`ChatChannel::Game`).
+#[hdk_extern]
+pub fn get_my_chat_channels(_: ()) -> ExternResult<Vec<ChatChannel>> {
+    let me = agent_info()?.agent_initial_pubkey;
+    let mut channels = vec![ChatChannel::Global];
+    for partner in get_direct_chat_partners(me.clone())? {
+        channels.push(ChatChannel::direct(me.clone(), partner));
+    }
+    Ok(channels)
+}
+
+/// Agents the given agent has a direct conversation with.
+#[hdk_extern]
+pub fn get_direct_chat_partners(agent: AgentPubKey) -> ExternResult<Vec<AgentPubKey>> {
+    let links = get_links(LinkQuery::try_new(agent, LinkTypes::AgentToDirectChats)?, GetStrategy::default())?;
+    let mut partners: Vec<AgentPubKey> = Vec::new();
+    for partner in links.into_iter().filter_map(|link| link.target.into_agent_pub_key()) {
+        if !partners.contains(&partner) {
+            partners.push(partner);
+        }
+    }
+    Ok(partners)
+}
+
+// --- Helpers ---
+
+// Builds the views of the given messages (keeping their order), leaving out
+// messages of other channels, hidden by a moderator or sent by agents the caller
+// filters. Messages, latest edits and reactions are each fetched in one batch.
+fn get_message_views(channel: Option<&ChatChannel>, hashes: Vec<ActionHash>) -> ExternResult<Vec<ChatMessageView>> {
+    let get_inputs: Vec<GetInput> = hashes
         .into_iter()
-        .filter_map(|link| link.target.into_action_hash())
         .map(|ah| GetInput::new(ah.into(), GetOptions::default()))
         .collect();
 
@@ -104,29 +316,235 @@ pub fn get_latest_chat_messages(_: ()) -> ExternResult<Vec<Signal>> {
         return Ok(vec![]);
     }
 
-    let records = HDK.with(|hdk| hdk.borrow().get(get_inputs))?;
-    let mut messages: Vec<Signal> = Vec::new();
+    let me = agent_info()?.agent_initial_pubkey;
+    let hidden = crate::moderation::get_hidden_chat_messages()?;
+    let filters = crate::moderation::get_chat_filters(())?;
+    let all_details = HDK.with(|hdk| hdk.borrow().get_details(get_inputs))?;
 
-    for record in records.into_iter().flatten() {
-        if let Ok(Some(chat_entry)) = record.entry().to_app_option::<ChatMessage>() {
-            messages.push(Signal::GlobalChatMessage {
-                timestamp: chat_entry.timestamp,
-                sender: chat_entry.sender,
-                content: chat_entry.content,
+    // 1. The visible messages, with their latest edit and whether they were deleted
+    let mut shown: Vec<(ActionHash, ChatMessage, String, Option<(ActionHash, Timestamp)>, bool)> = Vec::new();
+    for details in all_details.into_iter().flatten() {
+        let Details::Record(details) = details else {
+            continue;
+        };
+        let message_hash = details.record.action_address().clone();
+        if hidden.contains(&message_hash) || !matches!(details.record.action(), Action::Create(_)) {
+            continue;
+        }
+        let Ok(Some(chat_entry)) = details.record.entry().to_app_option::<ChatMessage>() else {
+            continue;
+        };
+        // Sealed direct messages only open for their two agents.
+        let Some(content) = open(&chat_entry, &me)? else {
+            continue;
+        };
+        // Keep to the channel being read, when there is one.
+        if channel.is_some_and(|channel| chat_entry.channel != *channel) || filters.hides_chat_from(&chat_entry.sender) {
+            continue;
+        }
+        let deleted = !details.deletes.is_empty();
+        let latest_edit = details
+            .updates
+            .iter()
+            .max_by_key(|update| update.action().timestamp())
+            .map(|edit| (edit.hashed.hash.clone(), edit.action().timestamp()));
+        shown.push((message_hash, chat_entry, content, latest_edit, deleted));
+    }
+
+    // 2. Latest edits of the live messages
+    let edit_inputs: Vec<GetInput> = shown
+        .iter()
+        .filter(|(_, _, _, _, deleted)| !deleted)
+        .filter_map(|(_, _, _, edit, _)| edit.as_ref())
+        .map(|(edit_hash, _)| GetInput::new(edit_hash.clone().into(), GetOptions::default()))
+        .collect();
+    let edits: Vec<Record> = if edit_inputs.is_empty() {
+        vec![]
+    } else {
+        HDK.with(|hdk| hdk.borrow().get(edit_inputs))?.into_iter().flatten().collect()
+    };
+
+    // 3. Reactions to the live messages
+    let live: Vec<&ActionHash> = shown.iter().filter(|(_, _, _, _, deleted)| !deleted).map(|(hash, ..)| hash).collect();
+    let reaction_inputs = live
+        .iter()
+        .map(|hash| Ok(GetLinksInputBuilder::try_new((*hash).clone(), LinkTypes::ChatMessageToReactions)?.build()))
+        .collect::<ExternResult<Vec<GetLinksInput>>>()?;
+    let mut reaction_links = if reaction_inputs.is_empty() {
+        vec![]
+    } else {
+        HDK.with(|hdk| hdk.borrow().get_links(reaction_inputs))?
+    }
+    .into_iter();
+
+    let mut views: Vec<ChatMessageView> = Vec::new();
+    for (message_hash, chat_entry, mut content, latest_edit, deleted) in shown {
+        let mut edited_at = None;
+        let mut reactions = vec![];
+        if !deleted {
+            let edit = latest_edit.and_then(|(edit_hash, at)| {
+                edits.iter().find(|record| *record.action_address() == edit_hash).map(|record| (record, at))
             });
+            if let Some((record, at)) = edit {
+                if let Ok(Some(edited)) = record.entry().to_app_option::<ChatMessage>() {
+                    if let Some(edited_content) = open(&edited, &me)? {
+                        content = edited_content;
+                        edited_at = Some(at);
+                    }
+                }
+            }
+            // Reaction link lists come back in the order of the live messages.
+            reactions = reaction_counts(reaction_links.next().unwrap_or_default(), &me);
         }
+        views.push(ChatMessageView {
+            message_hash,
+            channel: chat_entry.channel,
+            sender: chat_entry.sender,
+            timestamp: chat_entry.timestamp,
+            content: if deleted { String::new() } else { content },
+            edited_at,
+            deleted,
+            reactions,
+        });
     }
+    Ok(views)
+}
 
-    // Sort messages by timestamp ascending
-    messages.sort_by_key(|m| match m {
-        Signal::GlobalChatMessage { timestamp, .. } => *timestamp,
-        _ => Timestamp::from_micros(0),
-    });
+// Encrypts the content of a direct message between its two agents; other
+// messages stay plain text.
+fn seal(mut message: ChatMessage) -> ExternResult<ChatMessage> {
+    let ChatChannel::Direct(a, b) = &message.channel else {
+        return Ok(message);
+    };
+    let recipient = if *a == message.sender { b.clone() } else { a.clone() };
+    let content = std::mem::take(&mut message.content);
+    message.sealed = Some(ed_25519_x_salsa20_poly1305_encrypt(
+        message.sender.clone(),
+        recipient,
+        XSalsa20Poly1305Data::from(content.into_bytes()),
+    )?);
+    Ok(message)
+}
 
-    // Retain up to the latest 100 messages
-    if messages.len() > 100 {
-        messages = messages.split_off(messages.len() - 100);
+// The readable content of a message, or None if it is sealed and `me` is not one
+// of its two agents (or it cannot be opened).
+fn open(message: &ChatMessage, me: &AgentPubKey) -> ExternResult<Option<String>> {
+    let Some(sealed) = &message.sealed else {
+        return Ok(Some(message.content.clone()));
+    };
+    let ChatChannel::Direct(a, b) = &message.channel else {
+        return Ok(None);
+    };
+    // Both agents derive the same box key: their own secret key and the other's public key.
+    let other = if a == me { b } else if b == me { a } else { return Ok(None) };
+    match ed_25519_x_salsa20_poly1305_decrypt(me.clone(), other.clone(), sealed.clone()) {
+        Ok(data) => Ok(String::from_utf8(data.as_ref().to_vec()).ok()),
+        Err(e) => {
+            warn!("[chat.rs] open: Could not open a direct message: {:?}", e);
+            Ok(None)
+        }
+    }
+}
+
+// Reactions to a message, from its ChatMessageToReactions links, grouped by emoji
+// and counting each agent once per emoji.
+fn reaction_counts(links: Vec<Link>, me: &AgentPubKey) -> Vec<ReactionCount> {
+    let mut by_emoji: BTreeMap<String, Vec<AgentPubKey>> = BTreeMap::new();
+    for link in links {
+        let (Some(emoji), Some(agent)) = (reaction_emoji(&link.tag), link.target.into_agent_pub_key()) else {
+            continue;
+        };
+        let agents = by_emoji.entry(emoji).or_default();
+        if !agents.contains(&agent) {
+            agents.push(agent);
+        }
+    }
+    by_emoji
+        .into_iter()
+        .map(|(emoji, agents)| ReactionCount {
+            emoji,
+            count: agents.len() as u32,
+            reacted_by_me: agents.contains(me),
+        })
+        .collect()
+}
+
+// The caller's Reaction links to the message with the given emoji.
+fn my_reaction_links(me: &AgentPubKey, input: &ChatReactionInput) -> ExternResult<Vec<Link>> {
+    let links = get_links(LinkQuery::try_new(me.clone(), LinkTypes::Reaction)?, GetStrategy::default())?;
+    Ok(links
+        .into_iter()
+        .filter(|link| link.target.clone().into_action_hash().as_ref() == Some(&input.message_hash))
+        .filter(|link| link.tag.0 == input.emoji.as_bytes())
+        .collect())
+}
+
+fn get_message(message_hash: &ActionHash) -> ExternResult<ChatMessage> {
+    let record = get(message_hash.clone(), GetOptions::default())?
+        .ok_or(wasm_error!(WasmErrorInner::Guest(format!("Chat message not found: {}", message_hash))))?;
+    if !matches!(record.action(), Action::Create(_)) {
+        return Err(wasm_error!(WasmErrorInner::Guest("Expected the original chat message, not an edit".into())));
+    }
+    record.entry().to_app_option::<ChatMessage>()
+        .map_err(|e| wasm_error!(WasmErrorInner::Serialize(e)))?
+        .ok_or(wasm_error!(WasmErrorInner::Guest("Invalid ChatMessage entry format".into())))
+}
+
+// The caller's own message, provided it has not been deleted.
+fn get_own_live_message(message_hash: &ActionHash) -> ExternResult<ChatMessage> {
+    let message = get_message(message_hash)?;
+    if message.sender != agent_info()?.agent_initial_pubkey {
+        return Err(wasm_error!(WasmErrorInner::Guest("You can only change your own chat messages".into())));
+    }
+    if let Some(Details::Record(details)) = get_details(message_hash.clone(), GetOptions::default())? {
+        if !details.deletes.is_empty() {
+            return Err(wasm_error!(WasmErrorInner::Guest("This chat message was deleted".into())));
+        }
     }
+    Ok(message)
+}
 
-    Ok(messages)
+// Tells the channel's members to refetch a message.
+fn notify_message_changed(channel: &ChatChannel, message_hash: &ActionHash) -> ExternResult<()> {
+    let signal = Signal::ChatMessageChanged {
+        channel: channel.clone(),
+        message_hash: message_hash.clone(),
+        changed_by: agent_info()?.agent_initial_pubkey,
+    };
+    broadcast_chat_signal(channel_members(channel)?, &signal)
+}
+
+// Emits a chat signal locally and sends it to every other member in one
+// fire-and-forget `send_remote_signal`, without waiting for anyone.
+fn broadcast_chat_signal(recipients: Vec<AgentPubKey>, signal: &Signal) -> ExternResult<()> {
+    let my_pub_key = agent_info()?.agent_initial_pubkey;
+    emit_signal(signal)?;
+    let others: Vec<AgentPubKey> = recipients.into_iter().filter(|agent| *agent != my_pub_key).collect();
+    crate::signals::send_realtime_signal(others, signal)
+}
+
+// Everyone a message in the channel is signalled to (including the sender).
+// The global room only reaches recently present agents; the rest catch up later.
+fn channel_members(channel: &ChatChannel) -> ExternResult<Vec<AgentPubKey>> {
+    let mut members: Vec<AgentPubKey> = Vec::new();
+    match channel {
+        ChatChannel::Global => {
+            members = crate::game::get_online_users(())?;
+        }
+        ChatChannel::Game(game_id) => {
+            let (_, game) = crate::game::get_latest_game_entry(game_id)?;
+            members.push(game.player_1);
+            members.extend(game.player_2);
+            for spectator in crate::spectators::get_spectators(game_id.clone())? {
+                if !members.contains(&spectator) {
+                    members.push(spectator);
+                }
+            }
+        }
+        ChatChannel::Direct(a, b) => {
+            members.push(a.clone());
+            members.push(b.clone());
+        }
+    }
+    Ok(members)
 }
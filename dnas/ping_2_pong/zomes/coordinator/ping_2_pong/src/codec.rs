@@ -0,0 +1,44 @@
+// ping_2_pong/dnas/ping_2_pong/zomes/coordinator/ping_2_pong/src/codec.rs
+//! Frame stream codec. The varint / zigzag encoding lives in `pong_sim::codec`;
+//! this reader surfaces its errors as `WasmError`s.
+use hdk::prelude::*;
+use pong_sim::codec::CodecError;
+
+pub use pong_sim::codec::ByteWriter;
+
+fn codec_error(e: CodecError) -> WasmError {
+    wasm_error!(WasmErrorInner::Guest(format!("Frame codec: {}", e)))
+}
+
+#[derive(Debug)]
+pub struct ByteReader<'a>(pong_sim::codec::ByteReader<'a>);
+
+impl<'a> ByteReader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self(pong_sim::codec::ByteReader::new(bytes))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn get_u8(&mut self) -> ExternResult<u8> {
+        self.0.get_u8().map_err(codec_error)
+    }
+
+    pub fn get_uvarint(&mut self) -> ExternResult<u64> {
+        self.0.get_uvarint().map_err(codec_error)
+    }
+
+    pub fn get_ivarint(&mut self) -> ExternResult<i64> {
+        self.0.get_ivarint().map_err(codec_error)
+    }
+
+    pub fn get_i32(&mut self) -> ExternResult<i32> {
+        self.0.get_i32().map_err(codec_error)
+    }
+
+    pub fn get_u32(&mut self) -> ExternResult<u32> {
+        self.0.get_u32().map_err(codec_error)
+    }
+}
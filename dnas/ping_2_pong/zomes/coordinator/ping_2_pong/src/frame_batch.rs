@@ -0,0 +1,237 @@
+// ping_2_pong/dnas/ping_2_pong/zomes/coordinator/ping_2_pong/src/frame_batch.rs
+//! Batched real-time frames.
+//!
+//! `send_frame_batch` packs several paddle, hit and ball events into one
+//! binary frame and streams it to the opponent as a single remote signal.
+//! Instead of the 39-byte game id the frame carries a 4-byte handle derived
+//! from it; the receiver resolves it against the games it holds a session
+//! claim from the sender for. Positions are delta-encoded within the batch, so
+//! every batch decodes on its own.
+//!
+//! A batch stands for the individual `PaddleUpdate` / `PaddleHit` / `BallUpdate`
+//! signals it packs: replays record it as those signals, and the host forwards
+//! its latest ball position to spectators like a single `BallUpdate`.
+use hdk::prelude::*;
+use ping_2_pong_integrity::game::NetcodeMode;
+use crate::codec::{ByteReader, ByteWriter};
+use crate::session::find_session_game_with;
+use crate::signals::{is_fresh, send_realtime, RealtimeMessage};
+use crate::Signal;
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+const TAG_PADDLE: u8 = 0;
+const TAG_HIT: u8 = 1;
+const TAG_BALL: u8 = 2;
+
+/// Most events accepted in one batch.
+pub const MAX_FRAME_BATCH_EVENTS: usize = 64;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "kind")]
+pub enum FrameEvent {
+    Paddle { paddle_y: i32 },
+    Hit { ball_y: i32 },
+    Ball { ball_x: i32, ball_y: i32, ball_dx: i32, ball_dy: i32, score1: Option<u32>, score2: Option<u32> },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FrameBatchPayload {
+    pub game_id: ActionHash,
+    pub recipient: AgentPubKey,
+    pub events: Vec<FrameEvent>, // Oldest first
+}
+
+thread_local! {
+    // Newest batch seq sent per game, and accepted per (sender, game). A batch
+    // supersedes the ones before it, so one arriving after a newer batch is dropped.
+    // Lives as long as the wasm instance, like the other real-time caches.
+    static LAST_SENT_SEQ: RefCell<HashMap<ActionHash, u64>> = RefCell::new(HashMap::new());
+    static LAST_ACCEPTED_SEQ: RefCell<HashMap<(AgentPubKey, ActionHash), u64>> = RefCell::new(HashMap::new());
+}
+
+/// Short per-session handle for a game: the first four bytes of its hash.
+pub fn game_handle(game_id: &ActionHash) -> u32 {
+    let raw = game_id.get_raw_32();
+    u32::from_le_bytes([raw[0], raw[1], raw[2], raw[3]])
+}
+
+/// Sends a batch of the caller's real-time events to the opponent in one call.
+#[hdk_extern]
+pub fn send_frame_batch(payload: FrameBatchPayload) -> ExternResult<()> {
+    if payload.events.is_empty() {
+        return Ok(());
+    }
+    if payload.events.len() > MAX_FRAME_BATCH_EVENTS {
+        return Err(wasm_error!(WasmErrorInner::Guest(format!(
+            "Too many events in one frame batch ({} max)", MAX_FRAME_BATCH_EVENTS
+        ))));
+    }
+    let has_ball = payload.events.iter().any(|event| matches!(event, FrameEvent::Ball { .. }));
+    if has_ball && crate::lockstep::get_netcode_mode(&payload.game_id)? == NetcodeMode::Lockstep {
+        return Err(wasm_error!(WasmErrorInner::Guest("Lockstep games exchange inputs, not ball updates".into())));
+    }
+    let me = agent_info()?.agent_initial_pubkey;
+    let seq = next_seq(&payload.game_id, sys_time()?.as_millis() as u64);
+    let data = encode_batch(game_handle(&payload.game_id), seq, &payload.events);
+    // Frames are superseded by the next batch, so delivery is fire-and-forget.
+    send_realtime(vec![payload.recipient], RealtimeMessage::FrameBatch(data))?;
+
+    // Spectators get the newest ball position (throttled like single updates).
+    let latest_ball = expand_batch(&payload.game_id, &me, &payload.events)
+        .into_iter()
+        .rfind(|signal| matches!(signal, Signal::BallUpdate { .. }));
+    if let Some(ball_update) = latest_ball {
+        if let Err(e) = crate::spectators::forward_to_spectators(&payload.game_id, &ball_update) {
+            warn!("[frame_batch.rs] send_frame_batch: Spectator fan-out failed: {:?}", e);
+        }
+    }
+    // Echo locally like the single-event senders, so the UI can record what it sent.
+    emit_signal(&Signal::FrameBatch { game_id: payload.game_id, player: me, seq, events: payload.events })
+}
+
+/// The individual real-time signals a batch from `player` stands for, oldest first.
+pub fn expand_batch(game_id: &ActionHash, player: &AgentPubKey, events: &[FrameEvent]) -> Vec<Signal> {
+    events
+        .iter()
+        .map(|event| match event {
+            FrameEvent::Paddle { paddle_y } => Signal::PaddleUpdate {
+                game_id: game_id.clone(),
+                player: player.clone(),
+                paddle_y: *paddle_y,
+            },
+            FrameEvent::Hit { ball_y } => Signal::PaddleHit {
+                game_id: game_id.clone(),
+                player: player.clone(),
+                ball_y: *ball_y,
+            },
+            FrameEvent::Ball { ball_x, ball_y, ball_dx, ball_dy, score1, score2 } => Signal::BallUpdate {
+                game_id: game_id.clone(),
+                ball_x: *ball_x,
+                ball_y: *ball_y,
+                ball_dx: *ball_dx,
+                ball_dy: *ball_dy,
+                score1: *score1,
+                score2: *score2,
+            },
+        })
+        .collect()
+}
+
+/// Unpacks a batch streamed by `sender` and emits it to the UI as `Signal::FrameBatch`.
+pub fn accept_frame_batch(sender: AgentPubKey, data: &[u8]) -> ExternResult<()> {
+    let reject = |reason: &str| Err(wasm_error!(WasmErrorInner::Guest(format!("Rejected frame batch from {:?}: {}", sender, reason))));
+
+    let (handle, seq, events) = decode_batch(data)?;
+    let Some(game_id) = find_session_game_with(&sender, |game_id| game_handle(game_id) == handle)? else {
+        return reject("no game session with the sender matches the handle");
+    };
+    if !is_fresh(seq as i64 * 1_000)? {
+        return reject("stale or future sequence number");
+    }
+    if !mark_accepted(&sender, &game_id, seq) {
+        debug!("[frame_batch.rs] accept_frame_batch: Dropped batch {} from {:?}, a newer one was already accepted", seq, sender);
+        return Ok(());
+    }
+
+    emit_signal(&Signal::FrameBatch { game_id, player: sender, seq, events })
+}
+
+// The sender's clock in ms, bumped past the previous batch for the game so that
+// two batches sent within the same millisecond still have increasing seqs.
+fn next_seq(game_id: &ActionHash, now_ms: u64) -> u64 {
+    LAST_SENT_SEQ.with(|last_sent| {
+        let mut last_sent = last_sent.borrow_mut();
+        let seq = last_sent.get(game_id).map_or(now_ms, |last| now_ms.max(last + 1));
+        last_sent.insert(game_id.clone(), seq);
+        seq
+    })
+}
+
+// Records `seq` as the newest batch from `sender` for the game; false if it is not
+// newer than the last one accepted.
+fn mark_accepted(sender: &AgentPubKey, game_id: &ActionHash, seq: u64) -> bool {
+    LAST_ACCEPTED_SEQ.with(|last_accepted| {
+        let mut last_accepted = last_accepted.borrow_mut();
+        let key = (sender.clone(), game_id.clone());
+        if last_accepted.get(&key).is_some_and(|last| seq <= *last) {
+            return false;
+        }
+        last_accepted.insert(key, seq);
+        true
+    })
+}
+
+// Layout: handle, seq (sender ms), count, then per event a tag and its fields.
+// Paddle and ball values are deltas against the previous event of the same kind.
+fn encode_batch(handle: u32, seq: u64, events: &[FrameEvent]) -> Vec<u8> {
+    let mut out = ByteWriter::new();
+    out.put_uvarint(handle as u64);
+    out.put_uvarint(seq);
+    out.put_uvarint(events.len() as u64);
+    let mut last_paddle = 0i32;
+    let mut last_ball = [0i32; 4];
+    for event in events {
+        match event {
+            FrameEvent::Paddle { paddle_y } => {
+                out.put_u8(TAG_PADDLE);
+                out.put_ivarint(*paddle_y as i64 - last_paddle as i64);
+                last_paddle = *paddle_y;
+            }
+            FrameEvent::Hit { ball_y } => {
+                out.put_u8(TAG_HIT);
+                out.put_ivarint(*ball_y as i64);
+            }
+            FrameEvent::Ball { ball_x, ball_y, ball_dx, ball_dy, score1, score2 } => {
+                out.put_u8(TAG_BALL);
+                let ball = [*ball_x, *ball_y, *ball_dx, *ball_dy];
+                for (value, last) in ball.iter().zip(last_ball.iter()) {
+                    out.put_ivarint(*value as i64 - *last as i64);
+                }
+                last_ball = ball;
+                out.put_u8(score1.is_some() as u8 | (score2.is_some() as u8) << 1);
+                for score in [score1, score2].into_iter().flatten() {
+                    out.put_uvarint(*score as u64);
+                }
+            }
+        }
+    }
+    out.into_bytes()
+}
+
+fn decode_batch(data: &[u8]) -> ExternResult<(u32, u64, Vec<FrameEvent>)> {
+    let mut reader = ByteReader::new(data);
+    let handle = reader.get_u32()?;
+    let seq = reader.get_uvarint()?;
+    let count = reader.get_uvarint()? as usize;
+    if count > MAX_FRAME_BATCH_EVENTS {
+        return Err(wasm_error!(WasmErrorInner::Guest("Frame batch has too many events".into())));
+    }
+    let mut events = Vec::with_capacity(count);
+    let mut last_paddle = 0i32;
+    let mut last_ball = [0i32; 4];
+    for _ in 0..count {
+        let event = match reader.get_u8()? {
+            TAG_PADDLE => {
+                last_paddle = (last_paddle as i64 + reader.get_ivarint()?) as i32;
+                FrameEvent::Paddle { paddle_y: last_paddle }
+            }
+            TAG_HIT => FrameEvent::Hit { ball_y: reader.get_i32()? },
+            TAG_BALL => {
+                for value in last_ball.iter_mut() {
+                    *value = (*value as i64 + reader.get_ivarint()?) as i32;
+                }
+                let flags = reader.get_u8()?;
+                let score1 = if flags & 1 != 0 { Some(reader.get_u32()?) } else { None };
+                let score2 = if flags & 2 != 0 { Some(reader.get_u32()?) } else { None };
+                let [ball_x, ball_y, ball_dx, ball_dy] = last_ball;
+                FrameEvent::Ball { ball_x, ball_y, ball_dx, ball_dy, score1, score2 }
+            }
+            other => {
+                return Err(wasm_error!(WasmErrorInner::Guest(format!("Unknown frame event tag {}", other))));
+            }
+        };
+        events.push(event);
+    }
+    Ok((handle, seq, events))
+}
@@ -1,46 +1,24 @@
 use hdk::prelude::*;
 use ping_2_pong_integrity::*;
 // Use GameStatus directly from integrity crate
-use ping_2_pong_integrity::game::GameStatus;
+use ping_2_pong_integrity::game::{GameStatus, NetcodeMode};
+use ping_2_pong_integrity::game_lifecycle::{ check_transition, check_delete, find_transition };
+use ping_2_pong_integrity::game_rules::{ check_rules, GameRules };
 // Import helpers from local utils module
 use crate::utils::{ player_exists, is_player_in_ongoing_game, anchor_for };
+use crate::session::establish_game_session;
+use crate::outbox::send_reliable;
+use crate::game_index::{ get_all_indexed_games, index_new_game, reindex_game_status, unindex_game };
 // Import Signal enum definition from local lib.rs
 use crate::Signal;
 
 // --- Extern Functions ---
 
-/// Fetches all game records linked from the global "games" anchor.
+/// Fetches every game record from the time-bucketed game index, newest first.
+/// Prefer `get_games_page` for the lobby; this walks the whole history.
 #[hdk_extern]
 pub fn get_all_games(_: ()) -> ExternResult<Vec<Record>> {
-    let games_anchor = anchor_for("games")?;
-    let links = get_links(
-        LinkQuery::try_new(games_anchor, LinkTypes::GameIdToGame)?,
-        GetStrategy::default(),
-    )?;
-
-    // Prepare inputs for a batch get operation
-    let get_inputs: Vec<GetInput> = links
-        .into_iter()
-        .filter_map(|link| {
-            // The target of GameIdToGame link is the ActionHash of the game creation
-            link.target.into_action_hash().map(|ah| GetInput::new(ah.into(), GetOptions::default()))
-        })
-        .collect();
-
-    if get_inputs.is_empty() {
-        return Ok(vec![]); // No games found
-    }
-
-    // Perform the batch get using HDK::with for efficiency
-    // Note: Error handling wraps the result in a WasmError
-    let records_result = HDK.with(|hdk| hdk.borrow().get(get_inputs));
-    let records = match records_result {
-      Ok(records) => records,
-      Err(e) => return Err(wasm_error!(WasmErrorInner::Guest(format!("Failed to get game records: {:?}", e))))
-    };
-
-    // Flatten the results (get returns Vec<Option<Record>>) and collect valid records
-    Ok(records.into_iter().flatten().collect())
+    get_all_indexed_games(None)
 }
 
 
@@ -67,6 +45,12 @@ pub fn get_player_status(player_pub_key: AgentPubKey) -> ExternResult<PlayerStat
 pub struct CreateGameInput {
     pub player_1: AgentPubKey,
     pub player_2: Option<AgentPubKey>, // Optional: Used for direct invitations
+    #[serde(default)]
+    pub netcode: NetcodeMode,
+    #[serde(default)] // Standard rules when omitted
+    pub rules: GameRules,
+    #[serde(default)] // Original Series ActionHash when the game belongs to a series
+    pub series: Option<ActionHash>,
 }
 
 
@@ -94,20 +78,25 @@ pub fn join_game(original_game_hash: ActionHash) -> ExternResult<Record> {
     let current_game = Game::try_from(entry)?;
     let player1_pubkey = current_game.player_1.clone(); // Store Player 1's key for signal sending
 
-    // 2. Validate if joining is allowed
-    if current_game.game_status != GameStatus::Waiting {
-        return Err(wasm_error!(WasmErrorInner::Guest(format!(
-            "Cannot join game: Game status is not 'Waiting', it's {:?}", current_game.game_status
-        ))));
-    }
-    // Check if Player 2 slot is already taken by someone else
-    if current_game.player_2.is_some() && current_game.player_2.as_ref() != Some(&caller_pubkey) {
-        return Err(wasm_error!(WasmErrorInner::Guest("Cannot join game: Player 2 slot is already taken by another player".into())));
-    }
-    // Player 1 cannot join their own game as Player 2
-    if player1_pubkey == caller_pubkey {
-        return Err(wasm_error!(WasmErrorInner::Guest("Cannot join game: Player 1 cannot join their own game as Player 2".into())));
-    }
+    // 2. Prepare the updated game state with Player 2 added and status changed
+    let updated_game = Game {
+        player_1: player1_pubkey.clone(),
+        player_2: Some(caller_pubkey.clone()),
+        game_status: GameStatus::InProgress, // Set status to InProgress
+        created_at: current_game.created_at,
+        player_1_paddle: current_game.player_1_paddle, // Keep default positions
+        player_2_paddle: current_game.player_2_paddle,
+        ball_x: current_game.ball_x,
+        ball_y: current_game.ball_y,
+        netcode: current_game.netcode,
+        rules: current_game.rules.clone(),
+        forfeit: None,
+        series: current_game.series.clone(),
+    };
+
+    // 3. Validate if joining is allowed (status, Player 2 slot, not joining own game)
+    check_transition(&current_game, &updated_game, &caller_pubkey)
+        .map_err(|e| wasm_error!(WasmErrorInner::Guest(format!("Cannot join game: {}", e))))?;
     // Check if the joining player is already in another active game
     if is_player_in_ongoing_game(&caller_pubkey)? {
          // Allow re-joining the *same* game if P2 was already set but status somehow remained Waiting
@@ -121,18 +110,6 @@ pub fn join_game(original_game_hash: ActionHash) -> ExternResult<Record> {
      }
 
 
-    // 3. Prepare the updated game state with Player 2 added and status changed
-    let updated_game = Game {
-        player_1: player1_pubkey.clone(),
-        player_2: Some(caller_pubkey.clone()),
-        game_status: GameStatus::InProgress, // Set status to InProgress
-        created_at: current_game.created_at,
-        player_1_paddle: current_game.player_1_paddle, // Keep default positions
-        player_2_paddle: current_game.player_2_paddle,
-        ball_x: current_game.ball_x,
-        ball_y: current_game.ball_y,
-    };
-
     // 4. Commit the update action to the DHT
     debug!("[join_game] Updating game entry {:?} to add player 2 and set status InProgress", previous_action_hash);
     let update_action_hash = update_entry(previous_action_hash.clone(), &updated_game)?;
@@ -157,6 +134,7 @@ pub fn join_game(original_game_hash: ActionHash) -> ExternResult<Record> {
         (),
     )?;
     debug!("[join_game] Created GameUpdates link from {:?} to {:?}", original_game_hash, update_action_hash);
+    reindex_game_status(&original_game_hash, &updated_game, &current_game.game_status)?;
 
     // 7. *** Emit GameStarted signal (Broadcast) ***
     //    This signal informs connected UIs that the game is ready to start.
@@ -171,27 +149,22 @@ pub fn join_game(original_game_hash: ActionHash) -> ExternResult<Record> {
     // 7. Broadcast locally (player 2) …
     emit_signal(&start_sig)?;
 
-    let start_sig_io = ExternIO::encode(&start_sig).map_err(|e| wasm_error!(WasmErrorInner::Guest(e.to_string())))?;
-
-    // 8. Relay to player 1 – synchronous RPC
-    call_remote(
-        player1_pubkey.clone(),          // destination agent
-        zome_info()?.name,               // current zome name
-        "receive_remote_signal".into(),  // the helper you just added
-        None,                            // provenance (cap secret)
-        start_sig_io                     // same payload
-    )?;
-
-    debug!("[join_game] Emitted GameStarted signal (broadcast): {:?}", start_sig);
-
-
-    // 8. Fetch and return the latest record (representing the update action)
+    // 8. Fetch the latest record (representing the update action)
     //    This confirms the update and provides the latest state to the caller (Player 2).
     let final_record = get(update_action_hash.clone(), GetOptions::default())?
         .ok_or(wasm_error!(WasmErrorInner::Guest(format!(
             "Could not find the updated Game record after join: {:?}", update_action_hash
         ))))?;
 
+    // 9. Open the per-game capability session with player 1, then relay the start signal under it.
+    //    If player 1 is unreachable the join still stands; `open_game_session` can retry later
+    //    and the outbox keeps retrying the start signal until then.
+    if let Err(e) = establish_game_session(&original_game_hash, &final_record) {
+        warn!("[join_game] Could not open game session with player 1: {:?}", e);
+    }
+    send_reliable(&player1_pubkey, Some(&original_game_hash), &start_sig)?;
+    debug!("[join_game] Emitted GameStarted signal (broadcast): {:?}", start_sig);
+
     Ok(final_record)
 }
 
@@ -227,18 +200,26 @@ pub fn create_game(input: CreateGameInput) -> ExternResult<Record> {
             return Err(wasm_error!(WasmErrorInner::Guest("Player 2 is already in an ongoing game".into())));
         }
     }
+    check_rules(&input.rules)
+        .map_err(|e| wasm_error!(WasmErrorInner::Guest(format!("Invalid game rules: {}", e))))?;
     // --- End Validations ---
 
     // Construct the initial Game entry state
+    let paddle_start = input.rules.paddle_start();
+    let (ball_x, ball_y) = input.rules.ball_start();
     let game = Game {
         player_1: input.player_1.clone(),
         player_2: input.player_2.clone(), // None if not invited, Some(pubkey) if invited
         created_at: sys_time()?,          // Set creation timestamp
         game_status: GameStatus::Waiting, // Always start as Waiting
-        player_1_paddle: 250,             // Default positions
-        player_2_paddle: 250,
-        ball_x: 400,
-        ball_y: 300,
+        player_1_paddle: paddle_start,    // Centred starting positions for the field
+        player_2_paddle: paddle_start,
+        ball_x,
+        ball_y,
+        netcode: input.netcode,
+        rules: input.rules,
+        forfeit: None,
+        series: input.series,
     };
     debug!("[create_game] Constructed game entry: {:?}", game);
 
@@ -253,9 +234,8 @@ pub fn create_game(input: CreateGameInput) -> ExternResult<Record> {
     if let Some(player2) = game.player_2.clone() {
         create_link(player2, game_action_hash.clone(), LinkTypes::Player2ToGames, (),)?;
     }
-    // Link from the global "games" anchor to the game (for discoverability)
-    let games_anchor_hash = anchor_for("games")?;
-    create_link(games_anchor_hash, game_action_hash.clone(), LinkTypes::GameIdToGame, (),)?;
+    // Index the game under its creation hour, in the "all games" and "waiting" trees (for discoverability)
+    index_new_game(&game_action_hash, &game)?;
     debug!("[create_game] Links created successfully.");
 
     // Fetch and return the created record
@@ -266,6 +246,22 @@ pub fn create_game(input: CreateGameInput) -> ExternResult<Record> {
 }
 
 
+/// The other player of a game, if there is one yet.
+pub fn get_opponent(game_id: &ActionHash, me: &AgentPubKey) -> ExternResult<Option<AgentPubKey>> {
+    let Some(record) = get_latest_game(game_id.clone())? else { return Ok(None) };
+    Ok(opponent_of(&game_from_record(&record)?, me))
+}
+
+fn opponent_of(game: &Game, me: &AgentPubKey) -> Option<AgentPubKey> {
+    if game.player_1 == *me {
+        game.player_2.clone()
+    } else if game.player_2.as_ref() == Some(me) {
+        Some(game.player_1.clone())
+    } else {
+        None
+    }
+}
+
 /// Retrieves the latest version of a game record, following the GameUpdates links.
 #[hdk_extern]
 pub fn get_latest_game(original_game_hash: ActionHash) -> ExternResult<Option<Record>> {
@@ -305,6 +301,22 @@ pub fn get_latest_game(original_game_hash: ActionHash) -> ExternResult<Option<Re
     result
 }
 
+/// The latest revision of a game: its record's ActionHash and the decoded Game.
+pub fn get_latest_game_entry(game_id: &ActionHash) -> ExternResult<(ActionHash, Game)> {
+    let record = get_latest_game(game_id.clone())?
+        .ok_or(wasm_error!(WasmErrorInner::Guest(format!("Game does not exist: {}", game_id))))?;
+    Ok((record.action_address().clone(), game_from_record(&record)?))
+}
+
+/// Decodes the Game entry of a game record.
+pub fn game_from_record(record: &Record) -> ExternResult<Game> {
+    record
+        .entry()
+        .to_app_option::<Game>()
+        .map_err(|e| wasm_error!(WasmErrorInner::Serialize(e)))?
+        .ok_or(wasm_error!(WasmErrorInner::Guest("Invalid Game entry format".into())))
+}
+
 /// Retrieves the original record of a game creation action.
 #[hdk_extern]
 pub fn get_original_game(original_game_hash: ActionHash) -> ExternResult<Option<Record>> {
@@ -381,8 +393,11 @@ pub fn finish_game(input: FinishGameInput) -> ExternResult<Record> {
         _ => return Err(wasm_error!(WasmErrorInner::Guest("Expected Record for game details".to_string()))),
     };
     let entry = record.entry().as_option().ok_or(wasm_error!(WasmErrorInner::Guest("Game record has no entry".to_string())))?.clone();
-    let mut game = <Game>::try_from(entry)?;
+    let previous_game = <Game>::try_from(entry)?;
+    let mut game = previous_game.clone();
     game.game_status = GameStatus::Finished;
+    check_transition(&previous_game, &game, &agent_info()?.agent_initial_pubkey)
+        .map_err(|e| wasm_error!(WasmErrorInner::Guest(format!("Cannot finish game: {}", e))))?;
 
     let updated_action_hash = update_entry(input.previous_game_hash.clone(), &game)?;
     create_link(
@@ -391,6 +406,11 @@ pub fn finish_game(input: FinishGameInput) -> ExternResult<Record> {
         LinkTypes::GameUpdates,
         (),
     )?;
+    reindex_game_status(&input.original_game_hash, &game, &previous_game.game_status)?;
+    // Publish this player's replay if they opted into recording; never blocks finishing.
+    if let Err(e) = crate::replay::commit_replay(input.original_game_hash.clone()) {
+        warn!("[game.rs] finish_game: Could not publish replay: {:?}", e);
+    }
 
     let updated_record = get(updated_action_hash, GetOptions::default())?.ok_or(wasm_error!(
         WasmErrorInner::Guest("Could not find the updated Game record".to_string())
@@ -408,10 +428,17 @@ pub struct UpdateGameInput {
 }
 
 /// Updates a game entry. Creates a new action and links it to the original game via GameUpdates.
+/// The update is not pre-checked here: `validate_update_game` rejects illegal
+/// transitions when the action is committed (see tests/game_validation.rs).
 #[hdk_extern]
 pub fn update_game(input: UpdateGameInput) -> ExternResult<Record> {
     debug!("[game.rs] update_game: Called with input: {:?}", input);
 
+    // Read the status being replaced so the status index can follow the update
+    let previous_record = get(input.previous_game_hash.clone(), GetOptions::default())?
+        .ok_or(wasm_error!(WasmErrorInner::Guest("Previous Game record not found".to_string())))?;
+    let previous_game = game_from_record(&previous_record)?;
+
     // Commit the update action, referencing the previous action hash
     let updated_action_hash = match update_entry(input.previous_game_hash.clone(), &input.updated_game) {
         Ok(hash) => {
@@ -442,6 +469,8 @@ pub fn update_game(input: UpdateGameInput) -> ExternResult<Record> {
         }
     };
 
+    reindex_game_status(&input.original_game_hash, &input.updated_game, &previous_game.game_status)?;
+
     // Fetch and return the newly created update record
     let record = get(updated_action_hash.clone(), GetOptions::default())?.ok_or(wasm_error!(
         WasmErrorInner::Guest("Could not find the newly updated Game record".to_string())
@@ -465,25 +494,21 @@ pub fn delete_game(original_game_hash: ActionHash) -> ExternResult<ActionHash> {
     let entry = record.entry().as_option().ok_or(wasm_error!(WasmErrorInner::Guest("Game record has no entry".to_string())))?.clone();
     let game = <Game>::try_from(entry)?;
 
-    // --- Validation: Only allow deleting 'Waiting' games ---
-    // This check prevents deleting games that are in progress or finished.
-    if game.game_status != GameStatus::Waiting {
-         return Err(wasm_error!(WasmErrorInner::Guest("Only games in 'Waiting' status can be deleted".to_string())));
-    }
+    // --- Validation: Only participants may delete, and only 'Waiting' games ---
+    check_delete(&game, &agent_info()?.agent_initial_pubkey)
+        .map_err(|e| wasm_error!(WasmErrorInner::Guest(e)))?;
 
     // --- Link Deletion ---
     // Delete links from Player 1 to this game
     let links1 = get_links( LinkQuery::try_new(game.player_1.clone(), LinkTypes::Player1ToGames)?, GetStrategy::default() )?;
     for link in links1 { if let Some(action_hash) = link.target.into_action_hash() { if action_hash == original_game_hash { delete_link(link.create_link_hash, GetOptions::default())?; } } }
     // Delete links from Player 2 (if exists) to this game
-    if let Some(player2) = game.player_2 {
+    if let Some(player2) = game.player_2.clone() {
         let links2 = get_links( LinkQuery::try_new(player2, LinkTypes::Player2ToGames)?, GetStrategy::default() )?;
         for link in links2 { if let Some(action_hash) = link.target.into_action_hash() { if action_hash == original_game_hash { delete_link(link.create_link_hash, GetOptions::default())?; } } }
     }
-    // Delete link from the global "games" anchor to this game
-    let games_anchor_hash = anchor_for("games")?;
-    let anchor_links = get_links( LinkQuery::try_new(games_anchor_hash, LinkTypes::GameIdToGame)?, GetStrategy::default() )?;
-     for link in anchor_links { if let Some(action_hash) = link.target.into_action_hash() { if action_hash == original_game_hash { delete_link(link.create_link_hash, GetOptions::default())?; } } }
+    // Delete the game's time-bucket index links
+    unindex_game(&original_game_hash, &game)?;
 
     // --- Entry Deletion ---
     // Delete the game entry itself
@@ -617,21 +642,17 @@ pub fn abandon_game(original_game_hash: ActionHash) -> ExternResult<Record> {
         Err(e) => return Err(wasm_error!(WasmErrorInner::Guest(format!("Failed to deserialize game entry: {:?}", e)))),
     };
 
-    // 2. Validate if abandoning is allowed
-    if current_game.game_status == GameStatus::Finished || current_game.game_status == GameStatus::Abandoned {
+    // 2. Validate if abandoning is allowed (the lifecycle table is the only place that says so)
+    if find_transition(&current_game.game_status, &GameStatus::Abandoned).is_none() {
         debug!("[game.rs] abandon_game: Game is already {:?}, returning current record.", current_game.game_status);
         return Ok(latest_game_record);
     }
 
-    // Ensure the caller is one of the players in the game
-    if caller_pubkey != current_game.player_1 && current_game.player_2.as_ref() != Some(&caller_pubkey) {
-        return Err(wasm_error!(WasmErrorInner::Guest(
-            "Cannot abandon game: Caller is not a participant in this game".into()
-        )));
-    }
-
-    // 3. Prepare the updated game state
+    // 3. Prepare the updated game state; the caller must be a participant
+    let previous_game = current_game.clone();
     current_game.game_status = GameStatus::Abandoned; // Set status to Abandoned
+    check_transition(&previous_game, &current_game, &caller_pubkey)
+        .map_err(|e| wasm_error!(WasmErrorInner::Guest(format!("Cannot abandon game: {}", e))))?;
 
     // 4. Commit the update action to the DHT
     debug!("[game.rs] abandon_game: Updating game entry {:?} to set status Abandoned", previous_action_hash);
@@ -646,16 +667,24 @@ pub fn abandon_game(original_game_hash: ActionHash) -> ExternResult<Record> {
         (),
     )?;
     debug!("[game.rs] abandon_game: Created GameUpdates link from {:?} to {:?}", original_game_hash, update_action_hash);
+    reindex_game_status(&original_game_hash, &current_game, &previous_game.game_status)?;
 
-    // Send signal to the other player
+    // Send signal to the other player, if the game had started (only then is there a session)
     // The original_game_hash is the game_id the signal function expects in its payload
-    match crate::signals::send_game_abandoned_signal(crate::signals::GameAbandonedPayload { game_id: original_game_hash.clone(), recipient: None }) {
-        Ok(_) => debug!("[game.rs] abandon_game: GameAbandoned signal sent successfully for game: {:?}", original_game_hash),
-        Err(e) => {
-            // Log the error but don't fail the whole abandon_game operation,
-            // as the game state itself is already updated to Abandoned.
-            // The primary goal of abandoning (freeing players) is achieved.
-            error!("[game.rs] abandon_game: Failed to send GameAbandoned signal for game {:?}: {:?}", original_game_hash, e);
+    let opponent = match previous_game.game_status {
+        GameStatus::InProgress => opponent_of(&previous_game, &caller_pubkey),
+        _ => None,
+    };
+    if opponent.is_some() {
+        let payload = crate::signals::GameAbandonedPayload { game_id: original_game_hash.clone(), recipient: opponent };
+        match crate::signals::send_game_abandoned_signal(payload) {
+            Ok(_) => debug!("[game.rs] abandon_game: GameAbandoned signal sent successfully for game: {:?}", original_game_hash),
+            Err(e) => {
+                // Log the error but don't fail the whole abandon_game operation,
+                // as the game state itself is already updated to Abandoned.
+                // The primary goal of abandoning (freeing players) is achieved.
+                error!("[game.rs] abandon_game: Failed to send GameAbandoned signal for game {:?}: {:?}", original_game_hash, e);
+            }
         }
     }
 
@@ -0,0 +1,281 @@
+// ping_2_pong/dnas/ping_2_pong/zomes/coordinator/ping_2_pong/src/game_index.rs
+use hdk::prelude::*;
+use ping_2_pong_integrity::*;
+use ping_2_pong_integrity::game::GameStatus;
+use ping_2_pong_integrity::time_index::{hour_bucket, tag_time, time_tag, utc_hour_components};
+
+// Games are indexed under hour buckets (root.year.month.day.hour) in two trees:
+// one with every game and one per status, so the lobby only walks recent buckets.
+const ALL_GAMES_ROOT: &str = "all_games";
+const MAX_PAGE_SIZE: u32 = 100;
+const BUCKET_DEPTH: usize = 4; // Path levels below a root: year, month, day, hour
+
+/// Input structure for the `get_games_page` function.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GetGamesPageInput {
+    pub status: Option<GameStatus>, // None pages through every game
+    pub before: Option<IndexCursor>, // Cursor: only games ordered strictly before this one
+    pub limit: u32,
+}
+
+/// A page of original Game records, newest first.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GamesPage {
+    pub games: Vec<Record>,
+    pub next_cursor: Option<IndexCursor>, // Pass as `before` to fetch the next page
+}
+
+/// Position of a link in a time-bucketed index: the time its bucket was picked from
+/// (carried in the link tag) and its target, which orders links sharing that time.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct IndexCursor {
+    pub timestamp: Timestamp,
+    pub hash: ActionHash,
+}
+
+impl IndexCursor {
+    /// Position of an index link; links created before tags carried the time fall
+    /// back to the link's own timestamp.
+    pub fn of(link: &Link) -> Option<IndexCursor> {
+        Some(IndexCursor {
+            timestamp: tag_time(&link.tag).unwrap_or(link.timestamp),
+            hash: link.target.clone().into_action_hash()?,
+        })
+    }
+}
+
+/// Pages backwards through the game index, optionally restricted to one status.
+#[hdk_extern]
+pub fn get_games_page(input: GetGamesPageInput) -> ExternResult<GamesPage> {
+    let limit = input.limit.clamp(1, MAX_PAGE_SIZE) as usize;
+    let root = index_root(input.status.as_ref());
+    let links = collect_index_links(&root, input.before.as_ref(), Some(limit))?;
+
+    let next_cursor = if links.len() == limit { links.last().and_then(IndexCursor::of) } else { None };
+    Ok(GamesPage { games: get_game_records(links)?, next_cursor })
+}
+
+/// Every game in a tree, newest first. Used where the full history is needed (e.g. ratings).
+pub fn get_all_indexed_games(status: Option<&GameStatus>) -> ExternResult<Vec<Record>> {
+    let links = collect_index_links(&index_root(status), None, None)?;
+    get_game_records(links)
+}
+
+/// Adds a newly created game to the "all games" tree and its status tree.
+pub fn index_new_game(game_hash: &ActionHash, game: &Game) -> ExternResult<()> {
+    for root in [ALL_GAMES_ROOT.to_string(), index_root(Some(&game.game_status))] {
+        link_into_bucket(&root, game_hash, game.created_at)?;
+    }
+    Ok(())
+}
+
+/// Moves a game from its previous status tree to the one for its current status.
+pub fn reindex_game_status(game_hash: &ActionHash, game: &Game, previous_status: &GameStatus) -> ExternResult<()> {
+    if *previous_status == game.game_status {
+        return Ok(());
+    }
+    unlink_from_bucket(&index_root(Some(previous_status)), game_hash, game.created_at)?;
+    link_into_bucket(&index_root(Some(&game.game_status)), game_hash, game.created_at)
+}
+
+/// Removes a game from every tree it is indexed in (used when a game is deleted).
+pub fn unindex_game(game_hash: &ActionHash, game: &Game) -> ExternResult<()> {
+    for root in [ALL_GAMES_ROOT.to_string(), index_root(Some(&game.game_status))] {
+        unlink_from_bucket(&root, game_hash, game.created_at)?;
+    }
+    Ok(())
+}
+
+// --- Helpers ---
+
+fn index_root(status: Option<&GameStatus>) -> String {
+    match status {
+        Some(status) => format!("games_{:?}", status).to_lowercase(),
+        None => ALL_GAMES_ROOT.to_string(),
+    }
+}
+
+fn bucket_path(root: &str, timestamp: Timestamp) -> ExternResult<TypedPath> {
+    hour_bucket_path(root, timestamp, LinkTypes::GameTimePath)
+}
+
+/// The hour bucket (root.year.month.day.hour) a timestamp falls in, typed with
+/// the link type of the tree it belongs to. Shared with the chat history (chat.rs).
+pub fn hour_bucket_path(root: &str, timestamp: Timestamp, path_type: LinkTypes) -> ExternResult<TypedPath> {
+    hour_bucket(root, timestamp).typed(path_type)
+}
+
+fn link_into_bucket(root: &str, game_hash: &ActionHash, created_at: Timestamp) -> ExternResult<()> {
+    let bucket = bucket_path(root, created_at)?;
+    bucket.ensure()?;
+    create_link(bucket.path_entry_hash()?, game_hash.clone(), LinkTypes::TimeBucketToGame, time_tag(created_at))?;
+    Ok(())
+}
+
+fn unlink_from_bucket(root: &str, game_hash: &ActionHash, created_at: Timestamp) -> ExternResult<()> {
+    let bucket = bucket_path(root, created_at)?;
+    let links = get_links(
+        LinkQuery::try_new(bucket.path_entry_hash()?, LinkTypes::TimeBucketToGame)?,
+        GetStrategy::default(),
+    )?;
+    for link in links {
+        if link.target.clone().into_action_hash().as_ref() == Some(game_hash) {
+            delete_link(link.create_link_hash, GetOptions::default())?;
+        }
+    }
+    Ok(())
+}
+
+fn leaf_component(path: &TypedPath) -> Option<String> {
+    path.leaf().and_then(|component| String::try_from(component).ok())
+}
+
+fn collect_index_links(root: &str, before: Option<&IndexCursor>, limit: Option<usize>) -> ExternResult<Vec<Link>> {
+    collect_bucket_links(root, LinkTypes::GameTimePath, LinkTypes::TimeBucketToGame, before, limit)
+}
+
+/// Walks year -> month -> day -> hour buckets newest first, skipping buckets after
+/// the cursor, and returns the `bucket_type` links ordered strictly before it (newest first).
+pub fn collect_bucket_links(
+    root: &str,
+    path_type: LinkTypes,
+    bucket_type: LinkTypes,
+    before: Option<&IndexCursor>,
+    limit: Option<usize>,
+) -> ExternResult<Vec<Link>> {
+    walk_from_root(root, path_type, bucket_type, Walk::Before(before), limit)
+}
+
+/// Walks the buckets oldest first from `since` (or from just after `after` when
+/// continuing a page) and returns the `bucket_type` links in that order. Links at
+/// exactly `since` are included, so none sharing that time are skipped.
+pub fn collect_bucket_links_after(
+    root: &str,
+    path_type: LinkTypes,
+    bucket_type: LinkTypes,
+    since: Timestamp,
+    after: Option<&IndexCursor>,
+    limit: Option<usize>,
+) -> ExternResult<Vec<Link>> {
+    let walk = match after {
+        Some(after) => Walk::After(after),
+        None => Walk::Since(since),
+    };
+    walk_from_root(root, path_type, bucket_type, walk, limit)
+}
+
+// Where a bucket walk is bounded, and so which way it goes.
+#[derive(Clone, Copy)]
+enum Walk<'a> {
+    Before(Option<&'a IndexCursor>), // Newest first, strictly before the cursor
+    Since(Timestamp),                // Oldest first, at or after the time
+    After(&'a IndexCursor),          // Oldest first, strictly after the cursor
+}
+
+impl Walk<'_> {
+    fn bound_time(&self) -> Option<Timestamp> {
+        match self {
+            Walk::Before(before) => before.map(|before| before.timestamp),
+            Walk::Since(since) => Some(*since),
+            Walk::After(after) => Some(after.timestamp),
+        }
+    }
+
+    fn is_newest_first(&self) -> bool {
+        matches!(self, Walk::Before(_))
+    }
+
+    fn admits(&self, position: &IndexCursor) -> bool {
+        match self {
+            Walk::Before(before) => before.map_or(true, |before| position < before),
+            Walk::Since(since) => position.timestamp >= *since,
+            Walk::After(after) => position > *after,
+        }
+    }
+}
+
+fn walk_from_root(
+    root: &str,
+    path_type: LinkTypes,
+    bucket_type: LinkTypes,
+    walk: Walk<'_>,
+    limit: Option<usize>,
+) -> ExternResult<Vec<Link>> {
+    let cursor = walk.bound_time().map(utc_hour_components);
+    let root_path = Path::from(root).typed(path_type)?;
+    let mut collected: Vec<Link> = Vec::new();
+    walk_buckets(root_path, BUCKET_DEPTH, cursor.as_ref().map(|c| &c[..]), bucket_type, walk, limit, &mut collected)?;
+    Ok(collected)
+}
+
+fn walk_buckets(
+    path: TypedPath,
+    depth: usize,
+    cursor: Option<&[String]>, // Remaining cursor components, only while on the cursor's own branch
+    bucket_type: LinkTypes,
+    walk: Walk<'_>,
+    limit: Option<usize>,
+    collected: &mut Vec<Link>,
+) -> ExternResult<()> {
+    let is_full = |collected: &Vec<Link>| limit.is_some_and(|limit| collected.len() >= limit);
+
+    // Hour bucket reached: collect its links in walk order.
+    if depth == 0 {
+        let links = get_links(
+            LinkQuery::try_new(path.path_entry_hash()?, bucket_type)?,
+            GetStrategy::default(),
+        )?;
+        let mut positioned: Vec<(IndexCursor, Link)> = links
+            .into_iter()
+            .filter_map(|link| IndexCursor::of(&link).map(|position| (position, link)))
+            .filter(|(position, _)| walk.admits(position))
+            .collect();
+        positioned.sort_by(|(a, _), (b, _)| if walk.is_newest_first() { b.cmp(a) } else { a.cmp(b) });
+        for (_, link) in positioned {
+            if is_full(collected) {
+                break;
+            }
+            collected.push(link);
+        }
+        return Ok(());
+    }
+
+    let mut children: Vec<(String, TypedPath)> = path
+        .children_paths()?
+        .into_iter()
+        .filter_map(|child| leaf_component(&child).map(|component| (component, child)))
+        .collect();
+    children.sort_by(|(a, _), (b, _)| if walk.is_newest_first() { b.cmp(a) } else { a.cmp(b) });
+
+    let (bound, rest) = match cursor.and_then(|c| c.split_first()) {
+        Some((bound, rest)) => (Some(bound), Some(rest)),
+        None => (None, None),
+    };
+    for (component, child) in children {
+        if is_full(collected) {
+            break;
+        }
+        match bound {
+            Some(bound) if walk.is_newest_first() && component > *bound => continue,
+            Some(bound) if !walk.is_newest_first() && component < *bound => continue,
+            Some(bound) if component == *bound => walk_buckets(child, depth - 1, rest, bucket_type, walk, limit, collected)?,
+            _ => walk_buckets(child, depth - 1, None, bucket_type, walk, limit, collected)?,
+        }
+    }
+    Ok(())
+}
+
+fn get_game_records(links: Vec<Link>) -> ExternResult<Vec<Record>> {
+    let get_inputs: Vec<GetInput> = links
+        .into_iter()
+        .filter_map(|link| link.target.into_action_hash())
+        .map(|ah| GetInput::new(ah.into(), GetOptions::default()))
+        .collect();
+
+    if get_inputs.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let records = HDK.with(|hdk| hdk.borrow().get(get_inputs))?;
+    Ok(records.into_iter().flatten().collect())
+}
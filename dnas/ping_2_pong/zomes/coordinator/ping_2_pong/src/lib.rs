@@ -3,12 +3,29 @@
 // Declare modules
 pub mod chat;
 pub mod game;
+pub mod game_index;
 pub mod player;
 pub mod score;
 pub mod statistics;
 pub mod utils;
 pub mod signals;
 pub mod invitations;
+pub mod match_result;
+pub mod rating;
+pub mod matchmaking;
+pub mod codec;
+pub mod replay;
+pub mod session;
+pub mod spectators;
+pub mod tournament;
+pub mod tournament_bracket;
+pub mod simulation;
+pub mod lockstep;
+pub mod frame_batch;
+pub mod outbox;
+pub mod resume;
+pub mod series;
+pub mod moderation;
 
 pub use chat::send_global_chat_message;
 pub use signals::receive_remote_signal;
@@ -29,13 +46,9 @@ pub struct ChatMessagePayload {
 /// ---------- 1. grant the capability on startup ----------
 #[hdk_extern]
 pub fn init(_: ()) -> ExternResult<InitCallbackResult> {
-    // everybody can call remote signals and functions
-    let grant = CapGrantEntry {
-        tag: "remote-signal".into(),
-        access: CapAccess::Unrestricted,
-        functions: GrantedFunctions::All,
-    };
-    create_cap_grant(grant)?;
+    // Everybody may deliver signals and open game sessions; game traffic itself
+    // is authorised by per-game session grants (see session.rs)
+    signals::grant_remote_signal_cap()?;
     Ok(InitCallbackResult::Pass)
 }
 
@@ -44,11 +57,20 @@ pub fn init(_: ()) -> ExternResult<InitCallbackResult> {
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(tag = "type")]
 pub enum Signal {
-    GlobalChatMessage {
+    // Chat message in any channel (see chat.rs)
+    ChatMessage {
+        channel: ChatChannel,
+        message_hash: ActionHash,
         timestamp: Timestamp,
         sender: AgentPubKey,
         content: String,
     },
+    // A message was edited, deleted or reacted to; refetch it with get_chat_message
+    ChatMessageChanged {
+        channel: ChatChannel,
+        message_hash: ActionHash,
+        changed_by: AgentPubKey,
+    },
     // Standard Holochain signals
     LinkCreated { action: SignedActionHashed, link_type: LinkTypes },
     LinkDeleted { action: SignedActionHashed, create_link_action: SignedActionHashed, link_type: LinkTypes },
@@ -87,6 +109,20 @@ pub enum Signal {
         score1: Option<u32>,
         score2: Option<u32>,
     },
+    // Lockstep netcode: paddle inputs for consecutive frames from `first_frame`
+    InputFrame {
+        game_id: ActionHash,
+        player: AgentPubKey,
+        first_frame: u32,
+        inputs: Vec<pong_sim::PaddleInput>,
+    },
+    // Unpacked batch from the opponent's send_frame_batch (see frame_batch.rs)
+    FrameBatch {
+        game_id: ActionHash,
+        player: AgentPubKey,
+        seq: u64,
+        events: Vec<frame_batch::FrameEvent>,
+    },
     ScoreUpdate {
         game_id: ActionHash,
         score1:  u32,
@@ -102,6 +138,22 @@ pub enum Signal {
         game_id: ActionHash,
         abandoned_by_player: AgentPubKey,
     },
+    // A player picked an interrupted game back up (see resume.rs)
+    GameResumed {
+        game_id: ActionHash,
+        player: AgentPubKey,
+        checkpoint: Option<GameCheckpoint>,
+    },
+    // Countersigning of the final result (see match_result.rs)
+    MatchResultProposal {
+        game_id: ActionHash,
+        proposer: AgentPubKey,
+        proposal: PreflightResponse,
+    },
+    MatchResultCommitted {
+        game_id: ActionHash,
+        match_result_hash: ActionHash,
+    },
 }
 
 // post_commit hook (no changes needed here)
@@ -0,0 +1,162 @@
+// ping_2_pong/dnas/ping_2_pong/zomes/coordinator/ping_2_pong/src/lockstep.rs
+//! Lockstep / rollback netcode for games created with `NetcodeMode::Lockstep`.
+//!
+//! Instead of the host streaming the ball, both players send only their
+//! frame-numbered paddle inputs (`send_input_frame`) and run the same
+//! deterministic simulation (`pong_sim`). Each input signal repeats the
+//! sender's unconfirmed inputs, so a lost signal is covered by the next one.
+//! The rollback state lives in the UI and is passed through `advance_lockstep`.
+//! After a reconnect both players restart it from the checkpoint carried by the
+//! `GameResumed` signal, with frame numbering starting again at 1. The simulation
+//! follows the game's own rules.
+use hdk::prelude::*;
+use ping_2_pong_integrity::*;
+use ping_2_pong_integrity::game::NetcodeMode;
+use pong_sim::{GameState, PaddleInput, Rollback, Side, MAX_ROLLBACK_FRAMES};
+use crate::game::{game_from_record, get_latest_game_entry};
+use crate::signals::send_realtime_signal;
+use crate::simulation::sim_config;
+use crate::Signal;
+
+/// Most inputs carried by one `InputFrame` signal: the whole unconfirmed window.
+pub const MAX_INPUTS_PER_FRAME: usize = MAX_ROLLBACK_FRAMES;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct InputFramePayload {
+    pub game_id: ActionHash,
+    pub recipient: AgentPubKey,
+    pub first_frame: u32,
+    pub inputs: Vec<PaddleInput>, // Consecutive frames from first_frame, newest last
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RemoteInputs {
+    pub first_frame: u32,
+    pub inputs: Vec<PaddleInput>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AdvanceLockstepInput {
+    pub game_id: ActionHash,
+    pub rollback: Option<Rollback>,                // None on the first frame
+    pub resume_from: Option<GameCheckpoint>,       // Starting point when `rollback` is None after a resume
+    pub local_input: Option<(u32, PaddleInput)>,   // (frame, input) for this tick
+    pub remote_inputs: Vec<RemoteInputs>,          // InputFrame signals received since the last call
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AdvanceLockstepOutput {
+    pub rollback: Rollback,
+    pub state: GameState,              // Confirmed state plus predicted frames, for rendering
+    pub rolled_back_from: Option<u32>, // Earliest frame re-simulated because of a late input
+    pub stalled: bool,                 // Local input refused: too far ahead of the opponent
+}
+
+/// Sends the caller's paddle inputs for consecutive frames to the opponent.
+#[hdk_extern]
+pub fn send_input_frame(payload: InputFramePayload) -> ExternResult<()> {
+    if payload.inputs.is_empty() || payload.inputs.len() > MAX_INPUTS_PER_FRAME {
+        return Err(wasm_error!(WasmErrorInner::Guest(format!(
+            "An input frame carries 1 to {} inputs", MAX_INPUTS_PER_FRAME
+        ))));
+    }
+    if get_netcode_mode(&payload.game_id)? != NetcodeMode::Lockstep {
+        return Err(wasm_error!(WasmErrorInner::Guest("Game does not use lockstep netcode".into())));
+    }
+    // Only the two players send inputs; anyone else would be steering player 2.
+    local_side(&payload.game_id)?;
+    let signal = Signal::InputFrame {
+        game_id: payload.game_id.clone(),
+        player: agent_info()?.agent_initial_pubkey,
+        first_frame: payload.first_frame,
+        inputs: payload.inputs,
+    };
+    // Inputs are resent with the next frame, so delivery is fire-and-forget.
+    send_realtime_signal(vec![payload.recipient], &signal)
+}
+
+/// Applies this tick's local input and any received remote inputs, rolling back
+/// and re-simulating when a remote input contradicts the prediction.
+#[hdk_extern]
+pub fn advance_lockstep(input: AdvanceLockstepInput) -> ExternResult<AdvanceLockstepOutput> {
+    let mut rollback = match input.rollback {
+        Some(rollback) => rollback,
+        None => {
+            let game = get_current_game(&input.game_id)?;
+            let side = local_side_in(&game)?;
+            match &input.resume_from {
+                Some(checkpoint) => Rollback::from_state(side, resumed_state(&input.game_id, &game, checkpoint)?),
+                None => Rollback::from_state(side, GameState::with_config(sim_config(&game.rules))),
+            }
+        }
+    };
+
+    let mut rolled_back_from: Option<u32> = None;
+    for remote in input.remote_inputs {
+        if let Some(frame) = rollback.add_remote_inputs(remote.first_frame, &remote.inputs) {
+            rolled_back_from = Some(rolled_back_from.map_or(frame, |earliest| earliest.min(frame)));
+        }
+    }
+
+    let mut stalled = false;
+    if let Some((frame, paddle_input)) = input.local_input {
+        match rollback.add_local_input(frame, paddle_input) {
+            Ok(()) => {}
+            Err(pong_sim::InputRejection::Stalled) => stalled = true,
+            Err(pong_sim::InputRejection::OutOfOrder { expected }) => {
+                return Err(wasm_error!(WasmErrorInner::Guest(format!(
+                    "Local input for frame {} is out of order (expected frame {})", frame, expected
+                ))));
+            }
+        }
+    }
+
+    let (state, _) = rollback.predicted_state();
+    Ok(AdvanceLockstepOutput { rollback, state, rolled_back_from, stalled })
+}
+
+/// Netcode mode of a game. The mode never changes, so the original entry is enough.
+pub fn get_netcode_mode(game_id: &ActionHash) -> ExternResult<NetcodeMode> {
+    let game = get_original_game(game_id)?;
+    Ok(game.netcode)
+}
+
+// Which paddle the caller controls in the game. Player 2 may have joined after
+// creation, so the latest revision is used; callers who are not players are refused.
+fn local_side(game_id: &ActionHash) -> ExternResult<Side> {
+    local_side_in(&get_current_game(game_id)?)
+}
+
+fn local_side_in(game: &Game) -> ExternResult<Side> {
+    let me = agent_info()?.agent_initial_pubkey;
+    if game.player_1 == me {
+        Ok(Side::Player1)
+    } else if game.player_2.as_ref() == Some(&me) {
+        Ok(Side::Player2)
+    } else {
+        Err(wasm_error!(WasmErrorInner::Guest("Only the players of a game can play it".into())))
+    }
+}
+
+// Simulation state at the point a checkpoint of this game was taken.
+fn resumed_state(game_id: &ActionHash, game: &Game, checkpoint: &GameCheckpoint) -> ExternResult<GameState> {
+    if checkpoint.game_id != *game_id {
+        return Err(wasm_error!(WasmErrorInner::Guest("Checkpoint belongs to another game".into())));
+    }
+    // Between points the serve goes to the named player; otherwise it goes to player 2 as at kick-off.
+    let serve_towards = match &checkpoint.serve_to {
+        Some(player) if *player == game.player_1 => Side::Player1,
+        _ => Side::Player2,
+    };
+    Ok(GameState::resume_with(sim_config(&game.rules), checkpoint.score_1, checkpoint.score_2, serve_towards))
+}
+
+fn get_current_game(game_id: &ActionHash) -> ExternResult<Game> {
+    Ok(get_latest_game_entry(game_id)?.1)
+}
+
+fn get_original_game(game_id: &ActionHash) -> ExternResult<Game> {
+    let record = get(game_id.clone(), GetOptions::default())?
+        .ok_or(wasm_error!(WasmErrorInner::Guest(format!("Game does not exist: {}", game_id))))?;
+    game_from_record(&record)
+}
@@ -0,0 +1,247 @@
+// ping_2_pong/dnas/ping_2_pong/zomes/coordinator/ping_2_pong/src/match_result.rs
+use hdk::prelude::*;
+use ping_2_pong_integrity::*;
+use ping_2_pong_integrity::game::GameStatus;
+use ping_2_pong_integrity::game_rules::check_final_score;
+use crate::Signal;
+use crate::session::{ call_opponent, is_session_call };
+use crate::rating::{ get_latest_rating, my_latest_rating, rate_unrated_result };
+
+// How long both chains stay locked waiting for the opponent's signature.
+const COUNTERSIGNING_SESSION_MS: u64 = 30_000;
+
+/// Input for `propose_match_result`, sent by the player reporting the final score.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ProposeMatchResultInput {
+    pub game_id: ActionHash,
+    pub player_1_score: u32,
+    pub player_2_score: u32,
+}
+
+/// Input for `accept_match_result`: the proposer's preflight response plus the
+/// scores the accepting player observed locally, which must match the proposal.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AcceptMatchResultInput {
+    pub proposal: PreflightResponse,
+    pub player_1_score: u32,
+    pub player_2_score: u32,
+}
+
+/// Step 1 (proposer): builds the MatchResult, opens a countersigning session and
+/// sends the signed preflight to the opponent as a `MatchResultProposal` signal.
+#[hdk_extern]
+pub fn propose_match_result(input: ProposeMatchResultInput) -> ExternResult<PreflightResponse> {
+    let me = agent_info()?.agent_initial_pubkey;
+    let (game_revision, game) = get_finished_game(&input.game_id)?;
+    if !get_match_results_for_game(input.game_id.clone())?.is_empty() {
+        return Err(wasm_error!(WasmErrorInner::Guest("A match result has already been recorded for this game".into())));
+    }
+    let player_2 = game.player_2.clone()
+        .ok_or(wasm_error!(WasmErrorInner::Guest("Cannot record a result for a game without Player 2".into())))?;
+    if me != game.player_1 && me != player_2 {
+        return Err(wasm_error!(WasmErrorInner::Guest("Only game participants can propose a match result".into())));
+    }
+    let opponent = if me == game.player_1 { player_2.clone() } else { game.player_1.clone() };
+    check_final_score(&game.rules, input.player_1_score, input.player_2_score)
+        .map_err(|e| wasm_error!(WasmErrorInner::Guest(format!("Cannot propose match result: {}", e))))?;
+
+    let winner = match input.player_1_score.cmp(&input.player_2_score) {
+        core::cmp::Ordering::Greater => Some(game.player_1.clone()),
+        core::cmp::Ordering::Less => Some(player_2.clone()),
+        core::cmp::Ordering::Equal => None,
+    };
+    // Both pre-match ratings go into the countersigned entry; ours must be our latest.
+    rate_unrated_result()?;
+    let my_rating = my_latest_rating()?.map(|(hash, _)| hash);
+    let opponent_rating = get_latest_rating(&opponent)?.map(|(hash, _)| hash);
+    let (player_1_rating, player_2_rating) = if me == game.player_1 {
+        (my_rating, opponent_rating)
+    } else {
+        (opponent_rating, my_rating)
+    };
+    let result = MatchResult {
+        game_id: input.game_id.clone(),
+        game_revision,
+        player_1: game.player_1.clone(),
+        player_2,
+        player_1_score: input.player_1_score,
+        player_2_score: input.player_2_score,
+        winner,
+        created_at: sys_time()?,
+        player_1_rating,
+        player_2_rating,
+    };
+
+    let preflight_request = build_preflight_request(&result)?;
+    let my_response = match accept_countersigning_preflight_request(preflight_request)? {
+        PreflightRequestAcceptance::Accepted(response) => response,
+        other => return Err(wasm_error!(WasmErrorInner::Guest(format!("Could not open countersigning session: {:?}", other)))),
+    };
+    debug!("[match_result.rs] propose_match_result: Opened session for game {:?}", input.game_id);
+
+    let signal = Signal::MatchResultProposal {
+        game_id: input.game_id.clone(),
+        proposer: me,
+        proposal: my_response.clone(),
+    };
+    let signal_io = crate::signals::encode_remote_signal(&signal)?;
+    match call_opponent(&input.game_id, &opponent, "receive_remote_signal", signal_io)? {
+        ZomeCallResponse::Ok(_) => Ok(my_response),
+        other => Err(wasm_error!(WasmErrorInner::Guest(format!(
+            "Failed to deliver match result proposal to {:?}: {:?}", opponent, other
+        )))),
+    }
+}
+
+/// Step 2 (opponent): checks the proposal against the locally observed scores,
+/// countersigns it, commits the MatchResult and asks the proposer to commit too.
+#[hdk_extern]
+pub fn accept_match_result(input: AcceptMatchResultInput) -> ExternResult<ActionHash> {
+    let me = agent_info()?.agent_initial_pubkey;
+    let preflight_request = input.proposal.request().clone();
+    let result = decode_proposed_result(&preflight_request)?;
+
+    if result.player_1_score != input.player_1_score || result.player_2_score != input.player_2_score {
+        return Err(wasm_error!(WasmErrorInner::Guest("Proposed match result does not match the observed score".into())));
+    }
+    if me != result.player_1 && me != result.player_2 {
+        return Err(wasm_error!(WasmErrorInner::Guest("Only game participants can accept a match result".into())));
+    }
+    let (_, game) = get_finished_game(&result.game_id)?;
+    if game.player_1 != result.player_1 || game.player_2.as_ref() != Some(&result.player_2) {
+        return Err(wasm_error!(WasmErrorInner::Guest("Proposed match result players do not match the game".into())));
+    }
+    rate_unrated_result()?;
+    let my_rating = if me == result.player_1 { &result.player_1_rating } else { &result.player_2_rating };
+    if *my_rating != my_latest_rating()?.map(|(hash, _)| hash) {
+        return Err(wasm_error!(WasmErrorInner::Guest("Proposed match result carries an outdated rating; ask for a new proposal".into())));
+    }
+    let proposer_index = *input.proposal.agent_state().agent_index() as usize;
+    let proposer = preflight_request.signing_agents.get(proposer_index)
+        .map(|(agent, _roles)| agent.clone())
+        .ok_or(wasm_error!(WasmErrorInner::Guest("Proposal agent index is out of range".into())))?;
+
+    let my_response = match accept_countersigning_preflight_request(preflight_request)? {
+        PreflightRequestAcceptance::Accepted(response) => response,
+        other => return Err(wasm_error!(WasmErrorInner::Guest(format!("Could not join countersigning session: {:?}", other)))),
+    };
+    let responses = vec![input.proposal.clone(), my_response];
+
+    let action_hash = commit_match_result(&result, responses.clone())?;
+
+    let responses_io = ExternIO::encode(responses).map_err(|e| wasm_error!(WasmErrorInner::Guest(e.to_string())))?;
+    match call_opponent(&result.game_id, &proposer, "complete_match_result", responses_io)? {
+        ZomeCallResponse::Ok(_) => {}
+        other => warn!("[match_result.rs] accept_match_result: Proposer did not complete the session: {:?}", other),
+    }
+    Ok(action_hash)
+}
+
+/// Step 3 (proposer, called remotely by the opponent): commits the proposer's copy
+/// of the MatchResult. The conductor rejects it unless it matches the open session.
+#[hdk_extern]
+pub fn complete_match_result(responses: Vec<PreflightResponse>) -> ExternResult<ActionHash> {
+    let first = responses.first()
+        .ok_or(wasm_error!(WasmErrorInner::Guest("No preflight responses provided".into())))?;
+    let result = decode_proposed_result(first.request())?;
+    if !is_session_call(&result.game_id)? {
+        return Err(wasm_error!(WasmErrorInner::Guest("Match results can only be completed by the opponent of the game".into())));
+    }
+    commit_match_result(&result, responses)
+}
+
+/// Lists the MatchResult records linked from a game (one per player's chain).
+#[hdk_extern]
+pub fn get_match_results_for_game(game_id: ActionHash) -> ExternResult<Vec<Record>> {
+    let links = get_links(
+        LinkQuery::try_new(game_id, LinkTypes::GameToMatchResults)?,
+        GetStrategy::default(),
+    )?;
+
+    let get_inputs: Vec<GetInput> = links
+        .into_iter()
+        .filter_map(|link| link.target.into_action_hash())
+        .map(|ah| GetInput::new(ah.into(), GetOptions::default()))
+        .collect();
+
+    if get_inputs.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let records = HDK.with(|hdk| hdk.borrow().get(get_inputs))?;
+    Ok(records.into_iter().flatten().collect())
+}
+
+// --- Helpers ---
+
+// The latest revision of a finished game, which the MatchResult cites for Player 2.
+fn get_finished_game(game_id: &ActionHash) -> ExternResult<(ActionHash, Game)> {
+    let (revision, game) = crate::game::get_latest_game_entry(game_id)?;
+    if game.game_status != GameStatus::Finished {
+        return Err(wasm_error!(WasmErrorInner::Guest("Match results can only be recorded for 'Finished' games".into())));
+    }
+    Ok((revision, game))
+}
+
+fn build_preflight_request(result: &MatchResult) -> ExternResult<PreflightRequest> {
+    let ScopedEntryDefIndex { zome_index, zome_type } = ScopedEntryDefIndex::try_from(&EntryTypes::MatchResult(result.clone()))?;
+    let entry_type = EntryType::App(AppEntryDef::new(zome_type, zome_index, EntryVisibility::Public));
+    let result_bytes = SerializedBytes::try_from(result.clone())
+        .map_err(|e| wasm_error!(WasmErrorInner::Serialize(e)))?;
+
+    PreflightRequest::try_new(
+        hash_entry(result)?,
+        vec![(result.player_1.clone(), vec![]), (result.player_2.clone(), vec![])],
+        vec![],
+        0,
+        false,
+        session_times_from_millis(COUNTERSIGNING_SESSION_MS)?,
+        ActionBase::Create(CreateBase::new(entry_type)),
+        PreflightBytes(result_bytes.bytes().to_vec()),
+    )
+    .map_err(|e| wasm_error!(WasmErrorInner::Guest(e.to_string())))
+}
+
+// The MatchResult travels in the preflight bytes; its hash must match the session entry hash.
+fn decode_proposed_result(preflight_request: &PreflightRequest) -> ExternResult<MatchResult> {
+    let bytes = SerializedBytes::from(UnsafeBytes::from(preflight_request.preflight_bytes.0.clone()));
+    let result = MatchResult::try_from(bytes).map_err(|e| wasm_error!(WasmErrorInner::Serialize(e)))?;
+    if hash_entry(&result)? != preflight_request.app_entry_hash {
+        return Err(wasm_error!(WasmErrorInner::Guest("Preflight bytes do not match the countersigned entry".into())));
+    }
+    Ok(result)
+}
+
+fn commit_match_result(result: &MatchResult, mut responses: Vec<PreflightResponse>) -> ExternResult<ActionHash> {
+    responses.sort_by_key(|response| *response.agent_state().agent_index());
+    let session_data = CounterSigningSessionData::try_from_responses(responses, vec![])
+        .map_err(|e| wasm_error!(WasmErrorInner::Guest(e.to_string())))?;
+    let ScopedEntryDefIndex { zome_index, zome_type } = ScopedEntryDefIndex::try_from(&EntryTypes::MatchResult(result.clone()))?;
+    let result_bytes = SerializedBytes::try_from(result.clone())
+        .map_err(|e| wasm_error!(WasmErrorInner::Serialize(e)))?;
+
+    let action_hash = HDK.with(|hdk| {
+        hdk.borrow().create(CreateInput::new(
+            EntryDefLocation::app(zome_index, zome_type),
+            EntryVisibility::Public,
+            Entry::CounterSign(Box::new(session_data), result_bytes),
+            ChainTopOrdering::Strict,
+        ))
+    })?;
+    debug!("[match_result.rs] commit_match_result: Committed MatchResult {:?} for game {:?}", action_hash, result.game_id);
+
+    // Our Rating for this game is recorded once the session is over (see rating.rs).
+    schedule("rate_my_results")?;
+
+    // A decided series game is followed up once the session is over (see series.rs).
+    if result.winner.as_ref() == Some(&agent_info()?.agent_initial_pubkey) {
+        schedule("advance_my_series")?;
+    }
+
+    // Let the local UI know, so it can record the derived Score once the session completes.
+    emit_signal(&Signal::MatchResultCommitted {
+        game_id: result.game_id.clone(),
+        match_result_hash: action_hash.clone(),
+    })?;
+    Ok(action_hash)
+}
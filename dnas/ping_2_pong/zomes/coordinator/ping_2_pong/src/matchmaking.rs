@@ -0,0 +1,240 @@
+// ping_2_pong/dnas/ping_2_pong/zomes/coordinator/ping_2_pong/src/matchmaking.rs
+use hdk::prelude::*;
+use ping_2_pong_integrity::*;
+use ping_2_pong_integrity::game::{GameStatus, NetcodeMode};
+use ping_2_pong_integrity::game_rules::GameRules;
+use ping_2_pong_integrity::game_lifecycle::check_transition;
+use crate::utils::{ anchor_for, player_exists, is_player_in_ongoing_game };
+use crate::session::establish_game_session;
+use crate::game::{ create_game, game_from_record, get_online_users, CreateGameInput };
+use crate::rating::current_rating;
+use crate::game_index::reindex_game_status;
+use crate::Signal;
+
+const QUEUE_ANCHOR: &str = "matchmaking_queue";
+const QUEUE_TICKET_TTL_MS: i64 = 300_000; // Tickets expire like presence (5 minutes)
+const BASE_RATING_WINDOW: f64 = 100.0;    // Max rating gap accepted immediately
+const WINDOW_GROWTH_PER_SEC: f64 = 10.0;  // The gap widens the longer someone waits
+const CLAIM_TTL_MS: i64 = 30_000;          // A claim not followed by a game frees the ticket again
+
+/// A player currently waiting in the matchmaking queue.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct QueueEntry {
+    pub player: AgentPubKey,
+    pub ticket: ActionHash, // The MatchmakingQueue link, claimed before pairing
+    pub queued_at: Timestamp,
+    pub rating: f64,
+}
+
+/// Joins the matchmaking queue (idempotent) and tries to pair the caller right away.
+/// Returns the started Game record if a pair formed, or None while still waiting.
+/// Calling it again while queued retries pairing with a wider rating window.
+#[hdk_extern]
+pub fn enter_matchmaking_queue(_: ()) -> ExternResult<Option<Record>> {
+    let me = agent_info()?.agent_initial_pubkey;
+    if !player_exists(&me)? {
+        return Err(wasm_error!(WasmErrorInner::Guest("Cannot enter matchmaking: You do not have a player profile".into())));
+    }
+    if is_player_in_ongoing_game(&me)? {
+        return Err(wasm_error!(WasmErrorInner::Guest("Cannot enter matchmaking: You are already in an ongoing game".into())));
+    }
+
+    let queue = get_matchmaking_queue(())?;
+    if !queue.iter().any(|entry| entry.player == me) {
+        let queue_anchor = anchor_for(QUEUE_ANCHOR)?;
+        create_link(queue_anchor, me.clone(), LinkTypes::MatchmakingQueue, ())?;
+        debug!("[matchmaking.rs] enter_matchmaking_queue: {:?} joined the queue", me);
+    }
+
+    let queue = get_matchmaking_queue(())?;
+    let Some(my_entry) = queue.iter().find(|entry| entry.player == me) else {
+        return Ok(None);
+    };
+    let Some(opponent_entry) = pick_opponent(my_entry, &queue, sys_time()?)? else {
+        return Ok(None);
+    };
+    if !claim_pair(my_entry, opponent_entry)? {
+        debug!("[matchmaking.rs] enter_matchmaking_queue: Lost the claim on {:?}, still waiting", opponent_entry.player);
+        return Ok(None);
+    }
+    let opponent = opponent_entry.player.clone();
+    debug!("[matchmaking.rs] enter_matchmaking_queue: Pairing {:?} with {:?}", me, opponent);
+
+    let game_record = create_game(CreateGameInput {
+        player_1: me.clone(),
+        player_2: Some(opponent.clone()),
+        netcode: NetcodeMode::default(),
+        rules: GameRules::default(),
+        series: None,
+    })?;
+    remove_my_tickets(&me)?;
+    start_matched_game(game_record).map(Some)
+}
+
+/// Removes the caller's tickets from the matchmaking queue.
+#[hdk_extern]
+pub fn leave_matchmaking_queue(_: ()) -> ExternResult<()> {
+    let me = agent_info()?.agent_initial_pubkey;
+    remove_my_tickets(&me)
+}
+
+/// Lists players waiting for a match: one entry per agent, earliest ticket first.
+/// Expired tickets, tickets already consumed by a newer game and other players'
+/// tickets claimed for a pairing in progress are skipped.
+#[hdk_extern]
+pub fn get_matchmaking_queue(_: ()) -> ExternResult<Vec<QueueEntry>> {
+    let me = agent_info()?.agent_initial_pubkey;
+    let queue_anchor = anchor_for(QUEUE_ANCHOR)?;
+    let mut links = get_links(
+        LinkQuery::try_new(queue_anchor, LinkTypes::MatchmakingQueue)?,
+        GetStrategy::default(),
+    )?;
+    links.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+
+    let now_ms = sys_time()?.as_millis();
+    let mut entries: Vec<QueueEntry> = Vec::new();
+    for link in links {
+        let Some(player) = link.target.into_agent_pub_key() else { continue; };
+        if now_ms.saturating_sub(link.timestamp.as_millis()) >= QUEUE_TICKET_TTL_MS {
+            continue;
+        }
+        if entries.iter().any(|entry| entry.player == player) {
+            continue;
+        }
+        if has_game_since(&player, link.timestamp)? {
+            continue;
+        }
+        let ticket = link.create_link_hash;
+        if player != me && first_claimer(&ticket)?.is_some() {
+            continue;
+        }
+        let rating = current_rating(&player)?;
+        entries.push(QueueEntry { player, ticket, queued_at: link.timestamp, rating });
+    }
+    Ok(entries)
+}
+
+// --- Pairing ---
+
+// Deterministic pairing rule: among online candidates whose rating gap fits the
+// window (which widens with the longer wait of the two), prefer the closest rating,
+// then the longest waiting, then the lowest key.
+fn pick_opponent<'a>(my_entry: &QueueEntry, queue: &'a [QueueEntry], now: Timestamp) -> ExternResult<Option<&'a QueueEntry>> {
+    let me = &my_entry.player;
+    let online = get_online_users(())?;
+
+    let mut candidates: Vec<(&QueueEntry, f64)> = queue
+        .iter()
+        .filter(|entry| &entry.player != me && online.contains(&entry.player))
+        .filter_map(|entry| {
+            let longest_wait = waited_secs(my_entry, now).max(waited_secs(entry, now));
+            let window = BASE_RATING_WINDOW + WINDOW_GROWTH_PER_SEC * longest_wait;
+            let gap = (entry.rating - my_entry.rating).abs();
+            (gap <= window).then_some((entry, gap))
+        })
+        .collect();
+
+    candidates.sort_by(|(a, gap_a), (b, gap_b)| {
+        gap_a.total_cmp(gap_b)
+            .then_with(|| a.queued_at.cmp(&b.queued_at))
+            .then_with(|| a.player.cmp(&b.player))
+    });
+    Ok(candidates.first().map(|(entry, _)| *entry))
+}
+
+// Claims both tickets, then keeps the pair only if our claim is the earliest live one
+// on each. Otherwise withdraws, so concurrent pickers settle on a single game.
+fn claim_pair(mine: &QueueEntry, theirs: &QueueEntry) -> ExternResult<bool> {
+    let me = &mine.player;
+    let mut claims = Vec::new();
+    for ticket in [&mine.ticket, &theirs.ticket] {
+        claims.push(create_link(ticket.clone(), me.clone(), LinkTypes::MatchmakingClaims, ())?);
+    }
+    for ticket in [&mine.ticket, &theirs.ticket] {
+        if first_claimer(ticket)?.as_ref() != Some(me) {
+            for claim in claims {
+                delete_link(claim, GetOptions::default())?;
+            }
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+// Agent holding the earliest live claim on a ticket (ties go to the lowest key).
+fn first_claimer(ticket: &ActionHash) -> ExternResult<Option<AgentPubKey>> {
+    let now_ms = sys_time()?.as_millis();
+    let links = get_links(
+        LinkQuery::try_new(ticket.clone(), LinkTypes::MatchmakingClaims)?,
+        GetStrategy::default(),
+    )?;
+    Ok(links
+        .into_iter()
+        .filter(|link| now_ms.saturating_sub(link.timestamp.as_millis()) < CLAIM_TTL_MS)
+        .min_by(|a, b| a.timestamp.cmp(&b.timestamp).then_with(|| a.author.cmp(&b.author)))
+        .map(|link| link.author))
+}
+
+fn waited_secs(entry: &QueueEntry, now: Timestamp) -> f64 {
+    now.as_millis().saturating_sub(entry.queued_at.as_millis()).max(0) as f64 / 1000.0
+}
+
+// A ticket is consumed once the player is linked to a game created after it.
+fn has_game_since(player: &AgentPubKey, since: Timestamp) -> ExternResult<bool> {
+    for link_type in [LinkTypes::Player1ToGames, LinkTypes::Player2ToGames] {
+        let links = get_links(LinkQuery::try_new(player.clone(), link_type)?, GetStrategy::default())?;
+        if links.iter().any(|link| link.timestamp > since) {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+fn remove_my_tickets(me: &AgentPubKey) -> ExternResult<()> {
+    let queue_anchor = anchor_for(QUEUE_ANCHOR)?;
+    let links = get_links(
+        LinkQuery::try_new(queue_anchor, LinkTypes::MatchmakingQueue)?,
+        GetStrategy::default(),
+    )?;
+    for link in links {
+        if &link.author == me {
+            delete_link(link.create_link_hash, GetOptions::default())?;
+        }
+    }
+    Ok(())
+}
+
+// Moves the freshly created game straight to InProgress and notifies both players.
+fn start_matched_game(game_record: Record) -> ExternResult<Record> {
+    let original_game_hash = game_record.action_hashed().hash.clone();
+    let mut game = game_from_record(&game_record)?;
+    let player_2 = game.player_2.clone()
+        .ok_or(wasm_error!(WasmErrorInner::Guest("Matched game has no Player 2".into())))?;
+
+    let previous_game = game.clone();
+    game.game_status = GameStatus::InProgress;
+    check_transition(&previous_game, &game, &agent_info()?.agent_initial_pubkey)
+        .map_err(|e| wasm_error!(WasmErrorInner::Guest(format!("Cannot start matched game: {}", e))))?;
+    let update_action_hash = update_entry(original_game_hash.clone(), &game)?;
+    create_link(original_game_hash.clone(), update_action_hash.clone(), LinkTypes::GameUpdates, ())?;
+    reindex_game_status(&original_game_hash, &game, &previous_game.game_status)?;
+
+    let start_sig = Signal::GameStarted {
+        game_id: original_game_hash.clone(),
+        player_1: game.player_1.clone(),
+        player_2: player_2.clone(),
+    };
+    emit_signal(&start_sig)?;
+
+    let start_record = get(update_action_hash.clone(), GetOptions::default())?
+        .ok_or(wasm_error!(WasmErrorInner::Guest(format!(
+            "Could not find the updated Game record after matchmaking: {:?}", update_action_hash
+        ))))?;
+    // Open the per-game session with player 2, then notify them under it
+    // (the outbox retries until the session is open and they acknowledge).
+    if let Err(e) = establish_game_session(&original_game_hash, &start_record) {
+        warn!("[matchmaking.rs] start_matched_game: Could not open game session with {:?}: {:?}", player_2, e);
+    }
+    crate::outbox::send_reliable(&player_2, Some(&original_game_hash), &start_sig)?;
+    Ok(start_record)
+}
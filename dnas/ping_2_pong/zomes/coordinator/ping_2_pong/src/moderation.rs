@@ -0,0 +1,183 @@
+// ping_2_pong/dnas/ping_2_pong/zomes/coordinator/ping_2_pong/src/moderation.rs
+use hdk::prelude::*;
+use ping_2_pong_integrity::*;
+use ping_2_pong_integrity::properties::dna_properties;
+use std::collections::HashSet;
+
+// Block and mute lists live in one private ChatFilters entry per agent, read
+// locally on every received chat signal. Reports and hidden messages are public.
+const CHAT_REPORTS_ANCHOR: &str = "chat_reports";
+const HIDDEN_MESSAGES_ANCHOR: &str = "hidden_chat_messages";
+
+/// Input for `report_chat_message`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ReportChatMessageInput {
+    pub message: ActionHash,
+    pub reason: String,
+}
+
+/// Input for `hide_chat_message`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct HideChatMessageInput {
+    pub message: ActionHash,
+    pub reason: String,
+}
+
+/// The caller's block and mute lists.
+#[hdk_extern]
+pub fn get_chat_filters(_: ()) -> ExternResult<ChatFilters> {
+    Ok(latest_chat_filters()?.map(|(_, filters)| filters).unwrap_or_default())
+}
+
+/// Drops the agent's chat messages and game invitations.
+#[hdk_extern]
+pub fn block_agent(agent: AgentPubKey) -> ExternResult<ChatFilters> {
+    edit_chat_filters(|filters| add_agent(&mut filters.blocked, agent))
+}
+
+#[hdk_extern]
+pub fn unblock_agent(agent: AgentPubKey) -> ExternResult<ChatFilters> {
+    edit_chat_filters(|filters| filters.blocked.retain(|blocked| *blocked != agent))
+}
+
+/// Hides the agent's chat messages; invitations still arrive.
+#[hdk_extern]
+pub fn mute_agent(agent: AgentPubKey) -> ExternResult<ChatFilters> {
+    edit_chat_filters(|filters| add_agent(&mut filters.muted, agent))
+}
+
+#[hdk_extern]
+pub fn unmute_agent(agent: AgentPubKey) -> ExternResult<ChatFilters> {
+    edit_chat_filters(|filters| filters.muted.retain(|muted| *muted != agent))
+}
+
+/// Files a report about a chat message for the moderators.
+#[hdk_extern]
+pub fn report_chat_message(input: ReportChatMessageInput) -> ExternResult<ActionHash> {
+    let report = Report {
+        reporter: agent_info()?.agent_initial_pubkey,
+        message: input.message,
+        reason: input.reason,
+        created_at: sys_time()?,
+    };
+    let report_hash = create_entry(&EntryTypes::Report(report))?;
+    create_link(
+        Path::from(CHAT_REPORTS_ANCHOR).path_entry_hash()?,
+        report_hash.clone(),
+        LinkTypes::ChatReports,
+        (),
+    )?;
+    Ok(report_hash)
+}
+
+/// Every report filed, newest first.
+#[hdk_extern]
+pub fn get_chat_reports(_: ()) -> ExternResult<Vec<Record>> {
+    let mut links = get_links(
+        LinkQuery::try_new(Path::from(CHAT_REPORTS_ANCHOR).path_entry_hash()?, LinkTypes::ChatReports)?,
+        GetStrategy::default(),
+    )?;
+    links.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+    let mut records = Vec::new();
+    for hash in links.into_iter().filter_map(|link| link.target.into_action_hash()) {
+        if let Some(record) = get(hash, GetOptions::default())? {
+            records.push(record);
+        }
+    }
+    Ok(records)
+}
+
+/// True if the agent is listed as a moderator in the DNA properties.
+#[hdk_extern]
+pub fn is_chat_moderator(agent: AgentPubKey) -> ExternResult<bool> {
+    Ok(dna_properties()?.is_moderator(&agent))
+}
+
+/// Hides a chat message from everyone's history. Moderators only.
+#[hdk_extern]
+pub fn hide_chat_message(input: HideChatMessageInput) -> ExternResult<()> {
+    let me = agent_info()?.agent_initial_pubkey;
+    if !dna_properties()?.is_moderator(&me) {
+        return Err(wasm_error!(WasmErrorInner::Guest("Only moderators can hide chat messages".into())));
+    }
+    if get_hidden_chat_messages()?.contains(&input.message) {
+        return Ok(());
+    }
+    create_link(
+        Path::from(HIDDEN_MESSAGES_ANCHOR).path_entry_hash()?,
+        input.message,
+        LinkTypes::HiddenChatMessages,
+        LinkTag::new(input.reason.into_bytes()),
+    )?;
+    Ok(())
+}
+
+/// Makes a hidden chat message visible again. Moderators only.
+#[hdk_extern]
+pub fn unhide_chat_message(message: ActionHash) -> ExternResult<()> {
+    let me = agent_info()?.agent_initial_pubkey;
+    if !dna_properties()?.is_moderator(&me) {
+        return Err(wasm_error!(WasmErrorInner::Guest("Only moderators can unhide chat messages".into())));
+    }
+    for link in hidden_message_links()? {
+        if link.target.clone().into_action_hash().as_ref() == Some(&message) {
+            delete_link(link.create_link_hash, GetOptions::default())?;
+        }
+    }
+    Ok(())
+}
+
+/// ChatMessage ActionHashes hidden by a moderator.
+pub fn get_hidden_chat_messages() -> ExternResult<HashSet<ActionHash>> {
+    Ok(hidden_message_links()?
+        .into_iter()
+        .filter_map(|link| link.target.into_action_hash())
+        .collect())
+}
+
+// --- Helpers ---
+
+fn hidden_message_links() -> ExternResult<Vec<Link>> {
+    get_links(
+        LinkQuery::try_new(Path::from(HIDDEN_MESSAGES_ANCHOR).path_entry_hash()?, LinkTypes::HiddenChatMessages)?,
+        GetStrategy::default(),
+    )
+}
+
+// Newest revision of the caller's ChatFilters entry, if they ever saved one.
+fn latest_chat_filters() -> ExternResult<Option<(ActionHash, ChatFilters)>> {
+    let filter = ChainQueryFilter::new()
+        .entry_type(UnitEntryTypes::ChatFilters.try_into()?)
+        .include_entries(true);
+    Ok(query(filter)?
+        .into_iter()
+        .filter_map(|record| {
+            let filters = record.entry().to_app_option::<ChatFilters>().ok().flatten()?;
+            Some((record.action_address().clone(), filters))
+        })
+        .next_back())
+}
+
+fn edit_chat_filters(edit: impl FnOnce(&mut ChatFilters)) -> ExternResult<ChatFilters> {
+    let latest = latest_chat_filters()?;
+    let mut filters = latest.as_ref().map(|(_, filters)| filters.clone()).unwrap_or_default();
+    edit(&mut filters);
+    match latest {
+        Some((action_hash, previous)) if previous == filters => {
+            debug!("[moderation.rs] edit_chat_filters: No change to save over {}", action_hash);
+        }
+        Some((action_hash, _)) => {
+            update_entry(action_hash, &filters)?;
+        }
+        None => {
+            create_entry(&EntryTypes::ChatFilters(filters.clone()))?;
+        }
+    }
+    Ok(filters)
+}
+
+fn add_agent(list: &mut Vec<AgentPubKey>, agent: AgentPubKey) {
+    if !list.contains(&agent) {
+        list.push(agent);
+    }
+}
@@ -0,0 +1,272 @@
+// ping_2_pong/dnas/ping_2_pong/zomes/coordinator/ping_2_pong/src/outbox.rs
+//! Reliable delivery for critical signals (invitations, game start, game over,
+//! abandonment).
+//!
+//! Sending side: `send_reliable` tries the recipient straight away. A message
+//! counts as acknowledged once the recipient's `receive_remote_signal` returns
+//! `Ok`; until then it is kept as a private `OutboxMessage` and retried with
+//! exponential backoff by the scheduled `retry_outbox`. Retries keep the
+//! original sequence number, so a recipient that did get an earlier attempt
+//! drops the duplicate (signals are deduplicated by sender and seq, so a retry
+//! arriving after newer signals is still accepted).
+//!
+//! Receiving side: accepted critical signals are also kept as private
+//! `InboxMessage` entries, which the UI drains with `get_pending_signals` after
+//! reconnecting (signals emitted while it was away are lost otherwise).
+use hdk::prelude::*;
+use ping_2_pong_integrity::*;
+use std::collections::HashSet;
+use crate::signals::{create_private_relaxed, RemoteSignal};
+use crate::Signal;
+
+// First retry after this long, doubling per attempt up to the cap.
+const RETRY_BASE_MS: i64 = 2_000;
+const RETRY_MAX_BACKOFF_MS: i64 = 300_000;
+// Undelivered messages and undrained inbox entries are dropped after this long.
+pub const MESSAGE_TTL_MS: i64 = 3_600_000;
+// How often the scheduler looks at the outbox while it is not empty.
+const RETRY_TICK_SECS: u64 = 2;
+
+/// A critical signal received while the UI may not have been listening.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PendingSignal {
+    pub sender: AgentPubKey,
+    pub seq: i64, // Together with `sender`, identifies the signal for de-duplication in the UI
+    pub received_at: Timestamp,
+    pub signal: Signal,
+}
+
+/// Outbox entry as shown to the UI.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OutboxStatus {
+    pub recipient: AgentPubKey,
+    pub seq: i64,
+    pub attempts: u32,
+    pub next_attempt_at: Timestamp,
+    pub signal: Signal,
+}
+
+/// Signals that must reach the other side.
+pub fn is_critical_signal(signal: &Signal) -> bool {
+    matches!(
+        signal,
+        Signal::GameInvitation { .. }
+            | Signal::GameStarted { .. }
+            | Signal::GameOver { .. }
+            | Signal::GameAbandoned { .. }
+            | Signal::GameResumed { .. }
+    )
+}
+
+/// Delivers `signal` to `recipient`, queueing it for retries if they cannot be
+/// reached now. With `session_game` set it is sent under that game's session.
+/// Returns true if it was acknowledged straight away.
+pub fn send_reliable(recipient: &AgentPubKey, session_game: Option<&ActionHash>, signal: &Signal) -> ExternResult<bool> {
+    let seq = sys_time()?.as_micros();
+    if deliver(recipient, session_game, seq, signal)? {
+        return Ok(true);
+    }
+
+    let now = sys_time()?;
+    let message = OutboxMessage {
+        recipient: recipient.clone(),
+        game_id: session_game.cloned(),
+        seq,
+        payload: encode_signal(signal)?,
+        created_at: now,
+        attempts: 1,
+        next_attempt_at: retry_time(now, 1)?,
+    };
+    create_entry(&EntryTypes::OutboxMessage(message))?;
+    schedule("retry_outbox")?;
+    debug!("[outbox.rs] send_reliable: Queued signal {} for {:?}", seq, recipient);
+    Ok(false)
+}
+
+/// Scheduled while the outbox has messages; retries those that are due.
+#[hdk_extern(infallible)]
+pub fn retry_outbox(_: Option<Schedule>) -> Option<Schedule> {
+    match flush_due_messages(false) {
+        Ok(0) => None,
+        Ok(_) => Some(Schedule::Ephemeral(std::time::Duration::from_secs(RETRY_TICK_SECS))),
+        Err(e) => {
+            error!("[outbox.rs] retry_outbox: {:?}", e);
+            Some(Schedule::Ephemeral(std::time::Duration::from_secs(RETRY_TICK_SECS)))
+        }
+    }
+}
+
+/// Retries every queued message now, ignoring backoff (e.g. after reconnecting).
+/// Returns how many are still undelivered.
+#[hdk_extern]
+pub fn flush_outbox(_: ()) -> ExternResult<u32> {
+    let remaining = flush_due_messages(true)?;
+    if remaining > 0 {
+        schedule("retry_outbox")?;
+    }
+    Ok(remaining)
+}
+
+/// Messages still waiting for the recipient's acknowledgement.
+#[hdk_extern]
+pub fn get_outbox(_: ()) -> ExternResult<Vec<OutboxStatus>> {
+    Ok(live_entries::<OutboxMessage>(UnitEntryTypes::OutboxMessage)?
+        .into_iter()
+        .filter_map(|(_, message)| {
+            // Undecodable entries are dropped by the next retry.
+            let signal = decode_signal(message.payload).ok()?;
+            Some(OutboxStatus {
+                recipient: message.recipient,
+                seq: message.seq,
+                attempts: message.attempts,
+                next_attempt_at: message.next_attempt_at,
+                signal,
+            })
+        })
+        .collect())
+}
+
+/// Returns the critical signals received since the last call, oldest first,
+/// and removes them from the inbox.
+#[hdk_extern]
+pub fn get_pending_signals(_: ()) -> ExternResult<Vec<PendingSignal>> {
+    let now_ms = sys_time()?.as_millis();
+    let mut pending = Vec::new();
+    for (action_hash, message) in live_entries::<InboxMessage>(UnitEntryTypes::InboxMessage)? {
+        delete_entry(action_hash)?;
+        if now_ms - message.received_at.as_millis() > MESSAGE_TTL_MS {
+            continue;
+        }
+        match decode_signal(message.payload) {
+            Ok(signal) => pending.push(PendingSignal {
+                sender: message.sender,
+                seq: message.seq,
+                received_at: message.received_at,
+                signal,
+            }),
+            Err(e) => warn!("[outbox.rs] get_pending_signals: Dropping undecodable inbox entry: {:?}", e),
+        }
+    }
+    pending.sort_by_key(|p| p.seq);
+    Ok(pending)
+}
+
+/// Keeps an accepted critical signal until the UI drains it.
+pub fn store_in_inbox(sender: &AgentPubKey, seq: i64, signal: &Signal) -> ExternResult<()> {
+    let message = InboxMessage {
+        sender: sender.clone(),
+        seq,
+        payload: encode_signal(signal)?,
+        received_at: sys_time()?,
+    };
+    let entry = Entry::try_from(message.clone())?;
+    create_private_relaxed(&EntryTypes::InboxMessage(message), entry)?;
+    Ok(())
+}
+
+// Retries due (or, with `force`, all) messages; returns how many remain queued.
+fn flush_due_messages(force: bool) -> ExternResult<u32> {
+    let now = sys_time()?;
+    let mut messages = live_entries::<OutboxMessage>(UnitEntryTypes::OutboxMessage)?;
+    messages.sort_by_key(|(_, message)| message.seq);
+
+    let mut remaining = 0;
+    // A recipient that failed this round is skipped for the rest of it, which
+    // also keeps their messages in order.
+    let mut unreachable: HashSet<AgentPubKey> = HashSet::new();
+    for (action_hash, message) in messages {
+        if now.as_millis() - message.created_at.as_millis() > MESSAGE_TTL_MS {
+            warn!("[outbox.rs] flush_due_messages: Giving up on signal {} for {:?}", message.seq, message.recipient);
+            delete_entry(action_hash)?;
+            continue;
+        }
+        let due = force || message.next_attempt_at <= now;
+        if !due || unreachable.contains(&message.recipient) {
+            remaining += 1;
+            continue;
+        }
+
+        // A corrupt entry can never be delivered; drop it without holding up the rest.
+        let signal = match decode_signal(message.payload.clone()) {
+            Ok(signal) => signal,
+            Err(e) => {
+                warn!("[outbox.rs] flush_due_messages: Dropping undecodable signal {} for {:?}: {:?}", message.seq, message.recipient, e);
+                delete_entry(action_hash)?;
+                continue;
+            }
+        };
+        if deliver(&message.recipient, message.game_id.as_ref(), message.seq, &signal)? {
+            debug!("[outbox.rs] flush_due_messages: Signal {} acknowledged by {:?}", message.seq, message.recipient);
+            delete_entry(action_hash)?;
+            continue;
+        }
+        unreachable.insert(message.recipient.clone());
+        let attempts = message.attempts + 1;
+        let retry = OutboxMessage { attempts, next_attempt_at: retry_time(now, attempts)?, ..message };
+        update_entry(action_hash, &retry)?;
+        remaining += 1;
+    }
+    Ok(remaining)
+}
+
+// One delivery attempt; true once the recipient acknowledged.
+fn deliver(recipient: &AgentPubKey, session_game: Option<&ActionHash>, seq: i64, signal: &Signal) -> ExternResult<bool> {
+    let envelope = RemoteSignal { seq, sent_at: sys_time()?.as_micros(), signal: signal.clone() };
+    let payload = ExternIO::encode(envelope).map_err(|e| wasm_error!(WasmErrorInner::Guest(e.to_string())))?;
+    let response = match session_game {
+        Some(game_id) => crate::session::call_opponent(game_id, recipient, "receive_remote_signal", payload),
+        None => call_remote(recipient.clone(), zome_info()?.name, "receive_remote_signal".into(), None, payload),
+    };
+    match response {
+        Ok(ZomeCallResponse::Ok(_)) => Ok(true),
+        Ok(other) => {
+            debug!("[outbox.rs] deliver: {:?} did not acknowledge signal {}: {:?}", recipient, seq, other);
+            Ok(false)
+        }
+        Err(e) => {
+            debug!("[outbox.rs] deliver: Could not reach {:?}: {:?}", recipient, e);
+            Ok(false)
+        }
+    }
+}
+
+fn retry_time(now: Timestamp, attempts: u32) -> ExternResult<Timestamp> {
+    let backoff_ms = RETRY_BASE_MS.saturating_mul(1 << attempts.saturating_sub(1).min(16)).min(RETRY_MAX_BACKOFF_MS);
+    Ok(Timestamp::from_micros(now.as_micros() + backoff_ms * 1_000))
+}
+
+fn encode_signal(signal: &Signal) -> ExternResult<Vec<u8>> {
+    Ok(ExternIO::encode(signal).map_err(|e| wasm_error!(WasmErrorInner::Guest(e.to_string())))?.0)
+}
+
+fn decode_signal(payload: Vec<u8>) -> ExternResult<Signal> {
+    ExternIO(payload).decode().map_err(|e| wasm_error!(WasmErrorInner::Guest(e.to_string())))
+}
+
+// Entries of a private type on our chain that have been neither updated nor deleted.
+fn live_entries<T>(entry_type: UnitEntryTypes) -> ExternResult<Vec<(ActionHash, T)>>
+where
+    T: TryFrom<SerializedBytes, Error = SerializedBytesError>,
+{
+    let records = query(ChainQueryFilter::new().entry_type(entry_type.try_into()?).include_entries(true))?;
+    let mut retired: HashSet<ActionHash> = query(ChainQueryFilter::new().action_type(ActionType::Delete))?
+        .into_iter()
+        .filter_map(|record| match record.action() {
+            Action::Delete(delete) => Some(delete.deletes_address.clone()),
+            _ => None,
+        })
+        .collect();
+    for record in &records {
+        if let Action::Update(update) = record.action() {
+            retired.insert(update.original_action_address.clone());
+        }
+    }
+    Ok(records
+        .into_iter()
+        .filter(|record| !retired.contains(record.action_address()))
+        .filter_map(|record| {
+            let entry = record.entry().to_app_option::<T>().ok().flatten()?;
+            Some((record.action_address().clone(), entry))
+        })
+        .collect())
+}
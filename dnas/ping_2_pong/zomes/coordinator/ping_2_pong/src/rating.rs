@@ -0,0 +1,157 @@
+// ping_2_pong/dnas/ping_2_pong/zomes/coordinator/ping_2_pong/src/rating.rs
+use hdk::prelude::*;
+use ping_2_pong_integrity::*;
+use ping_2_pong_integrity::rating::{outcome_for, rate, Glicko2, DEFAULT_RATING};
+use crate::player::get_all_player_pubkeys;
+
+// Ratings are updated incrementally: after each countersigned MatchResult both players
+// commit a Rating entry computed from the pre-match Ratings named in the result (see
+// integrity rating_validation.rs). Reading a rating is a lookup of the latest one.
+
+/// Glicko-2 rating of a player, as of their latest rated game.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PlayerRating {
+    pub player_key: AgentPubKey,
+    pub rating: f64,
+    pub deviation: f64,
+    pub volatility: f64,
+    pub games_rated: u32,
+}
+
+impl PlayerRating {
+    fn from_values(player_key: AgentPubKey, values: Glicko2) -> Self {
+        PlayerRating {
+            player_key,
+            rating: values.rating,
+            deviation: values.deviation,
+            volatility: values.volatility,
+            games_rated: values.games_rated,
+        }
+    }
+}
+
+/// Returns the current rating of a player (defaults if they have no rated games).
+#[hdk_extern]
+pub fn get_player_rating(player_key: AgentPubKey) -> ExternResult<PlayerRating> {
+    let values = get_latest_rating(&player_key)?.map(|(_, rating)| rating.values).unwrap_or_default();
+    Ok(PlayerRating::from_values(player_key, values))
+}
+
+/// Returns all registered players sorted by rating (highest first).
+#[hdk_extern]
+pub fn get_rating_leaderboard(_: ()) -> ExternResult<Vec<PlayerRating>> {
+    let mut leaderboard = Vec::new();
+    for player_key in get_all_player_pubkeys(())? {
+        leaderboard.push(get_player_rating(player_key)?);
+    }
+    leaderboard.sort_by(|a, b| {
+        b.rating.total_cmp(&a.rating) // Sort by rating descending
+            .then_with(|| a.deviation.total_cmp(&b.deviation)) // Then by certainty
+            .then_with(|| a.player_key.cmp(&b.player_key)) // Then by player_key for consistent tie-breaking
+    });
+    Ok(leaderboard)
+}
+
+/// Scheduled after a MatchResult is committed (the chain is locked during the
+/// countersigning session): records the caller's Rating for it.
+#[hdk_extern(infallible)]
+pub fn rate_my_results(_: Option<Schedule>) -> Option<Schedule> {
+    if let Err(e) = rate_unrated_result() {
+        error!("[rating.rs] rate_my_results: {:?}", e);
+    }
+    None
+}
+
+/// Current rating value of a player, for seeding and matchmaking.
+pub fn current_rating(player: &AgentPubKey) -> ExternResult<f64> {
+    Ok(get_latest_rating(player)?.map(|(_, rating)| rating.values.rating).unwrap_or(DEFAULT_RATING))
+}
+
+/// The player's latest Rating from the DHT: the most recently linked one.
+pub fn get_latest_rating(player: &AgentPubKey) -> ExternResult<Option<(ActionHash, Rating)>> {
+    let links = get_links(
+        LinkQuery::try_new(player.clone(), LinkTypes::PlayerToRatings)?,
+        GetStrategy::default(),
+    )?;
+    let Some(link) = links.into_iter().max_by_key(|link| link.timestamp) else {
+        return Ok(None);
+    };
+    let Some(hash) = link.target.into_action_hash() else {
+        return Ok(None);
+    };
+    let Some(record) = get(hash.clone(), GetOptions::default())? else {
+        return Ok(None);
+    };
+    Ok(record.entry().to_app_option::<Rating>().ok().flatten().map(|rating| (hash, rating)))
+}
+
+/// The caller's latest Rating, read from their own chain.
+pub fn my_latest_rating() -> ExternResult<Option<(ActionHash, Rating)>> {
+    let filter = ChainQueryFilter::new()
+        .entry_type(UnitEntryTypes::Rating.try_into()?)
+        .include_entries(true);
+    Ok(query(filter)?
+        .into_iter()
+        .filter_map(|record| {
+            let rating = record.entry().to_app_option::<Rating>().ok().flatten()?;
+            Some((record.action_address().clone(), rating))
+        })
+        .next_back())
+}
+
+/// Rates the caller's newest MatchResult if it has no Rating yet. Validation only lets
+/// a new MatchResult be committed once the previous one is rated, so there is at most one.
+pub fn rate_unrated_result() -> ExternResult<()> {
+    let me = agent_info()?.agent_initial_pubkey;
+    let filter = ChainQueryFilter::new()
+        .entry_type(UnitEntryTypes::MatchResult.try_into()?)
+        .include_entries(true);
+    let Some(result_record) = query(filter)?.pop() else {
+        return Ok(());
+    };
+    let result_hash = result_record.action_address().clone();
+    if my_latest_rating()?.is_some_and(|(_, rating)| rating.match_result == result_hash) {
+        return Ok(());
+    }
+    let result = result_record
+        .entry()
+        .to_app_option::<MatchResult>()
+        .map_err(|e| wasm_error!(WasmErrorInner::Serialize(e)))?
+        .ok_or(wasm_error!(WasmErrorInner::Guest("Invalid MatchResult entry format".into())))?;
+
+    let (previous, opponent_previous) = if result.player_1 == me {
+        (&result.player_1_rating, &result.player_2_rating)
+    } else {
+        (&result.player_2_rating, &result.player_1_rating)
+    };
+    let values = rate(
+        &rating_values(previous)?,
+        &rating_values(opponent_previous)?,
+        outcome_for(&result, &me),
+    );
+    let rating_hash = create_entry(&EntryTypes::Rating(Rating {
+        player: me.clone(),
+        match_result: result_hash,
+        values,
+    }))?;
+    create_link(me, rating_hash.clone(), LinkTypes::PlayerToRatings, ())?;
+    debug!("[rating.rs] rate_unrated_result: Recorded Rating {:?} for game {:?}", rating_hash, result.game_id);
+    Ok(())
+}
+
+// --- Helpers ---
+
+// Values of a pre-match Rating named in a MatchResult (defaults when there is none).
+fn rating_values(rating_hash: &Option<ActionHash>) -> ExternResult<Glicko2> {
+    let Some(rating_hash) = rating_hash else {
+        return Ok(Glicko2::default());
+    };
+    let record = get(rating_hash.clone(), GetOptions::default())?
+        .ok_or(wasm_error!(WasmErrorInner::Guest(format!("Pre-match Rating not found: {}", rating_hash))))?;
+    let rating = record
+        .entry()
+        .to_app_option::<Rating>()
+        .map_err(|e| wasm_error!(WasmErrorInner::Serialize(e)))?
+        .ok_or(wasm_error!(WasmErrorInner::Guest("Invalid Rating entry format".into())))?;
+    Ok(rating.values)
+}
@@ -0,0 +1,364 @@
+// ping_2_pong/dnas/ping_2_pong/zomes/coordinator/ping_2_pong/src/replay.rs
+//! Opt-in game replay recording.
+//!
+//! While a game is running the UI hands batches of the real-time signals it
+//! sent/received to `record_replay_frames` (frame batches are recorded as the
+//! individual signals they pack). Batches are kept as private
+//! `ReplayBuffer` entries on the recorder's own chain. When the game finishes
+//! they are merged, delta-encoded and published as `ReplayChunk` entries
+//! linked from the game, which `get_replay` turns back into ordered frames.
+use hdk::prelude::*;
+use ping_2_pong_integrity::*;
+use crate::codec::{ByteReader, ByteWriter};
+use crate::game::get_latest_game_entry;
+use crate::utils::get_game_hash_by_id;
+use crate::lockstep::MAX_INPUTS_PER_FRAME;
+use crate::Signal;
+use pong_sim::PaddleInput;
+use std::collections::BTreeMap;
+
+// Frames per published chunk; each chunk is decodable on its own.
+const CHUNK_FRAMES: usize = 2_000;
+// Upper bound on a single recording call, keeps buffer entries small.
+const MAX_FRAMES_PER_BATCH: usize = 5_000;
+
+const TAG_PADDLE_UPDATE: u8 = 0;
+const TAG_PADDLE_HIT: u8 = 1;
+const TAG_BALL_UPDATE: u8 = 2;
+const TAG_SCORE_UPDATE: u8 = 3;
+const TAG_INPUT_FRAME: u8 = 4;
+
+/// A single recorded real-time signal with the time it was observed.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ReplayFrame {
+    pub timestamp: Timestamp,
+    pub signal: Signal,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RecordReplayFramesInput {
+    pub game_id: ActionHash,
+    pub frames: Vec<ReplayFrame>,
+}
+
+/// Buffers a batch of frames for the game. Returns None if nothing recordable was passed.
+#[hdk_extern]
+pub fn record_replay_frames(input: RecordReplayFramesInput) -> ExternResult<Option<ActionHash>> {
+    debug!("[replay.rs] record_replay_frames: {} frames for game {:?}", input.frames.len(), input.game_id);
+    if input.frames.len() > MAX_FRAMES_PER_BATCH {
+        return Err(wasm_error!(WasmErrorInner::Guest(format!(
+            "Too many frames in one batch (max {})", MAX_FRAMES_PER_BATCH
+        ))));
+    }
+    let players = get_game_players(&input.game_id)?;
+    let me = agent_info()?.agent_initial_pubkey;
+    if !players.contains(&me) {
+        return Err(wasm_error!(WasmErrorInner::Guest("Only game participants can record a replay".into())));
+    }
+
+    let mut frames: Vec<ReplayFrame> = input
+        .frames
+        .into_iter()
+        .flat_map(|frame| match &frame.signal {
+            Signal::FrameBatch { game_id, player, events, .. } => crate::frame_batch::expand_batch(game_id, player, events)
+                .into_iter()
+                .map(|signal| ReplayFrame { timestamp: frame.timestamp, signal })
+                .collect(),
+            _ => vec![frame],
+        })
+        .filter(|frame| is_recordable(&frame.signal, &input.game_id))
+        .collect();
+    if frames.is_empty() {
+        return Ok(None);
+    }
+    frames.sort_by_key(|frame| frame.timestamp);
+
+    let buffer = ReplayBuffer {
+        game_id: input.game_id,
+        start_time: frames[0].timestamp,
+        frame_count: frames.len() as u32,
+        data: encode_frames(&frames, &players)?,
+    };
+    let action_hash = create_entry(&EntryTypes::ReplayBuffer(buffer))?;
+    Ok(Some(action_hash))
+}
+
+/// Publishes this agent's buffered frames for a game as linked ReplayChunks.
+/// Called from `finish_game`; a no-op if nothing was recorded or it was already published.
+#[hdk_extern]
+pub fn commit_replay(game_id: ActionHash) -> ExternResult<Vec<ActionHash>> {
+    let me = agent_info()?.agent_initial_pubkey;
+    let already_published = get_links(
+        LinkQuery::try_new(game_id.clone(), LinkTypes::GameToReplayChunks)?,
+        GetStrategy::Local,
+    )?
+    .into_iter()
+    .any(|link| link.author == me);
+    if already_published {
+        debug!("[replay.rs] commit_replay: Replay for {:?} already published.", game_id);
+        return Ok(vec![]);
+    }
+
+    let (game_revision, players) = get_game_revision_and_players(&game_id)?;
+    let filter = ChainQueryFilter::new()
+        .entry_type(UnitEntryTypes::ReplayBuffer.try_into()?)
+        .include_entries(true);
+    let mut frames: Vec<ReplayFrame> = Vec::new();
+    for record in query(filter)? {
+        let Ok(Some(buffer)) = record.entry().to_app_option::<ReplayBuffer>() else { continue };
+        if buffer.game_id != game_id {
+            continue;
+        }
+        frames.extend(decode_frames(&buffer.data, buffer.start_time, &game_id, &players)?);
+    }
+    if frames.is_empty() {
+        return Ok(vec![]);
+    }
+    frames.sort_by_key(|frame| frame.timestamp);
+
+    let mut chunk_hashes = Vec::new();
+    for (index, slice) in frames.chunks(CHUNK_FRAMES).enumerate() {
+        let chunk = ReplayChunk {
+            game_id: game_id.clone(),
+            game_revision: game_revision.clone(),
+            chunk_index: index as u32,
+            start_time: slice[0].timestamp,
+            frame_count: slice.len() as u32,
+            data: encode_frames(slice, &players)?,
+        };
+        let chunk_hash = create_entry(&EntryTypes::ReplayChunk(chunk))?;
+        create_link(game_id.clone(), chunk_hash.clone(), LinkTypes::GameToReplayChunks, ())?;
+        chunk_hashes.push(chunk_hash);
+    }
+    debug!("[replay.rs] commit_replay: Published {} frames in {} chunks.", frames.len(), chunk_hashes.len());
+    Ok(chunk_hashes)
+}
+
+/// Returns the recorded frames of a game in playback order.
+/// If both players recorded, Player 1's recording is used. Chunk frame counts are
+/// self-declared, so they cannot be what picks the recording.
+#[hdk_extern]
+pub fn get_replay(game_id: ActionHash) -> ExternResult<Vec<ReplayFrame>> {
+    let players = get_game_players(&game_id)?;
+    let links = get_links(
+        LinkQuery::try_new(game_id.clone(), LinkTypes::GameToReplayChunks)?,
+        GetStrategy::default(),
+    )?;
+    let get_inputs: Vec<GetInput> = links
+        .into_iter()
+        .filter_map(|link| link.target.into_action_hash())
+        .map(|ah| GetInput::new(ah.into(), GetOptions::default()))
+        .collect();
+    if get_inputs.is_empty() {
+        return Ok(vec![]);
+    }
+    let records = HDK.with(|hdk| hdk.borrow().get(get_inputs))?;
+
+    // Group chunks by recorder
+    let mut by_author: BTreeMap<AgentPubKey, Vec<ReplayChunk>> = BTreeMap::new();
+    for record in records.into_iter().flatten() {
+        if let Ok(Some(chunk)) = record.entry().to_app_option::<ReplayChunk>() {
+            if chunk.game_id == game_id {
+                by_author.entry(record.action().author().clone()).or_default().push(chunk);
+            }
+        }
+    }
+    let Some(mut chunks) = players.iter().find_map(|player| by_author.remove(player)) else {
+        return Ok(vec![]);
+    };
+    chunks.sort_by_key(|chunk| chunk.chunk_index);
+    chunks.dedup_by_key(|chunk| chunk.chunk_index);
+
+    let mut frames = Vec::new();
+    for chunk in chunks {
+        frames.extend(decode_frames(&chunk.data, chunk.start_time, &game_id, &players)?);
+    }
+    Ok(frames)
+}
+
+// Both players of the latest game revision, player_1 first.
+fn get_game_players(game_id: &ActionHash) -> ExternResult<[AgentPubKey; 2]> {
+    Ok(get_game_revision_and_players(game_id)?.1)
+}
+
+// The latest Game revision, which names both players, and the players themselves.
+fn get_game_revision_and_players(game_id: &ActionHash) -> ExternResult<(ActionHash, [AgentPubKey; 2])> {
+    let original_hash = get_game_hash_by_id(game_id)?
+        .ok_or(wasm_error!(WasmErrorInner::Guest(format!("Game ID does not exist: {}", game_id))))?;
+    let (revision, game) = get_latest_game_entry(&original_hash)?;
+    let player_2 = game
+        .player_2
+        .ok_or(wasm_error!(WasmErrorInner::Guest("Game has no second player yet".into())))?;
+    Ok((revision, [game.player_1, player_2]))
+}
+
+fn is_recordable(signal: &Signal, game: &ActionHash) -> bool {
+    match signal {
+        Signal::PaddleUpdate { game_id, .. }
+        | Signal::PaddleHit { game_id, .. }
+        | Signal::BallUpdate { game_id, .. }
+        | Signal::InputFrame { game_id, .. }
+        | Signal::ScoreUpdate { game_id, .. } => game_id == game,
+        _ => false,
+    }
+}
+
+fn player_index(players: &[AgentPubKey; 2], player: &AgentPubKey) -> ExternResult<u8> {
+    players
+        .iter()
+        .position(|p| p == player)
+        .map(|i| i as u8)
+        .ok_or(wasm_error!(WasmErrorInner::Guest("Replay frame references a non-participant".into())))
+}
+
+// Running values that frames are delta-encoded against.
+#[derive(Default)]
+struct DeltaState {
+    last_ms: i64,
+    paddles: [i32; 2],
+    ball: [i32; 4],
+}
+
+/// Encodes frames as: tag, time delta (ms), then per-kind fields as deltas
+/// against the previous frame of the same kind.
+fn encode_frames(frames: &[ReplayFrame], players: &[AgentPubKey; 2]) -> ExternResult<Vec<u8>> {
+    let mut out = ByteWriter::new();
+    let mut state = DeltaState {
+        last_ms: frames.first().map(|f| f.timestamp.as_millis()).unwrap_or_default(),
+        ..Default::default()
+    };
+    for frame in frames {
+        let ms = frame.timestamp.as_millis();
+        let time_delta = (ms - state.last_ms).max(0) as u64;
+        state.last_ms = ms.max(state.last_ms);
+        match &frame.signal {
+            Signal::PaddleUpdate { player, paddle_y, .. } => {
+                let index = player_index(players, player)?;
+                out.put_u8(TAG_PADDLE_UPDATE);
+                out.put_uvarint(time_delta);
+                out.put_u8(index);
+                out.put_ivarint(*paddle_y as i64 - state.paddles[index as usize] as i64);
+                state.paddles[index as usize] = *paddle_y;
+            }
+            Signal::PaddleHit { player, ball_y, .. } => {
+                out.put_u8(TAG_PADDLE_HIT);
+                out.put_uvarint(time_delta);
+                out.put_u8(player_index(players, player)?);
+                out.put_ivarint(*ball_y as i64);
+            }
+            Signal::BallUpdate { ball_x, ball_y, ball_dx, ball_dy, score1, score2, .. } => {
+                out.put_u8(TAG_BALL_UPDATE);
+                out.put_uvarint(time_delta);
+                let ball = [*ball_x, *ball_y, *ball_dx, *ball_dy];
+                for (value, last) in ball.iter().zip(state.ball.iter()) {
+                    out.put_ivarint(*value as i64 - *last as i64);
+                }
+                state.ball = ball;
+                out.put_u8(score1.is_some() as u8 | (score2.is_some() as u8) << 1);
+                for score in [score1, score2].into_iter().flatten() {
+                    out.put_uvarint(*score as u64);
+                }
+            }
+            Signal::ScoreUpdate { score1, score2, .. } => {
+                out.put_u8(TAG_SCORE_UPDATE);
+                out.put_uvarint(time_delta);
+                out.put_uvarint(*score1 as u64);
+                out.put_uvarint(*score2 as u64);
+            }
+            Signal::InputFrame { player, first_frame, inputs, .. } => {
+                // The count is read back as is, so an over-long frame would desync the stream.
+                if inputs.len() > MAX_INPUTS_PER_FRAME {
+                    return Err(wasm_error!(WasmErrorInner::Guest(format!(
+                        "An input frame carries at most {} inputs", MAX_INPUTS_PER_FRAME
+                    ))));
+                }
+                out.put_u8(TAG_INPUT_FRAME);
+                out.put_uvarint(time_delta);
+                out.put_u8(player_index(players, player)?);
+                out.put_uvarint(*first_frame as u64);
+                out.put_uvarint(inputs.len() as u64);
+                for input in inputs {
+                    out.put_u8(encode_input(*input));
+                }
+            }
+            _ => continue,
+        }
+    }
+    Ok(out.into_bytes())
+}
+
+fn encode_input(input: PaddleInput) -> u8 {
+    match input {
+        PaddleInput::Idle => 0,
+        PaddleInput::Up => 1,
+        PaddleInput::Down => 2,
+    }
+}
+
+fn decode_input(byte: u8) -> PaddleInput {
+    match byte {
+        1 => PaddleInput::Up,
+        2 => PaddleInput::Down,
+        _ => PaddleInput::Idle,
+    }
+}
+
+fn decode_frames(
+    data: &[u8],
+    start_time: Timestamp,
+    game_id: &ActionHash,
+    players: &[AgentPubKey; 2],
+) -> ExternResult<Vec<ReplayFrame>> {
+    let mut reader = ByteReader::new(data);
+    let mut state = DeltaState { last_ms: start_time.as_millis(), ..Default::default() };
+    let mut frames = Vec::new();
+    while !reader.is_empty() {
+        let tag = reader.get_u8()?;
+        state.last_ms += reader.get_uvarint()? as i64;
+        let signal = match tag {
+            TAG_PADDLE_UPDATE => {
+                let index = (reader.get_u8()? as usize).min(1);
+                let paddle_y = (state.paddles[index] as i64 + reader.get_ivarint()?) as i32;
+                state.paddles[index] = paddle_y;
+                Signal::PaddleUpdate { game_id: game_id.clone(), player: players[index].clone(), paddle_y }
+            }
+            TAG_PADDLE_HIT => {
+                let index = (reader.get_u8()? as usize).min(1);
+                Signal::PaddleHit { game_id: game_id.clone(), player: players[index].clone(), ball_y: reader.get_i32()? }
+            }
+            TAG_BALL_UPDATE => {
+                for value in state.ball.iter_mut() {
+                    *value = (*value as i64 + reader.get_ivarint()?) as i32;
+                }
+                let flags = reader.get_u8()?;
+                let score1 = if flags & 1 != 0 { Some(reader.get_u32()?) } else { None };
+                let score2 = if flags & 2 != 0 { Some(reader.get_u32()?) } else { None };
+                let [ball_x, ball_y, ball_dx, ball_dy] = state.ball;
+                Signal::BallUpdate { game_id: game_id.clone(), ball_x, ball_y, ball_dx, ball_dy, score1, score2 }
+            }
+            TAG_SCORE_UPDATE => Signal::ScoreUpdate {
+                game_id: game_id.clone(),
+                score1: reader.get_u32()?,
+                score2: reader.get_u32()?,
+            },
+            TAG_INPUT_FRAME => {
+                let index = (reader.get_u8()? as usize).min(1);
+                let first_frame = reader.get_u32()?;
+                let count = reader.get_uvarint()? as usize;
+                if count > MAX_INPUTS_PER_FRAME {
+                    return Err(wasm_error!(WasmErrorInner::Guest("Replay input frame carries too many inputs".into())));
+                }
+                let mut inputs = Vec::with_capacity(count);
+                for _ in 0..count {
+                    inputs.push(decode_input(reader.get_u8()?));
+                }
+                Signal::InputFrame { game_id: game_id.clone(), player: players[index].clone(), first_frame, inputs }
+            }
+            other => {
+                return Err(wasm_error!(WasmErrorInner::Guest(format!("Unknown replay frame tag {}", other))));
+            }
+        };
+        frames.push(ReplayFrame { timestamp: Timestamp::from_micros(state.last_ms * 1_000), signal });
+    }
+    Ok(frames)
+}
@@ -0,0 +1,317 @@
+// ping_2_pong/dnas/ping_2_pong/zomes/coordinator/ping_2_pong/src/resume.rs
+//! Checkpoints and resuming interrupted games.
+//!
+//! While a game runs the UI publishes `GameCheckpoint`s (score and serve
+//! state). They let a player whose conductor restarted pick the game up again
+//! with `resume_game`, and they show when each player was last active. A game
+//! with no activity for longer than the grace period (DNA property
+//! `resume_grace_period_secs`) is stale: it no longer blocks its players from
+//! new games, and resuming it abandons it instead. When only one player went
+//! quiet, the one who stayed can end it with `claim_forfeit` and win.
+use hdk::prelude::*;
+use ping_2_pong_integrity::*;
+use ping_2_pong_integrity::game::{ForfeitEvidence, GameStatus};
+use ping_2_pong_integrity::game_lifecycle::{check_forfeit, check_transition};
+use ping_2_pong_integrity::properties::dna_properties;
+use crate::game::{abandon_game, game_from_record, get_latest_game, get_latest_game_entry};
+use crate::game_index::reindex_game_status;
+use crate::outbox::send_reliable;
+use crate::session::establish_game_session;
+use crate::utils::anchor_for;
+use crate::Signal;
+
+// A player's checkpoints closer together than this are skipped unless the score changed.
+const CHECKPOINT_MIN_INTERVAL_MS: i64 = 10_000;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SaveCheckpointInput {
+    pub game_id: ActionHash,
+    pub score_1: u32,
+    pub score_2: u32,
+    pub serve_to: Option<AgentPubKey>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ResumeGameOutput {
+    pub game: Record,                         // Latest game record
+    pub checkpoint: Option<GameCheckpoint>,   // Most recent checkpoint by either player
+    pub resumed: bool,                        // False if the game had ended or went stale (and was abandoned)
+}
+
+/// Publishes a checkpoint of an in-progress game. Returns None when skipped
+/// because the caller checkpointed the same score moments ago.
+#[hdk_extern]
+pub fn save_game_checkpoint(input: SaveCheckpointInput) -> ExternResult<Option<ActionHash>> {
+    let me = agent_info()?.agent_initial_pubkey;
+    let record = get_latest_game(input.game_id.clone())?
+        .ok_or(wasm_error!(WasmErrorInner::Guest(format!("Game does not exist: {}", input.game_id))))?;
+    let game = game_from_record(&record)?;
+    if game.game_status != GameStatus::InProgress {
+        return Err(wasm_error!(WasmErrorInner::Guest("Only in-progress games can be checkpointed".into())));
+    }
+
+    let now = sys_time()?;
+    if let Some((link, checkpoint)) = latest_checkpoint_by(&input.game_id, &me)? {
+        let unchanged = checkpoint.score_1 == input.score_1 && checkpoint.score_2 == input.score_2;
+        if unchanged && now.as_millis() - link.timestamp.as_millis() < CHECKPOINT_MIN_INTERVAL_MS {
+            return Ok(None);
+        }
+    }
+    create_checkpoint(GameCheckpoint {
+        game_id: input.game_id,
+        game_revision: record.action_address().clone(),
+        player: me,
+        score_1: input.score_1,
+        score_2: input.score_2,
+        serve_to: input.serve_to,
+        taken_at: now,
+    })
+    .map(Some)
+}
+
+/// Most recent checkpoint of a game by either player.
+#[hdk_extern]
+pub fn get_latest_checkpoint(game_id: ActionHash) -> ExternResult<Option<GameCheckpoint>> {
+    let (_, game) = get_latest_game_entry(&game_id)?;
+    let is_player = |agent: &AgentPubKey| *agent == game.player_1 || game.player_2.as_ref() == Some(agent);
+    // Validation only admits the players' checkpoints; anything else is ignored.
+    let mut links: Vec<Link> = checkpoint_links(&game_id)?.into_iter().filter(|link| is_player(&link.author)).collect();
+    links.sort_by_key(|link| link.timestamp);
+    for link in links.into_iter().rev() {
+        if let Some(checkpoint) = get_checkpoint(&link)?.filter(|c| c.game_id == game_id && c.player == link.author) {
+            return Ok(Some(checkpoint));
+        }
+    }
+    Ok(None)
+}
+
+/// Picks an interrupted game back up: re-opens the game session with the
+/// opponent and tells them we are back. A stale game is abandoned instead.
+#[hdk_extern]
+pub fn resume_game(game_id: ActionHash) -> ExternResult<ResumeGameOutput> {
+    let me = agent_info()?.agent_initial_pubkey;
+    let record = get_latest_game(game_id.clone())?
+        .ok_or(wasm_error!(WasmErrorInner::Guest(format!("Game does not exist: {}", game_id))))?;
+    let game = game_from_record(&record)?;
+    let opponent = match &game.player_2 {
+        Some(player_2) if game.player_1 == me => player_2.clone(),
+        Some(player_2) if *player_2 == me => game.player_1.clone(),
+        _ => return Err(wasm_error!(WasmErrorInner::Guest("Not a participant of this game".into()))),
+    };
+    let checkpoint = get_latest_checkpoint(game_id.clone())?;
+
+    if game.game_status != GameStatus::InProgress {
+        return Ok(ResumeGameOutput { game: record, checkpoint, resumed: false });
+    }
+    if is_stale(&game_id, &record)? {
+        debug!("[resume.rs] resume_game: Game {:?} is past its grace period, abandoning", game_id);
+        let abandoned = abandon_game(game_id)?;
+        return Ok(ResumeGameOutput { game: abandoned, checkpoint, resumed: false });
+    }
+
+    // A restart loses nothing on our chain, but the opponent may have lost its
+    // session state too; a fresh handshake is cheap and makes sure.
+    if let Err(e) = establish_game_session(&game_id, &record) {
+        warn!("[resume.rs] resume_game: Could not re-open game session with {:?}: {:?}", opponent, e);
+    }
+    // Record our return, which also restarts the grace period.
+    let (score_1, score_2, serve_to) = checkpoint
+        .as_ref()
+        .map(|c| (c.score_1, c.score_2, c.serve_to.clone()))
+        .unwrap_or((0, 0, None));
+    create_checkpoint(GameCheckpoint {
+        game_id: game_id.clone(),
+        game_revision: record.action_address().clone(),
+        player: me.clone(),
+        score_1,
+        score_2,
+        serve_to,
+        taken_at: sys_time()?,
+    })?;
+
+    let signal = Signal::GameResumed { game_id: game_id.clone(), player: me, checkpoint: checkpoint.clone() };
+    emit_signal(&signal)?;
+    send_reliable(&opponent, Some(&game_id), &signal)?;
+    Ok(ResumeGameOutput { game: record, checkpoint, resumed: true })
+}
+
+/// Ends an in-progress game whose opponent has shown no activity (checkpoints
+/// or presence) for longer than the grace period, with the caller as winner.
+/// The last-seen times are recorded on the finished game as evidence.
+#[hdk_extern]
+pub fn claim_forfeit(game_id: ActionHash) -> ExternResult<Record> {
+    let me = agent_info()?.agent_initial_pubkey;
+    let record = get_latest_game(game_id.clone())?
+        .ok_or(wasm_error!(WasmErrorInner::Guest(format!("Game does not exist: {}", game_id))))?;
+    let previous_game = game_from_record(&record)?;
+    if previous_game.game_status != GameStatus::InProgress {
+        return Err(wasm_error!(WasmErrorInner::Guest("Only in-progress games can be forfeited".into())));
+    }
+    let opponent = match &previous_game.player_2 {
+        Some(player_2) if previous_game.player_1 == me => player_2.clone(),
+        Some(player_2) if *player_2 == me => previous_game.player_1.clone(),
+        _ => return Err(wasm_error!(WasmErrorInner::Guest("Not a participant of this game".into()))),
+    };
+
+    // The opponent counts as present while they checkpoint the game or publish
+    // presence in the lobby (they may be about to resume).
+    let (opponent_last_seen, opponent_last_action) = last_activity_of(&opponent, &game_id, &record)?.ok_or(wasm_error!(
+        WasmErrorInner::Guest("Opponent has no activity in this game to cite; abandon it instead".into())
+    ))?;
+    let grace_period_secs = dna_properties()?.resume_grace_period_secs;
+    let idle_ms = idle_for_ms(opponent_last_seen)?;
+    if idle_ms <= grace_period_ms()? {
+        return Err(wasm_error!(WasmErrorInner::Guest(format!(
+            "Opponent was last seen {}s ago; a forfeit can be claimed after {}s", idle_ms / 1_000, grace_period_secs
+        ))));
+    }
+
+    let now = sys_time()?;
+    let evidence = ForfeitEvidence {
+        winner: me.clone(),
+        winner_last_seen: now,
+        opponent_last_seen,
+        opponent_last_action,
+        grace_period_secs,
+    };
+    let mut game = previous_game.clone();
+    game.game_status = GameStatus::Finished;
+    game.forfeit = Some(evidence.clone());
+    check_transition(&previous_game, &game, &me)
+        .and_then(|_| check_forfeit(&game, &evidence, &me, now, grace_period_secs))
+        .map_err(|e| wasm_error!(WasmErrorInner::Guest(format!("Cannot claim forfeit: {}", e))))?;
+
+    let update_action_hash = update_entry(record.action_address().clone(), &game)?;
+    create_link(game_id.clone(), update_action_hash.clone(), LinkTypes::GameUpdates, ())?;
+    reindex_game_status(&game_id, &game, &previous_game.game_status)?;
+    debug!("[resume.rs] claim_forfeit: {:?} won game {:?} by forfeit", me, game_id);
+
+    // Queued until the opponent is back, so they learn how the game ended.
+    let (score1, score2) = get_latest_checkpoint(game_id.clone())?
+        .map(|c| (c.score_1, c.score_2))
+        .unwrap_or((0, 0));
+    let signal = Signal::GameOver { game_id: game_id.clone(), winner: Some(me), score1, score2 };
+    emit_signal(&signal)?;
+    send_reliable(&opponent, Some(&game_id), &signal)?;
+    // Starts the next game if this one was part of a series.
+    schedule("advance_my_series")?;
+
+    get(update_action_hash.clone(), GetOptions::default())?.ok_or(wasm_error!(WasmErrorInner::Guest(format!(
+        "Could not find the updated Game record after forfeit: {:?}", update_action_hash
+    ))))
+}
+
+/// When and by which action `player` last showed activity in the game: their
+/// latest checkpoint of it, lobby presence or revision of the game, whichever is
+/// newest. None if they have shown none. This is what a forfeit claim cites as
+/// evidence, so only actions authored by `player` count.
+pub fn last_activity_of(player: &AgentPubKey, game_id: &ActionHash, latest_record: &Record) -> ExternResult<Option<(Timestamp, ActionHash)>> {
+    let mut last = last_revision_by(player, latest_record)?;
+    let checkpoint = checkpoint_links(game_id)?
+        .into_iter()
+        .filter(|link| link.author == *player)
+        .max_by_key(|link| link.timestamp)
+        .and_then(|link| link.target.into_action_hash());
+    if let Some(checkpoint_hash) = checkpoint {
+        if let Some(record) = get(checkpoint_hash, GetOptions::default())? {
+            if record.action().author() == player {
+                last = last.max(Some((record.action().timestamp(), record.action_address().clone())));
+            }
+        }
+    }
+    last = last.max(last_presence(player)?);
+    Ok(last)
+}
+
+// The newest revision of the game authored by `player`, walking back from the latest.
+fn last_revision_by(player: &AgentPubKey, latest_record: &Record) -> ExternResult<Option<(Timestamp, ActionHash)>> {
+    let mut record = latest_record.clone();
+    loop {
+        if record.action().author() == player {
+            return Ok(Some((record.action().timestamp(), record.action_address().clone())));
+        }
+        let Action::Update(update) = record.action() else { return Ok(None) };
+        let previous = update.original_action_address.clone();
+        record = get(previous.clone(), GetOptions::default())?
+            .ok_or(wasm_error!(WasmErrorInner::Guest(format!("Game revision not found: {}", previous))))?;
+    }
+}
+
+/// True if nobody has been active in the game for longer than the grace period.
+/// A game without checkpoints gives no evidence either way (the UI may not have
+/// checkpointed yet), so it is only stale once both players are gone from the
+/// lobby too.
+pub fn is_stale(game_id: &ActionHash, latest_record: &Record) -> ExternResult<bool> {
+    let game = game_from_record(latest_record)?;
+    let is_player = |agent: &AgentPubKey| *agent == game.player_1 || game.player_2.as_ref() == Some(agent);
+    let mut last_activity = checkpoint_links(game_id)?
+        .into_iter()
+        .filter(|link| is_player(&link.author))
+        .map(|link| link.timestamp)
+        .max();
+    if last_activity.is_none() {
+        for player in std::iter::once(&game.player_1).chain(game.player_2.as_ref()) {
+            let Some((presence, _)) = last_presence(player)? else {
+                // Never seen in the lobby: nothing says they left.
+                return Ok(false);
+            };
+            last_activity = last_activity.max(Some(presence));
+        }
+    }
+    let last_activity = last_activity.unwrap_or_else(|| latest_record.action().timestamp()).max(latest_record.action().timestamp());
+    Ok(idle_for_ms(last_activity)? > grace_period_ms()?)
+}
+
+/// Grace period for interrupted games, from the DNA properties.
+pub fn grace_period_ms() -> ExternResult<i64> {
+    Ok((dna_properties()?.resume_grace_period_secs as i64).saturating_mul(1_000))
+}
+
+/// Milliseconds since `since`.
+pub fn idle_for_ms(since: Timestamp) -> ExternResult<i64> {
+    Ok(sys_time()?.as_millis() - since.as_millis())
+}
+
+// Latest lobby presence link created by `agent`, if any.
+fn last_presence(agent: &AgentPubKey) -> ExternResult<Option<(Timestamp, ActionHash)>> {
+    let links = get_links(
+        LinkQuery::try_new(anchor_for("presence")?, LinkTypes::Presence)?,
+        GetStrategy::default(),
+    )?;
+    Ok(links
+        .into_iter()
+        .filter(|link| link.author == *agent)
+        .map(|link| (link.timestamp, link.create_link_hash))
+        .max())
+}
+
+fn create_checkpoint(checkpoint: GameCheckpoint) -> ExternResult<ActionHash> {
+    let game_id = checkpoint.game_id.clone();
+    let checkpoint_hash = create_entry(&EntryTypes::GameCheckpoint(checkpoint))?;
+    create_link(game_id, checkpoint_hash.clone(), LinkTypes::GameToCheckpoints, ())?;
+    Ok(checkpoint_hash)
+}
+
+fn checkpoint_links(game_id: &ActionHash) -> ExternResult<Vec<Link>> {
+    get_links(
+        LinkQuery::try_new(game_id.clone(), LinkTypes::GameToCheckpoints)?,
+        GetStrategy::default(),
+    )
+}
+
+fn latest_checkpoint_by(game_id: &ActionHash, player: &AgentPubKey) -> ExternResult<Option<(Link, GameCheckpoint)>> {
+    let Some(link) = checkpoint_links(game_id)?
+        .into_iter()
+        .filter(|link| link.author == *player)
+        .max_by_key(|link| link.timestamp)
+    else {
+        return Ok(None);
+    };
+    Ok(get_checkpoint(&link)?.map(|checkpoint| (link, checkpoint)))
+}
+
+fn get_checkpoint(link: &Link) -> ExternResult<Option<GameCheckpoint>> {
+    let Some(hash) = link.target.clone().into_action_hash() else { return Ok(None) };
+    let Some(record) = get(hash, GetOptions::default())? else { return Ok(None) };
+    Ok(record.entry().to_app_option::<GameCheckpoint>().ok().flatten())
+}
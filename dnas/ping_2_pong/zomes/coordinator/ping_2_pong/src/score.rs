@@ -2,90 +2,59 @@
 use hdk::prelude::*;
 use ping_2_pong_integrity::*;
 use crate::utils::get_game_hash_by_id; // Use helper
-use ping_2_pong_integrity::Game; // Assuming Game is also directly available
-
-// Maximum allowed score points.
-const MAX_POINTS: u32 = 10000; // Keep high for flexibility, game logic enforces 10
-
-#[derive(Serialize, Deserialize, Debug, Clone)]
-pub struct CreateScoreInput {
-    pub game_id: ActionHash,
-    pub player: AgentPubKey,
-    pub player_points: u32,
-}
 
+/// Records the caller's Score from a countersigned MatchResult.
+/// Points are taken from the result itself, so they cannot be chosen by the caller.
+/// Calling it again for the same result returns the Score already recorded.
 #[hdk_extern]
-pub fn create_score(input: CreateScoreInput) -> ExternResult<Record> {
-    debug!("[score.rs] create_score: Called with input: {:?}", input);
-
-    // --- Validation ---
-    // Ensure the game_id corresponds to an actual Game entry
-    // Note: game_id in Score struct should be the original ActionHash of the game creation
-    let game_action_hash = get_game_hash_by_id(&input.game_id)?
-        .ok_or(wasm_error!(WasmErrorInner::Guest(format!("Game ID does not exist: {}", input.game_id))))?;
-
-    // Fetch the *latest* game state record to check status
-    debug!("[score.rs] create_score: Fetching game record for game_id: {:?}", input.game_id);
-    match crate::game::get_latest_game(game_action_hash.clone()) { // game_action_hash is the original_game_hash needed by get_latest_game
-        Ok(Some(game_record)) => {
-            match game_record.entry().to_app_option::<ping_2_pong_integrity::Game>() {
-                Ok(Some(game_entry)) => {
-                    debug!("[score.rs] create_score: For game_id {:?}, current game status is: {:?}", input.game_id, game_entry.game_status);
-                    if game_entry.game_status != ping_2_pong_integrity::game::GameStatus::Finished {
-                        debug!("[score.rs] create_score: WARNING - Attempting to create score for a game (id: {:?}) not in 'Finished' state. Actual status: {:?}", input.game_id, game_entry.game_status);
-                        // Note: Original code returns error here, which is good. This log is just an additional warning.
-                        // The original error will be hit below if this condition is true.
-                    }
-                }
-                Err(e) => debug!("[score.rs] create_score: Error deserializing game entry for game_id {:?}: {:?}", input.game_id, e),
-                Ok(None) => debug!("[score.rs] create_score: Game entry data not found for game_id {:?}", input.game_id),
-            }
-        }
-        Err(e) => debug!("[score.rs] create_score: Error fetching game record for game_id {:?}: {:?}", input.game_id, e),
-        Ok(None) => debug!("[score.rs] create_score: No game record found for game_id {:?}", input.game_id),
+pub fn create_score(match_result_hash: ActionHash) -> ExternResult<Record> {
+    debug!("[score.rs] create_score: Called with match_result_hash: {:?}", match_result_hash);
+    let my_pub_key = agent_info()?.agent_initial_pubkey;
+
+    // Integrity allows one Score per MatchResult, so a retry gets the existing one.
+    if let Some(existing) = find_my_score(&match_result_hash)? {
+        debug!("[score.rs] create_score: Score already recorded for {:?}", match_result_hash);
+        return Ok(existing);
     }
 
-    // Re-fetch for actual validation logic (original code structure)
-    let game_record_for_validation = crate::game::get_latest_game(game_action_hash.clone())? // Use cloned game_action_hash
-        .ok_or(wasm_error!(WasmErrorInner::Guest("Game record not found for validation".into())))?;
-    let game_for_validation = game_record_for_validation
+    // --- Validation ---
+    let result_record = get(match_result_hash.clone(), GetOptions::default())?
+        .ok_or(wasm_error!(WasmErrorInner::Guest(format!("MatchResult does not exist: {}", match_result_hash))))?;
+    let result = result_record
         .entry()
-        .to_app_option::<Game>() // Assuming Game is directly available from integrity
+        .to_app_option::<MatchResult>()
         .map_err(|e| wasm_error!(WasmErrorInner::Serialize(e)))?
-        .ok_or(wasm_error!(WasmErrorInner::Guest("Invalid Game entry format for validation".into())))?;
+        .ok_or(wasm_error!(WasmErrorInner::Guest("Invalid MatchResult entry format".into())))?;
 
-    // Ensure the score is being assigned to a player who was actually in the game.
-    if input.player != game_for_validation.player_1 && game_for_validation.player_2.as_ref() != Some(&input.player) {
+    // Ensure the game_id corresponds to an actual Game entry
+    let _ = get_game_hash_by_id(&result.game_id)?
+        .ok_or(wasm_error!(WasmErrorInner::Guest(format!("Game ID does not exist: {}", result.game_id))))?;
+
+    let player_points = if result.player_1 == my_pub_key {
+        result.player_1_score
+    } else if result.player_2 == my_pub_key {
+        result.player_2_score
+    } else {
         return Err(wasm_error!(WasmErrorInner::Guest(
-            "Score must be assigned to a player who participated in the game".into()
+            "Only players of the match result can record a score from it".into()
         )));
-    }
-
-    // Ensure caller is one of the players in the game? Or allow anyone to record score?
-    // Let's allow anyone for now, assuming UI calls this after game ends for both players.
-    // let my_pub_key = agent_info()?.agent_latest_pubkey;
-    // if my_pub_key != game_for_validation.player_1 && game_for_validation.player_2.as_ref() != Some(&my_pub_key) {
-    //     return Err(wasm_error!(WasmErrorInner::Guest("Only game participants can record the score".into())));
-    // }
-
-
-    // Validate that the score points are within a reasonable range.
-    if input.player_points > MAX_POINTS { // MAX_POINTS is high, maybe check against game win condition?
-        warn!("Score points {} exceed MAX_POINTS {}", input.player_points, MAX_POINTS);
-        // Allow high scores for now, UI/game logic should enforce game rules like first to 10.
-        // return Err(wasm_error!(WasmErrorInner::Guest("Player points exceed the maximum allowed".into())));
-    }
-     if input.player_points > 100 { // Add a more reasonable sanity check
-         warn!("Recorded score {} seems high.", input.player_points);
-     }
-     // --- End Validation ---
+    };
+    // --- End Validation ---
 
+    // Link the game to this player's copy of the countersigned result.
+    create_link(
+        result.game_id.clone(),
+        match_result_hash.clone(),
+        LinkTypes::GameToMatchResults,
+        (),
+    )?;
 
     // Create the Score entry.
     let score_to_create = Score {
-        game_id: input.game_id.clone(),
-        player: input.player.clone(),
-        player_points: input.player_points,
+        game_id: result.game_id.clone(),
+        match_result: match_result_hash,
+        player: my_pub_key.clone(),
+        player_points,
         created_at: sys_time()?,
     };
     let score_action_hash = match create_entry(&EntryTypes::Score(score_to_create)) {
@@ -100,19 +69,18 @@ pub fn create_score(input: CreateScoreInput) -> ExternResult<Record> {
     };
 
     // Link the Score action hash from the Player's pubkey.
-    // Error handling for create_link can be added if necessary, for now assuming ? operator is sufficient
     create_link(
-        input.player.clone(),
+        my_pub_key,
         score_action_hash.clone(),
-        LinkTypes::PlayerToScores, // Changed from ScoreToPlayer based on convention BaseToTargets
+        LinkTypes::PlayerToScores,
         (),
     )?;
 
     // Link the Score action hash from the original game's action hash.
     create_link(
-        input.game_id.clone(), // Base is the original game action hash
+        result.game_id,
         score_action_hash.clone(),
-        LinkTypes::GameToScores, // Use a more descriptive name if possible, or reuse ScoreUpdates? Let's define GameToScores
+        LinkTypes::GameToScores,
         (),
     )?;
 
@@ -123,6 +91,29 @@ pub fn create_score(input: CreateScoreInput) -> ExternResult<Record> {
     Ok(record)
 }
 
+// The caller's Score for the given MatchResult, if already on their chain.
+fn find_my_score(match_result_hash: &ActionHash) -> ExternResult<Option<Record>> {
+    let filter = ChainQueryFilter::new()
+        .entry_type(UnitEntryTypes::Score.try_into()?)
+        .include_entries(true);
+    Ok(query(filter)?.into_iter().find(|record| {
+        record
+            .entry()
+            .to_app_option::<Score>()
+            .ok()
+            .flatten()
+            .is_some_and(|score| score.match_result == *match_result_hash)
+    }))
+}
+
+// Score hashes behind the links, each once even if linked twice.
+fn unique_targets(links: Vec<Link>) -> Vec<ActionHash> {
+    let mut hashes: Vec<ActionHash> = links.into_iter().filter_map(|link| link.target.into_action_hash()).collect();
+    hashes.sort();
+    hashes.dedup();
+    hashes
+}
+
 // --- Other Score CRUD functions ---
 // get_latest_score/get_original_score/update_score etc. rely on ScoreUpdates links.
 // The create_score now uses GameToScores. If updates to scores are needed,
@@ -142,9 +133,8 @@ pub fn get_scores_for_game(game_id: ActionHash) -> ExternResult<Vec<Record>> {
         GetStrategy::default(),
     )?;
 
-    let get_inputs: Vec<GetInput> = links
+    let get_inputs: Vec<GetInput> = unique_targets(links)
         .into_iter()
-        .filter_map(|link| link.target.into_action_hash())
         .map(|ah| GetInput::new(ah.into(), GetOptions::default()))
         .collect();
 
@@ -164,9 +154,8 @@ pub fn get_scores_for_player(player: AgentPubKey) -> ExternResult<Vec<Record>> {
         GetStrategy::default(),
     )?;
 
-     let get_inputs: Vec<GetInput> = links
+     let get_inputs: Vec<GetInput> = unique_targets(links)
         .into_iter()
-        .filter_map(|link| link.target.into_action_hash())
         .map(|ah| GetInput::new(ah.into(), GetOptions::default()))
         .collect();
 
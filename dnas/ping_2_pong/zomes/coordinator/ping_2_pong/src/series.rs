@@ -0,0 +1,321 @@
+// ping_2_pong/dnas/ping_2_pong/zomes/coordinator/ping_2_pong/src/series.rs
+//! Best-of-N series between two players.
+//!
+//! A `Series` entry fixes the players, length and rules; its games name it when
+//! they are created and are linked to it (and back). Game wins are never stored: like a
+//! tournament bracket they are derived from each game's countersigned result
+//! or forfeit. When a game is decided, its winner's cell starts the next game
+//! (scheduled `advance_my_series`, since the result is committed inside a
+//! countersigning session), or completes the series once someone has enough
+//! wins. `advance_series` does the same on demand for either player.
+use hdk::prelude::*;
+use ping_2_pong_integrity::*;
+use ping_2_pong_integrity::game::{GameStatus, NetcodeMode};
+use ping_2_pong_integrity::game_rules::GameRules;
+use ping_2_pong_integrity::series::SeriesStatus;
+use crate::game::{create_game, get_latest_game, CreateGameInput};
+use crate::invitations::{send_invitation, InvitationPayload};
+use crate::match_result::get_match_results_for_game;
+use crate::utils::player_exists;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CreateSeriesInput {
+    pub opponent: AgentPubKey,
+    pub best_of: u32,
+    #[serde(default)]
+    pub rules: GameRules,
+    #[serde(default)]
+    pub netcode: NetcodeMode,
+}
+
+/// One game of a series and how it ended.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SeriesGame {
+    pub game_id: ActionHash,
+    pub status: GameStatus,
+    pub created_at: Timestamp,
+    pub winner: Option<AgentPubKey>, // From the countersigned result or a forfeit
+    pub decided_by: Option<ActionHash>, // The MatchResult, or the Game revision with the forfeit
+    pub player_1_score: Option<u32>, // The series' player 1; None until a result is recorded
+    pub player_2_score: Option<u32>,
+}
+
+impl SeriesGame {
+    // Underway, or finished but still waiting for its result.
+    fn is_live(&self) -> bool {
+        matches!(self.status, GameStatus::Waiting | GameStatus::InProgress)
+            || (self.status == GameStatus::Finished && self.winner.is_none())
+    }
+}
+
+/// Current state of a series with its games (oldest first) and game wins.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SeriesView {
+    pub series_id: ActionHash,
+    pub series: Series,
+    pub games: Vec<SeriesGame>,
+    pub player_1_wins: u32,
+    pub player_2_wins: u32,
+    pub leader: Option<AgentPubKey>, // Whoever has reached the wins needed, if anyone
+}
+
+/// Starts a best-of-N series against `opponent` and creates its first game.
+#[hdk_extern]
+pub fn create_series(input: CreateSeriesInput) -> ExternResult<SeriesView> {
+    let me = agent_info()?.agent_initial_pubkey;
+    if !player_exists(&me)? || !player_exists(&input.opponent)? {
+        return Err(wasm_error!(WasmErrorInner::Guest("Both players of a series must be registered".into())));
+    }
+    let series = Series {
+        player_1: me.clone(),
+        player_2: input.opponent.clone(),
+        best_of: input.best_of,
+        rules: input.rules,
+        netcode: input.netcode,
+        status: SeriesStatus::InProgress,
+        winner: None,
+        created_at: sys_time()?,
+        won_games: Vec::new(),
+    };
+    let series_id = create_entry(&EntryTypes::Series(series))?;
+    create_link(me, series_id.clone(), LinkTypes::PlayerToSeries, ())?;
+    create_link(input.opponent, series_id.clone(), LinkTypes::PlayerToSeries, ())?;
+
+    advance_series(series_id.clone())?;
+    get_series(series_id)
+}
+
+/// The series with its games and results so far.
+#[hdk_extern]
+pub fn get_series(series_id: ActionHash) -> ExternResult<SeriesView> {
+    let (_, series) = get_latest_series_entry(&series_id)?;
+    let games = get_series_games(&series_id, &series)?;
+    let wins_of = |player: &AgentPubKey| games.iter().filter(|g| g.winner.as_ref() == Some(player)).count() as u32;
+    let player_1_wins = wins_of(&series.player_1);
+    let player_2_wins = wins_of(&series.player_2);
+    let leader = if player_1_wins >= series.wins_needed() {
+        Some(series.player_1.clone())
+    } else if player_2_wins >= series.wins_needed() {
+        Some(series.player_2.clone())
+    } else {
+        None
+    };
+    Ok(SeriesView { series_id, series, games, player_1_wins, player_2_wins, leader })
+}
+
+#[hdk_extern]
+pub fn get_latest_series(original_series_hash: ActionHash) -> ExternResult<Option<Record>> {
+    let links = get_links(
+        LinkQuery::try_new(original_series_hash.clone(), LinkTypes::SeriesUpdates)?,
+        GetStrategy::default(),
+    )?;
+    let latest_hash = match links.into_iter().max_by(|a, b| a.timestamp.cmp(&b.timestamp)) {
+        Some(link) => link.target.into_action_hash()
+            .ok_or(wasm_error!(WasmErrorInner::Guest("SeriesUpdates link target is not an ActionHash".into())))?,
+        None => original_series_hash,
+    };
+    get(latest_hash, GetOptions::default())
+}
+
+/// Latest revision of every series a player has taken part in.
+#[hdk_extern]
+pub fn get_series_for_player(player: AgentPubKey) -> ExternResult<Vec<Record>> {
+    let links = get_links(LinkQuery::try_new(player, LinkTypes::PlayerToSeries)?, GetStrategy::default())?;
+    let mut records = Vec::new();
+    for hash in links.into_iter().filter_map(|link| link.target.into_action_hash()) {
+        if let Some(record) = get_latest_series(hash)? {
+            records.push(record);
+        }
+    }
+    Ok(records)
+}
+
+/// The series a game belongs to, if any.
+#[hdk_extern]
+pub fn get_series_for_game(game_id: ActionHash) -> ExternResult<Option<ActionHash>> {
+    let links = get_links(LinkQuery::try_new(game_id, LinkTypes::GameToSeries)?, GetStrategy::default())?;
+    Ok(links.into_iter().find_map(|link| link.target.into_action_hash()))
+}
+
+/// Completes the series if someone has won enough games, otherwise creates
+/// the next game unless one is still being played. Either player may call it.
+/// Returns the new Game record, if one was created.
+#[hdk_extern]
+pub fn advance_series(series_id: ActionHash) -> ExternResult<Option<Record>> {
+    let view = get_series(series_id.clone())?;
+    if view.series.status != SeriesStatus::InProgress {
+        return Ok(None);
+    }
+    let me = agent_info()?.agent_initial_pubkey;
+    if !view.series.is_player(&me) {
+        return Err(wasm_error!(WasmErrorInner::Guest("Only a player of the series can advance it".into())));
+    }
+
+    if let Some(winner) = view.leader.clone() {
+        let (previous_hash, mut series) = get_latest_series_entry(&series_id)?;
+        // Validation recounts the wins from the cited results and forfeits.
+        series.won_games = view
+            .games
+            .iter()
+            .filter(|game| game.winner.as_ref() == Some(&winner))
+            .filter_map(|game| game.decided_by.clone())
+            .take(series.wins_needed() as usize)
+            .collect();
+        series.status = SeriesStatus::Completed;
+        series.winner = Some(winner);
+        update_series(&series_id, &previous_hash, &series)?;
+        debug!("[series.rs] advance_series: Series {:?} completed", series_id);
+        return Ok(None);
+    }
+    if view.games.iter().any(|game| game.is_live()) {
+        return Ok(None);
+    }
+
+    let series = view.series;
+    let record = create_game(CreateGameInput {
+        player_1: me.clone(),
+        player_2: Some(if me == series.player_1 { series.player_2.clone() } else { series.player_1.clone() }),
+        netcode: series.netcode,
+        rules: series.rules.clone(),
+        series: Some(series_id.clone()),
+    })?;
+    let game_id = record.action_hashed().hash.clone();
+    create_link(series_id.clone(), game_id.clone(), LinkTypes::SeriesToGames, ())?;
+    create_link(game_id.clone(), series_id, LinkTypes::GameToSeries, ())?;
+
+    let opponent = if me == series.player_1 { series.player_2 } else { series.player_1 };
+    if let Err(e) = send_invitation(InvitationPayload {
+        game_id,
+        invitee: opponent,
+        message: format!("Best of {}: game {}", series.best_of, view.games.len() + 1),
+    }) {
+        warn!("[series.rs] advance_series: Could not notify opponent: {:?}", e);
+    }
+    Ok(Some(record))
+}
+
+/// Ends a series early without a winner. Either player may cancel it.
+#[hdk_extern]
+pub fn cancel_series(series_id: ActionHash) -> ExternResult<Record> {
+    let (previous_hash, mut series) = get_latest_series_entry(&series_id)?;
+    if !series.is_player(&agent_info()?.agent_initial_pubkey) {
+        return Err(wasm_error!(WasmErrorInner::Guest("Only a player of the series can cancel it".into())));
+    }
+    if series.status != SeriesStatus::InProgress {
+        return Err(wasm_error!(WasmErrorInner::Guest("Series is already over".into())));
+    }
+    series.status = SeriesStatus::Cancelled;
+    update_series(&series_id, &previous_hash, &series)
+}
+
+/// Scheduled after one of our games was decided: for each of our running
+/// series whose latest decided game we won, moves the series on.
+#[hdk_extern(infallible)]
+pub fn advance_my_series(_: Option<Schedule>) -> Option<Schedule> {
+    if let Err(e) = advance_won_series() {
+        error!("[series.rs] advance_my_series: {:?}", e);
+    }
+    None
+}
+
+// --- Helpers ---
+
+fn advance_won_series() -> ExternResult<()> {
+    let me = agent_info()?.agent_initial_pubkey;
+    for record in get_series_for_player(me.clone())? {
+        let Ok(Some(series)) = record.entry().to_app_option::<Series>() else { continue };
+        if series.status != SeriesStatus::InProgress {
+            continue;
+        }
+        let series_id = match record.action() {
+            Action::Update(update) => original_series_hash(&update.original_action_address)?,
+            _ => record.action_address().clone(),
+        };
+        let games = get_series_games(&series_id, &series)?;
+        // Only the winner of the last game moves on, so the two players don't both start one.
+        let last_winner = games.last().and_then(|game| game.winner.clone());
+        if last_winner.as_ref() == Some(&me) {
+            advance_series(series_id)?;
+        }
+    }
+    Ok(())
+}
+
+// Series revisions update each other in a chain; walks back to the create action.
+fn original_series_hash(action_hash: &ActionHash) -> ExternResult<ActionHash> {
+    let mut hash = action_hash.clone();
+    while let Some(record) = get(hash.clone(), GetOptions::default())? {
+        match record.action() {
+            Action::Update(update) => hash = update.original_action_address.clone(),
+            _ => break,
+        }
+    }
+    Ok(hash)
+}
+
+// Returns the latest revision's hash and entry.
+fn get_latest_series_entry(series_id: &ActionHash) -> ExternResult<(ActionHash, Series)> {
+    let record = get_latest_series(series_id.clone())?
+        .ok_or(wasm_error!(WasmErrorInner::Guest(format!("Series does not exist: {}", series_id))))?;
+    let series = record
+        .entry()
+        .to_app_option::<Series>()
+        .map_err(|e| wasm_error!(WasmErrorInner::Serialize(e)))?
+        .ok_or(wasm_error!(WasmErrorInner::Guest("Invalid Series entry format".into())))?;
+    Ok((record.action_hashed().hash.clone(), series))
+}
+
+fn update_series(series_id: &ActionHash, previous_hash: &ActionHash, series: &Series) -> ExternResult<Record> {
+    let updated_hash = update_entry(previous_hash.clone(), series)?;
+    create_link(series_id.clone(), updated_hash.clone(), LinkTypes::SeriesUpdates, ())?;
+    get(updated_hash, GetOptions::default())?
+        .ok_or(wasm_error!(WasmErrorInner::Guest("Could not find the updated Series record".into())))
+}
+
+// Games of the series, oldest first, with their outcome.
+fn get_series_games(series_id: &ActionHash, series: &Series) -> ExternResult<Vec<SeriesGame>> {
+    let links = get_links(
+        LinkQuery::try_new(series_id.clone(), LinkTypes::SeriesToGames)?,
+        GetStrategy::default(),
+    )?;
+    let mut games: Vec<SeriesGame> = Vec::new();
+    for game_id in links.into_iter().filter_map(|link| link.target.into_action_hash()) {
+        // Both players may have linked the same game.
+        if games.iter().any(|game| game.game_id == game_id) {
+            continue;
+        }
+        let Some(record) = get_latest_game(game_id.clone())? else { continue };
+        let Ok(Some(game)) = record.entry().to_app_option::<Game>() else { continue };
+
+        // The countersigned result is authoritative; a forfeit has none.
+        let result_record = get_match_results_for_game(game_id.clone())?
+            .into_iter()
+            .find_map(|r| Some((r.action_address().clone(), r.entry().to_app_option::<MatchResult>().ok().flatten()?)));
+        let (result_hash, result) = result_record.unzip();
+        let forfeit_winner = game.forfeit.as_ref().map(|evidence| evidence.winner.clone());
+        let decided_by = match (&result, &forfeit_winner) {
+            (Some(result), _) if result.winner.is_some() => result_hash,
+            (_, Some(_)) => Some(record.action_address().clone()),
+            _ => None,
+        };
+        // Either player may have created the game, so its player 1 need not be the series' player 1.
+        let scores = result.as_ref().map(|result| {
+            if result.player_1 == series.player_1 {
+                (result.player_1_score, result.player_2_score)
+            } else {
+                (result.player_2_score, result.player_1_score)
+            }
+        });
+        games.push(SeriesGame {
+            game_id,
+            status: game.game_status,
+            created_at: game.created_at,
+            winner: result.as_ref().and_then(|result| result.winner.clone()).or(forfeit_winner),
+            decided_by,
+            player_1_score: scores.map(|(score, _)| score),
+            player_2_score: scores.map(|(_, score)| score),
+        });
+    }
+    games.sort_by_key(|game| game.created_at);
+    Ok(games)
+}
@@ -0,0 +1,235 @@
+// ping_2_pong/dnas/ping_2_pong/zomes/coordinator/ping_2_pong/src/session.rs
+//! Per-game capability sessions between the two players.
+//!
+//! When a game starts each player creates a transferable grant, tagged with the
+//! game and the opponent it was issued to, covering only the externs the
+//! opponent needs during the match. The secrets are swapped through
+//! `exchange_game_session` and stored as cap claims. Game traffic is then sent
+//! with the claim, and receivers check which grant authorised the call.
+use hdk::prelude::*;
+use ping_2_pong_integrity::*;
+use ping_2_pong_integrity::game::GameStatus;
+use std::cell::RefCell;
+use std::collections::{BTreeSet, HashMap};
+
+const SESSION_TAG_PREFIX: &str = "game-session";
+
+/// Externs the opponent may call during a game.
+const SESSION_FUNCTIONS: [&str; 2] = ["receive_remote_signal", "complete_match_result"];
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SessionHandshake {
+    pub game_id: ActionHash,
+    pub secret: CapSecret,
+    // The signed update that moved the game to InProgress. It is sent along
+    // because it is not yet published when the session is opened.
+    pub start_record: Record,
+}
+
+// Tag identifying the grant issued to `grantee` for `game_id`.
+fn session_tag(game_id: &ActionHash, grantee: &AgentPubKey) -> String {
+    format!("{}:{}:{}", SESSION_TAG_PREFIX, game_id, grantee)
+}
+
+fn create_session_grant(game_id: &ActionHash, grantee: &AgentPubKey) -> ExternResult<CapSecret> {
+    let zome_name = zome_info()?.name;
+    let functions: BTreeSet<(ZomeName, FunctionName)> = SESSION_FUNCTIONS
+        .iter()
+        .map(|f| (zome_name.clone(), FunctionName::from(*f)))
+        .collect();
+    let secret = generate_cap_secret()?;
+    create_cap_grant(CapGrantEntry {
+        tag: session_tag(game_id, grantee),
+        access: CapAccess::Transferable { secret },
+        functions: GrantedFunctions::Listed(functions),
+    })?;
+    Ok(secret)
+}
+
+// Checks that `start_record` is an authentic update by either player starting
+// `game_id` between `caller` and `me`. The caller may be re-opening a session
+// (resume_game) for a game the other player started.
+fn verify_start_record(game_id: &ActionHash, start_record: &Record, caller: &AgentPubKey, me: &AgentPubKey) -> ExternResult<()> {
+    let fail = |reason: &str| Err(wasm_error!(WasmErrorInner::Guest(format!("Invalid game session handshake: {}", reason))));
+    let signed_action = &start_record.signed_action;
+    let author = signed_action.action().author();
+    if author != caller && author != me {
+        return fail("start record was not authored by either player");
+    }
+    if !verify_signature(author.clone(), signed_action.signature().clone(), signed_action.action().clone())? {
+        return fail("bad signature on start record");
+    }
+    let Action::Update(update) = signed_action.action() else {
+        return fail("start record is not a game update");
+    };
+    if update.original_action_address != *game_id {
+        return fail("start record updates a different game");
+    }
+    let Some(entry) = start_record.entry().as_option() else {
+        return fail("start record has no entry");
+    };
+    if hash_entry(entry.clone())? != update.entry_hash {
+        return fail("start record entry does not match its action");
+    }
+    let game = Game::try_from(entry.clone())?;
+    let players_match = (game.player_1 == *caller && game.player_2.as_ref() == Some(me))
+        || (game.player_1 == *me && game.player_2.as_ref() == Some(caller));
+    if game.game_status != GameStatus::InProgress || !players_match {
+        return fail("start record is not an in-progress game between the two agents");
+    }
+    Ok(())
+}
+
+/// Called remotely by the opponent: stores their secret and returns ours.
+#[hdk_extern]
+pub fn exchange_game_session(handshake: SessionHandshake) -> ExternResult<CapSecret> {
+    let caller = call_info()?.provenance;
+    let me = agent_info()?.agent_initial_pubkey;
+    verify_start_record(&handshake.game_id, &handshake.start_record, &caller, &me)?;
+    store_session_claim(&handshake.game_id, caller.clone(), handshake.secret)?;
+    create_session_grant(&handshake.game_id, &caller)
+}
+
+/// Opens the session with the opponent. Called by the player who moves the game
+/// to InProgress (`start_record`), before any game signal is sent, and by either
+/// player when resuming.
+pub fn establish_game_session(game_id: &ActionHash, start_record: &Record) -> ExternResult<()> {
+    let me = agent_info()?.agent_initial_pubkey;
+    let game = crate::game::game_from_record(start_record)?;
+    let opponent = match game.player_2 {
+        Some(player_2) if game.player_1 == me => player_2,
+        Some(player_2) if player_2 == me => game.player_1,
+        _ => return Err(wasm_error!(WasmErrorInner::Guest("Not a participant of this game".into()))),
+    };
+
+    let secret = create_session_grant(game_id, &opponent)?;
+    let handshake = SessionHandshake { game_id: game_id.clone(), secret, start_record: start_record.clone() };
+    let handshake_io = ExternIO::encode(handshake).map_err(|e| wasm_error!(WasmErrorInner::Guest(e.to_string())))?;
+    match call_remote(opponent.clone(), zome_info()?.name, "exchange_game_session".into(), None, handshake_io)? {
+        ZomeCallResponse::Ok(io) => {
+            let their_secret: CapSecret = io.decode().map_err(|e| wasm_error!(WasmErrorInner::Guest(e.to_string())))?;
+            store_session_claim(game_id, opponent, their_secret)?;
+            Ok(())
+        }
+        other => Err(wasm_error!(WasmErrorInner::Guest(format!(
+            "Could not open game session with {:?}: {:?}", opponent, other
+        )))),
+    }
+}
+
+/// Re-opens the session for a game already in progress, e.g. if the opponent
+/// was unreachable when the game started.
+#[hdk_extern]
+pub fn open_game_session(game_id: ActionHash) -> ExternResult<()> {
+    let start_record = crate::game::get_latest_game(game_id.clone())?
+        .ok_or(wasm_error!(WasmErrorInner::Guest(format!("Game does not exist: {}", game_id))))?;
+    establish_game_session(&game_id, &start_record)
+}
+
+// Session secrets opponents issued to us, keyed by (game, opponent). Real-time
+// signals arrive at up to 50 Hz, so claims are cached for the life of the wasm
+// instance. A miss reloads them from the chain, at most once per interval, since
+// another instance may have stored a claim since.
+#[derive(Default)]
+struct SessionClaims {
+    secrets: HashMap<(ActionHash, AgentPubKey), CapSecret>,
+    loaded_at_ms: Option<i64>,
+}
+
+thread_local! {
+    static SESSION_CLAIMS: RefCell<SessionClaims> = RefCell::new(SessionClaims::default());
+}
+
+const SESSION_CLAIMS_RELOAD_MS: i64 = 1_000;
+
+// All session claims on our chain; the newest claim for a game and opponent wins.
+fn load_session_claims() -> ExternResult<HashMap<(ActionHash, AgentPubKey), CapSecret>> {
+    let prefix = format!("{}:", SESSION_TAG_PREFIX);
+    let suffix = format!(":{}", agent_info()?.agent_initial_pubkey);
+    let filter = ChainQueryFilter::new().entry_type(EntryType::CapClaim).include_entries(true);
+    let mut secrets = HashMap::new();
+    for record in query(filter)? {
+        let Some(Entry::CapClaim(claim)) = record.entry().as_option() else { continue };
+        let game = claim
+            .tag()
+            .strip_prefix(prefix.as_str())
+            .and_then(|rest| rest.strip_suffix(suffix.as_str()))
+            .and_then(|game| ActionHashB64::from_b64_str(game).ok())
+            .map(ActionHash::from);
+        if let Some(game_id) = game {
+            secrets.insert((game_id, claim.grantor().clone()), *claim.secret());
+        }
+    }
+    Ok(secrets)
+}
+
+// Looks up the cached claims, reloading them on a miss unless they were loaded recently.
+fn find_in_session_claims<T>(
+    find: impl Fn(&HashMap<(ActionHash, AgentPubKey), CapSecret>) -> Option<T>,
+) -> ExternResult<Option<T>> {
+    if let Some(found) = SESSION_CLAIMS.with(|claims| find(&claims.borrow().secrets)) {
+        return Ok(Some(found));
+    }
+    let now_ms = sys_time()?.as_millis();
+    let fresh = SESSION_CLAIMS.with(|claims| {
+        claims.borrow().loaded_at_ms.is_some_and(|loaded| now_ms - loaded < SESSION_CLAIMS_RELOAD_MS)
+    });
+    if fresh {
+        return Ok(None);
+    }
+    let secrets = load_session_claims()?;
+    let found = find(&secrets);
+    SESSION_CLAIMS.with(|claims| *claims.borrow_mut() = SessionClaims { secrets, loaded_at_ms: Some(now_ms) });
+    Ok(found)
+}
+
+// Stores a claim on our chain and in the cache.
+fn store_session_claim(game_id: &ActionHash, opponent: AgentPubKey, secret: CapSecret) -> ExternResult<()> {
+    let tag = session_tag(game_id, &agent_info()?.agent_initial_pubkey);
+    create_cap_claim(CapClaimEntry::new(tag, opponent.clone(), secret))?;
+    SESSION_CLAIMS.with(|claims| claims.borrow_mut().secrets.insert((game_id.clone(), opponent), secret));
+    Ok(())
+}
+
+/// Secret the opponent issued to us for this game, if a session was opened.
+pub fn get_session_secret(game_id: &ActionHash, opponent: &AgentPubKey) -> ExternResult<Option<CapSecret>> {
+    let key = (game_id.clone(), opponent.clone());
+    find_in_session_claims(|secrets| secrets.get(&key).copied())
+}
+
+/// True if `opponent` opened a game session with us for this game.
+pub fn has_session_with(game_id: &ActionHash, opponent: &AgentPubKey) -> ExternResult<bool> {
+    Ok(get_session_secret(game_id, opponent)?.is_some())
+}
+
+/// A game we hold a session claim from `opponent` for that matches `predicate`.
+pub fn find_session_game_with(opponent: &AgentPubKey, predicate: impl Fn(&ActionHash) -> bool) -> ExternResult<Option<ActionHash>> {
+    find_in_session_claims(|secrets| {
+        secrets
+            .keys()
+            .find(|(game_id, grantor)| grantor == opponent && predicate(game_id))
+            .map(|(game_id, _)| game_id.clone())
+    })
+}
+
+/// Calls an opponent's extern under the game session grant.
+pub fn call_opponent(
+    game_id: &ActionHash,
+    opponent: &AgentPubKey,
+    fn_name: &str,
+    payload: ExternIO,
+) -> ExternResult<ZomeCallResponse> {
+    let secret = get_session_secret(game_id, opponent)?
+        .ok_or(wasm_error!(WasmErrorInner::Guest(format!("No game session with {:?}", opponent))))?;
+    call_remote(opponent.clone(), zome_info()?.name, fn_name.into(), Some(secret), payload)
+}
+
+/// True if the current call was authorised by the session grant we issued to
+/// the caller for this game.
+pub fn is_session_call(game_id: &ActionHash) -> ExternResult<bool> {
+    let info = call_info()?;
+    Ok(match &info.cap_grant {
+        CapGrant::RemoteAgent(grant) => grant.tag == session_tag(game_id, &info.provenance),
+        _ => false,
+    })
+}
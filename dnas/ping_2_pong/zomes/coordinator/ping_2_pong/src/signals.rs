@@ -1,23 +1,296 @@
 // ping_2_pong/dnas/ping_2_pong/zomes/coordinator/ping_2_pong/src/signals.rs
 use hdk::prelude::*;
 use crate::Signal;
+use ping_2_pong_integrity::{ChatChannel, EntryTypes, SeenSignal, UnitEntryTypes};
+use ping_2_pong_integrity::game::NetcodeMode;
+use std::cell::RefCell;
+use std::collections::{BTreeSet, HashMap};
 
 /// ───────────────────────── init helper ─────────────────────────
+/// Unrestricted grant limited to the entry points other agents need before a
+/// game session exists; everything else requires a session or authorship.
 pub fn grant_remote_signal_cap() -> ExternResult<()> {
+    let zome_name = zome_info()?.name;
+    let functions: BTreeSet<(ZomeName, FunctionName)> = ["receive_remote_signal", "recv_remote_signal", "exchange_game_session"]
+        .into_iter()
+        .map(|f| (zome_name.clone(), FunctionName::from(f)))
+        .collect();
     let grant = CapGrantEntry {
         tag: "remote-signal".into(),
         access: CapAccess::Unrestricted,
-        functions: GrantedFunctions::All,
+        functions: GrantedFunctions::Listed(functions),
     };
-    let _ = create_cap_grant(grant);
+    create_cap_grant(grant)?;
     Ok(())
 }
 
+/// Wire format for signals sent to other agents. `seq` is the sender's clock in
+/// microseconds when the signal was first sent, which only moves forward for a
+/// given sender; `sent_at` is the time of this attempt (later for outbox retries).
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RemoteSignal {
+    pub seq: i64,
+    pub sent_at: i64,
+    pub signal: Signal,
+}
+
+// Signals older than this (by the sender's clock) are dropped as stale.
+const MAX_SIGNAL_AGE_MICROS: i64 = 15_000_000;
+// Tolerated clock drift for signals that appear to come from the future.
+const MAX_CLOCK_SKEW_MICROS: i64 = 15_000_000;
+
+/// Encodes a signal for `call_remote`, stamping it with the next sequence number.
+pub fn encode_remote_signal(signal: &Signal) -> ExternResult<ExternIO> {
+    let now = sys_time()?.as_micros();
+    let envelope = RemoteSignal { seq: now, sent_at: now, signal: signal.clone() };
+    ExternIO::encode(envelope).map_err(|e| wasm_error!(WasmErrorInner::Guest(e.to_string())))
+}
+
+/// True if a sender timestamp (microseconds) is within the accepted freshness window.
+pub fn is_fresh(seq: i64) -> ExternResult<bool> {
+    let now = sys_time()?.as_micros();
+    Ok(seq >= now - MAX_SIGNAL_AGE_MICROS && seq <= now + MAX_CLOCK_SKEW_MICROS)
+}
+
+// Game the signal belongs to, for signals that may only come from the opponent.
+fn session_game_id(signal: &Signal) -> Option<&ActionHash> {
+    match signal {
+        Signal::GameStarted { game_id, .. }
+        | Signal::PaddleUpdate { game_id, .. }
+        | Signal::PaddleHit { game_id, .. }
+        | Signal::BallUpdate { game_id, .. }
+        | Signal::InputFrame { game_id, .. }
+        | Signal::ScoreUpdate { game_id, .. }
+        | Signal::GameOver { game_id, .. }
+        | Signal::GameAbandoned { game_id, .. }
+        | Signal::GameResumed { game_id, .. }
+        | Signal::MatchResultProposal { game_id, .. } => Some(game_id),
+        _ => None,
+    }
+}
+
+// Checks the identity fields a signal claims against the calling agent.
+fn check_claimed_sender(signal: &Signal, sender: &AgentPubKey, me: &AgentPubKey) -> Result<(), String> {
+    let claimed_ok = match signal {
+        Signal::ChatMessage { sender: claimed, channel, .. } | Signal::ChatMessageChanged { changed_by: claimed, channel, .. } => {
+            // A direct message must be addressed to us.
+            claimed == sender && match channel {
+                ChatChannel::Direct(a, b) => a == me || b == me,
+                _ => true,
+            }
+        }
+        Signal::GameInvitation { inviter, .. } => inviter == sender,
+        Signal::GameStarted { player_1, player_2, .. } => {
+            (player_1 == sender && player_2 == me) || (player_2 == sender && player_1 == me)
+        }
+        Signal::PaddleUpdate { player, .. } | Signal::PaddleHit { player, .. } | Signal::InputFrame { player, .. } => player == sender,
+        Signal::GameAbandoned { abandoned_by_player, .. } => abandoned_by_player == sender,
+        Signal::GameResumed { player, .. } => player == sender,
+        Signal::MatchResultProposal { proposer, .. } => proposer == sender,
+        // Sent by the opponent (session) or host (spectators); checked by the caller.
+        Signal::BallUpdate { .. } | Signal::ScoreUpdate { .. } | Signal::GameOver { .. } => true,
+        // Only ever emitted locally (post_commit / local bookkeeping).
+        Signal::LinkCreated { .. }
+        | Signal::LinkDeleted { .. }
+        | Signal::EntryCreated { .. }
+        | Signal::EntryUpdated { .. }
+        | Signal::EntryDeleted { .. }
+        | Signal::FrameBatch { .. }
+        | Signal::MatchResultCommitted { .. } => return Err("local-only signal cannot be received remotely".into()),
+    };
+    if claimed_ok {
+        Ok(())
+    } else {
+        Err("claimed sender does not match the calling agent".into())
+    }
+}
+
+// True if the caller's block / mute lists say the signal should not be shown.
+fn is_filtered(signal: &Signal, sender: &AgentPubKey) -> ExternResult<bool> {
+    match signal {
+        Signal::ChatMessage { .. } | Signal::ChatMessageChanged { .. } => {
+            Ok(crate::moderation::get_chat_filters(())?.hides_chat_from(sender))
+        }
+        Signal::GameInvitation { .. } => Ok(crate::moderation::get_chat_filters(())?.blocked.contains(sender)),
+        _ => Ok(false),
+    }
+}
+
+// Low-rate signals whose replay would have an effect; these are deduplicated
+// by sender and seq. Frame signals are superseded within
+// milliseconds, so the freshness window is enough for them. Chat signals carry
+// the message hash the UI already deduplicates by, so they skip the seq record.
+fn is_control_signal(signal: &Signal) -> bool {
+    matches!(
+        signal,
+        Signal::GameInvitation { .. }
+            | Signal::GameStarted { .. }
+            | Signal::GameOver { .. }
+            | Signal::GameAbandoned { .. }
+            | Signal::GameResumed { .. }
+            | Signal::MatchResultProposal { .. }
+    )
+}
+
+thread_local! {
+    // Control signals accepted in this wasm instance, per sender. Critical ones
+    // are also persisted as SeenSignal entries, for after the instance is gone.
+    static SEEN_SIGNALS: RefCell<HashMap<AgentPubKey, BTreeSet<i64>>> = RefCell::new(HashMap::new());
+}
+
+// Records the control signal (`sender`, `seq`) as accepted; false if it already was.
+// Signals are deduplicated individually rather than against the newest seq, since
+// an outbox retry keeps its original seq and may arrive after newer signals.
+// Only critical signals, whose replay would restart or end a game, are persisted
+// on the source chain.
+fn mark_signal_seen(sender: &AgentPubKey, seq: i64, persist: bool) -> ExternResult<bool> {
+    // Retries stop after the outbox TTL, so older seqs are refused and forgotten.
+    let oldest = sys_time()?.as_micros() - crate::outbox::MESSAGE_TTL_MS * 1_000;
+    if seq < oldest {
+        return Ok(false);
+    }
+    let seen_here = SEEN_SIGNALS.with(|seen| {
+        let mut seen = seen.borrow_mut();
+        let seqs = seen.entry(sender.clone()).or_default();
+        seqs.retain(|s| *s >= oldest);
+        seqs.contains(&seq)
+    });
+    if seen_here {
+        return Ok(false);
+    }
+
+    if persist {
+        if is_seen_signal_persisted(sender, seq)? {
+            return Ok(false);
+        }
+        let seen = SeenSignal { sender: sender.clone(), seq };
+        let entry = Entry::try_from(seen.clone())?;
+        create_private_relaxed(&EntryTypes::SeenSignal(seen), entry)?;
+    }
+    SEEN_SIGNALS.with(|seen| seen.borrow_mut().entry(sender.clone()).or_default().insert(seq));
+    Ok(true)
+}
+
+// True if a previous instance accepted this critical signal.
+fn is_seen_signal_persisted(sender: &AgentPubKey, seq: i64) -> ExternResult<bool> {
+    let filter = ChainQueryFilter::new()
+        .entry_type(UnitEntryTypes::SeenSignal.try_into()?)
+        .include_entries(true);
+    Ok(query(filter)?
+        .into_iter()
+        .filter_map(|record| record.entry().to_app_option::<SeenSignal>().ok().flatten())
+        .any(|seen| seen.sender == *sender && seen.seq == seq))
+}
+
+/// Creates a private entry with relaxed chain ordering, so concurrent
+/// deliveries don't fail on a moved chain head.
+pub fn create_private_relaxed(app_entry: &EntryTypes, entry: Entry) -> ExternResult<ActionHash> {
+    let ScopedEntryDefIndex { zome_index, zome_type } = ScopedEntryDefIndex::try_from(app_entry)?;
+    HDK.with(|hdk| {
+        hdk.borrow().create(CreateInput::new(
+            EntryDefLocation::app(zome_index, zome_type),
+            EntryVisibility::Private,
+            entry,
+            ChainTopOrdering::Relaxed,
+        ))
+    })
+}
+
+/// Wire format for fire-and-forget real-time traffic sent with `send_remote_signal`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum RealtimeMessage {
+    Signal(RemoteSignal),
+    FrameBatch(#[serde(with = "serde_bytes")] Vec<u8>), // Encoded by frame_batch.rs
+}
+
+/// Sends real-time traffic without waiting for the recipients; delivery is best effort.
+pub fn send_realtime(recipients: Vec<AgentPubKey>, message: RealtimeMessage) -> ExternResult<()> {
+    if recipients.is_empty() {
+        return Ok(());
+    }
+    send_remote_signal(message, recipients)
+}
+
+/// Encodes and sends a signal as real-time traffic.
+pub fn send_realtime_signal(recipients: Vec<AgentPubKey>, signal: &Signal) -> ExternResult<()> {
+    let now = sys_time()?.as_micros();
+    let envelope = RemoteSignal { seq: now, sent_at: now, signal: signal.clone() };
+    send_realtime(recipients, RealtimeMessage::Signal(envelope))
+}
+
 /// ──────────────────────── local re-emit ───────────────────────
+/// Acknowledged delivery (`call_remote`), used for invitations, game start/over etc.
 #[hdk_extern]
 pub fn receive_remote_signal(input: ExternIO) -> ExternResult<()> {
-    let _ = grant_remote_signal_cap();
-    let signal: Signal = input.decode().map_err(|e| wasm_error!(WasmErrorInner::Guest(e.to_string())))?;
+    let envelope: RemoteSignal = input.decode().map_err(|e| wasm_error!(WasmErrorInner::Guest(e.to_string())))?;
+    let sender = call_info()?.provenance;
+    // Game traffic must arrive under the session grant issued to the opponent
+    // for that game (only granted to the other participant).
+    accept_remote_signal(sender, envelope, crate::session::is_session_call)
+}
+
+/// Fire-and-forget delivery (`send_remote_signal`), used for paddle, ball and input frames.
+#[hdk_extern]
+pub fn recv_remote_signal(input: ExternIO) -> ExternResult<()> {
+    let message: RealtimeMessage = input.decode().map_err(|e| wasm_error!(WasmErrorInner::Guest(e.to_string())))?;
+    let sender = call_info()?.provenance;
+    match message {
+        // No cap secret travels with a remote signal, so game traffic is checked
+        // against the session claim the sender gave us for that game instead.
+        RealtimeMessage::Signal(envelope) => {
+            let from = sender.clone();
+            accept_remote_signal(sender, envelope, move |game_id| crate::session::has_session_with(game_id, &from))
+        }
+        RealtimeMessage::FrameBatch(data) => crate::frame_batch::accept_frame_batch(sender, &data),
+    }
+}
+
+// Checks a received signal and re-emits it to the UI. `in_session` tells whether
+// the sender is our opponent in the given game.
+fn accept_remote_signal(
+    sender: AgentPubKey,
+    envelope: RemoteSignal,
+    in_session: impl Fn(&ActionHash) -> ExternResult<bool>,
+) -> ExternResult<()> {
+    let RemoteSignal { seq, sent_at, signal } = envelope;
+    let me = agent_info()?.agent_initial_pubkey;
+    let reject = |reason: String| Err(wasm_error!(WasmErrorInner::Guest(format!("Rejected remote signal from {:?}: {}", sender, reason))));
+
+    // 1. Freshness: drop stale frames and anything stamped too far ahead.
+    if !is_fresh(sent_at)? {
+        return reject(format!("stale or future timestamp {}", sent_at));
+    }
+
+    // 2. Identity: the claimed player / inviter / sender must be the caller.
+    if let Err(reason) = check_claimed_sender(&signal, &sender, &me) {
+        return reject(reason);
+    }
+
+    // 3. Filters: drop chat from blocked or muted agents and invitations from blocked ones.
+    if is_filtered(&signal, &sender)? {
+        debug!("[signals.rs] receive_remote_signal: Dropped filtered signal from {:?}", sender);
+        return Ok(());
+    }
+
+    // 4. Participation: game traffic must come from our opponent in that game,
+    //    or from the host of a game we are watching.
+    if let Some(game_id) = session_game_id(&signal) {
+        if !in_session(game_id)? && !crate::spectators::is_watched_host(game_id, &sender, &signal)? {
+            return reject(format!("not a participant of game {:?}", game_id));
+        }
+    }
+
+    // 5. Replay: each control signal is accepted once.
+    let critical = crate::outbox::is_critical_signal(&signal);
+    if is_control_signal(&signal) && !mark_signal_seen(&sender, seq, critical)? {
+        debug!("[signals.rs] receive_remote_signal: Dropped duplicate signal {} from {:?}", seq, sender);
+        return Ok(());
+    }
+
+    // 6. Keep critical signals until the UI drains them, in case it is not listening.
+    if critical {
+        crate::outbox::store_in_inbox(&sender, seq, &signal)?;
+    }
     emit_signal(&signal)
 }
 
@@ -64,24 +337,51 @@ pub struct GameAbandonedPayload {
 }
 
 /// ───────────────────── broadcast helper ──────────────────────
-fn broadcast_signal(recipient: Option<AgentPubKey>, _game_id: &ActionHash, signal: &Signal) -> ExternResult<()> {
-    let signal_io = ExternIO::encode(signal).map_err(|e| wasm_error!(WasmErrorInner::Guest(e.to_string())))?;
+// Real-time frames: fire-and-forget to the opponent.
+fn stream_signal(recipient: Option<AgentPubKey>, signal: &Signal) -> ExternResult<()> {
+    let me = agent_info()?.agent_initial_pubkey;
+    let recipients: Vec<AgentPubKey> = recipient.into_iter().filter(|target| *target != me).collect();
+    send_realtime_signal(recipients, signal)
+}
+
+// Messages that must arrive: acknowledged call to the opponent.
+fn broadcast_signal(recipient: Option<AgentPubKey>, game_id: &ActionHash, signal: &Signal) -> ExternResult<()> {
+    let signal_io = encode_remote_signal(signal)?;
 
     if let Some(target) = recipient {
         let me = agent_info()?.agent_initial_pubkey;
         if target != me {
-            let _ = call_remote(
-                target,
-                "ping_2_pong",
-                "receive_remote_signal".into(),
-                None,
-                signal_io,
-            );
+            if let Err(e) = crate::session::call_opponent(game_id, &target, "receive_remote_signal", signal_io) {
+                debug!("[signals.rs] broadcast_signal: Could not reach {:?}: {:?}", target, e);
+            }
         }
     }
     Ok(())
 }
 
+// Critical signals: queued in the outbox until the opponent acknowledges.
+fn send_reliable_to(recipient: Option<AgentPubKey>, game_id: &ActionHash, signal: &Signal) -> ExternResult<()> {
+    let me = agent_info()?.agent_initial_pubkey;
+    if let Some(target) = recipient.filter(|target| *target != me) {
+        crate::outbox::send_reliable(&target, Some(game_id), signal)?;
+    }
+    Ok(())
+}
+
+/// Sends to the opponent first, then (host only) to any spectators.
+/// Ball updates are streamed; score and game-over are acknowledged.
+fn broadcast_with_spectators(recipient: Option<AgentPubKey>, game_id: &ActionHash, signal: &Signal) -> ExternResult<()> {
+    match signal {
+        Signal::BallUpdate { .. } => stream_signal(recipient, signal)?,
+        Signal::GameOver { .. } => send_reliable_to(recipient, game_id, signal)?,
+        _ => broadcast_signal(recipient, game_id, signal)?,
+    }
+    if let Err(e) = crate::spectators::forward_to_spectators(game_id, signal) {
+        warn!("[signals.rs] broadcast_with_spectators: Spectator fan-out failed: {:?}", e);
+    }
+    Ok(())
+}
+
 /// ───────────────────── externs used by UI ────────────────────
 #[hdk_extern]
 pub fn send_paddle_update(payload: PaddleUpdatePayload) -> ExternResult<()> {
@@ -91,7 +391,7 @@ pub fn send_paddle_update(payload: PaddleUpdatePayload) -> ExternResult<()> {
         paddle_y: payload.paddle_y,
     };
     emit_signal(&signal)?;
-    broadcast_signal(payload.recipient, &payload.game_id, &signal)
+    stream_signal(payload.recipient, &signal)
 }
 
 #[hdk_extern]
@@ -102,22 +402,31 @@ pub fn send_paddle_hit(payload: PaddleHitPayload) -> ExternResult<()> {
         ball_y:   payload.ball_y,
     };
     emit_signal(&signal)?;
-    broadcast_signal(payload.recipient, &payload.game_id, &signal)
+    stream_signal(payload.recipient, &signal)
 }
 
+/// Tells the opponent the caller abandoned the game. Without a recipient the
+/// opponent is looked up from the game.
 #[hdk_extern]
 pub fn send_game_abandoned_signal(payload: GameAbandonedPayload) -> ExternResult<()> {
     let abandoned_by_player = agent_info()?.agent_initial_pubkey;
+    let recipient = match payload.recipient {
+        Some(recipient) => Some(recipient),
+        None => crate::game::get_opponent(&payload.game_id, &abandoned_by_player)?,
+    };
     let signal = Signal::GameAbandoned {
         game_id: payload.game_id.clone(),
         abandoned_by_player,
     };
-    
-    broadcast_signal(payload.recipient, &payload.game_id, &signal)
+
+    send_reliable_to(recipient, &payload.game_id, &signal)
 }
 
 #[hdk_extern]
 pub fn send_ball_update(payload: BallUpdatePayload) -> ExternResult<()> {
+    if crate::lockstep::get_netcode_mode(&payload.game_id)? == NetcodeMode::Lockstep {
+        return Err(wasm_error!(WasmErrorInner::Guest("Lockstep games exchange inputs, not ball updates".into())));
+    }
     let signal = Signal::BallUpdate {
         game_id: payload.game_id.clone(),
         ball_x:  payload.ball_x,
@@ -128,7 +437,7 @@ pub fn send_ball_update(payload: BallUpdatePayload) -> ExternResult<()> {
         score2:  payload.score2,
     };
     emit_signal(&signal)?;
-    broadcast_signal(payload.recipient, &payload.game_id, &signal)
+    broadcast_with_spectators(payload.recipient, &payload.game_id, &signal)
 }
 
 #[hdk_extern]
@@ -139,7 +448,7 @@ pub fn send_score_update(payload: GameOverPayload) -> ExternResult<()> {
         score2:  payload.score2,
     };
     emit_signal(&signal)?;
-    broadcast_signal(payload.recipient, &payload.game_id, &signal)
+    broadcast_with_spectators(payload.recipient, &payload.game_id, &signal)
 }
 
 #[hdk_extern]
@@ -151,5 +460,5 @@ pub fn send_game_over(payload: GameOverPayload) -> ExternResult<()> {
         score2:  payload.score2,
     };
     emit_signal(&signal)?;
-    broadcast_signal(payload.recipient, &payload.game_id, &signal)
+    broadcast_with_spectators(payload.recipient, &payload.game_id, &signal)
 }
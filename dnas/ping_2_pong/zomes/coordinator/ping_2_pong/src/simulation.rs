@@ -0,0 +1,133 @@
+// ping_2_pong/dnas/ping_2_pong/zomes/coordinator/ping_2_pong/src/simulation.rs
+//! Host-authoritative physics on top of the `pong_sim` crate.
+//!
+//! The zome keeps no state between calls, so the caller passes the last
+//! simulation state back in. The host steps it with both players' inputs and
+//! broadcasts the resulting ball; the opponent steps the same inputs from the
+//! same state and checks the host's `BallUpdate` against its own result.
+//! Both step under the game's own rules, looked up from `game_id`.
+use hdk::prelude::*;
+use ping_2_pong_integrity::*;
+use ping_2_pong_integrity::game_rules::GameRules;
+use pong_sim::{to_fixed, Fixed, FrameInput, GameState, SimConfig, StepEvent, ONE};
+use crate::game::get_latest_game_entry;
+use crate::Signal;
+
+// Allowed difference, in pixels, between a received ball position and the
+// locally simulated one (rounding of the broadcast integers).
+const BALL_POSITION_TOLERANCE_PX: i32 = 1;
+// Upper bound on frames stepped in one call, keeps the call cheap.
+const MAX_FRAMES_PER_CALL: usize = 600;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SimulateFramesInput {
+    pub game_id: ActionHash,
+    pub state: Option<GameState>, // None starts from kick-off
+    pub inputs: Vec<FrameInput>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SimulateFramesOutput {
+    pub state: GameState,
+    pub events: Vec<StepEvent>,
+    pub checksum: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct VerifyBallUpdateInput {
+    pub game_id: ActionHash,
+    pub state: GameState, // State before `inputs` were applied
+    pub inputs: Vec<FrameInput>,
+    pub update: Signal,   // The received Signal::BallUpdate
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BallUpdateCheck {
+    pub consistent: bool,
+    pub drift_px: i32,      // Largest position difference in pixels
+    pub expected: [i32; 4], // ball_x, ball_y, ball_dx, ball_dy from the local simulation
+    pub state: GameState,   // Local state after `inputs`
+}
+
+/// Steps the simulation through `inputs` and returns the new state.
+#[hdk_extern]
+pub fn simulate_frames(input: SimulateFramesInput) -> ExternResult<SimulateFramesOutput> {
+    check_frame_count(input.inputs.len())?;
+    let config = game_sim_config(&input.game_id)?;
+    let mut state = match input.state {
+        Some(state) => GameState { config, ..state },
+        None => GameState::with_config(config),
+    };
+    let events = state.run(&input.inputs);
+    let checksum = state.checksum();
+    Ok(SimulateFramesOutput { state, events, checksum })
+}
+
+/// Replays `inputs` locally and compares the result with a host's `BallUpdate`.
+#[hdk_extern]
+pub fn verify_ball_update(input: VerifyBallUpdateInput) -> ExternResult<BallUpdateCheck> {
+    check_frame_count(input.inputs.len())?;
+    let Signal::BallUpdate { ball_x, ball_y, ball_dx, ball_dy, score1, score2, .. } = input.update else {
+        return Err(wasm_error!(WasmErrorInner::Guest("Expected a BallUpdate signal".into())));
+    };
+    let mut state = GameState { config: game_sim_config(&input.game_id)?, ..input.state };
+    state.run(&input.inputs);
+    let expected = state.ball_pixels();
+
+    let drift_px = (ball_x - expected[0]).abs().max((ball_y - expected[1]).abs());
+    let velocity_matches = (ball_dx - expected[2]).abs() <= BALL_POSITION_TOLERANCE_PX
+        && (ball_dy - expected[3]).abs() <= BALL_POSITION_TOLERANCE_PX;
+    let scores_match = score1.is_none_or(|s| s == state.score_1) && score2.is_none_or(|s| s == state.score_2);
+    let consistent = drift_px <= BALL_POSITION_TOLERANCE_PX && velocity_matches && scores_match;
+    if !consistent {
+        debug!(
+            "[simulation.rs] verify_ball_update: Mismatch at frame {}: got ({}, {}, {}, {}), expected {:?}",
+            state.frame, ball_x, ball_y, ball_dx, ball_dy, expected
+        );
+    }
+    Ok(BallUpdateCheck { consistent, drift_px, expected, state })
+}
+
+/// Builds the `BallUpdate` the host broadcasts for a simulated state.
+pub fn ball_update_for(game_id: ActionHash, state: &GameState) -> Signal {
+    let [ball_x, ball_y, ball_dx, ball_dy] = state.ball_pixels();
+    Signal::BallUpdate {
+        game_id,
+        ball_x,
+        ball_y,
+        ball_dx,
+        ball_dy,
+        score1: Some(state.score_1),
+        score2: Some(state.score_2),
+    }
+}
+
+/// The `pong_sim` config for a game's rules.
+pub fn sim_config(rules: &GameRules) -> SimConfig {
+    SimConfig {
+        field_width: rules.field_width as i32,
+        field_height: rules.field_height as i32,
+        paddle_height: rules.paddle_height as i32,
+        points_to_win: rules.points_to_win,
+        win_by_two: rules.win_by_two,
+        serve_dx: (rules.serve_speed_tenths as i32 * ONE) / 10,
+        max_ball_speed: to_fixed(rules.max_ball_speed as i32),
+        // Rounded, so the standard 8% gives pong_sim's standard factor exactly.
+        speed_up: (((100 + rules.acceleration_pct as i64) * ONE as i64 + 50) / 100) as Fixed,
+    }
+}
+
+/// The `pong_sim` config of an existing game. Rules never change, so any revision will do.
+pub fn game_sim_config(game_id: &ActionHash) -> ExternResult<SimConfig> {
+    let (_, game) = get_latest_game_entry(game_id)?;
+    Ok(sim_config(&game.rules))
+}
+
+fn check_frame_count(count: usize) -> ExternResult<()> {
+    if count > MAX_FRAMES_PER_CALL {
+        return Err(wasm_error!(WasmErrorInner::Guest(format!(
+            "Too many frames in one call ({} max)", MAX_FRAMES_PER_CALL
+        ))));
+    }
+    Ok(())
+}
@@ -0,0 +1,157 @@
+// ping_2_pong/dnas/ping_2_pong/zomes/coordinator/ping_2_pong/src/spectators.rs
+//! Spectator subscriptions for live games.
+//!
+//! Spectators link themselves to the game; the host (player 1) streams
+//! ball, score and game-over signals to them after the opponent has been served.
+use hdk::prelude::*;
+use ping_2_pong_integrity::*;
+use ping_2_pong_integrity::game::GameStatus;
+use crate::game::get_latest_game_entry;
+use crate::utils::get_game_hash_by_id;
+use crate::Signal;
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+// Hard cap on spectators served per game, keeps the host's fan-out bounded.
+pub const MAX_SPECTATORS: usize = 32;
+// Ball updates are forwarded at most once per interval (~10 Hz).
+const SPECTATOR_BALL_INTERVAL_MS: i64 = 100;
+
+thread_local! {
+    // When a ball update was last forwarded, per game. Lives as long as the wasm
+    // instance; a fresh instance forwards its first update straight away.
+    static LAST_BALL_FORWARD_MS: RefCell<HashMap<ActionHash, i64>> = RefCell::new(HashMap::new());
+}
+
+/// Registers the caller as a spectator of an in-progress or waiting game.
+#[hdk_extern]
+pub fn subscribe_as_spectator(game_id: ActionHash) -> ExternResult<ActionHash> {
+    debug!("[spectators.rs] subscribe_as_spectator: game {:?}", game_id);
+    let me = agent_info()?.agent_initial_pubkey;
+    let game = get_current_game(&game_id)?;
+    if game.player_1 == me || game.player_2.as_ref() == Some(&me) {
+        return Err(wasm_error!(WasmErrorInner::Guest("Players cannot spectate their own game".into())));
+    }
+    if matches!(game.game_status, GameStatus::Finished | GameStatus::Abandoned) {
+        return Err(wasm_error!(WasmErrorInner::Guest("Game is no longer live".into())));
+    }
+
+    let links = get_spectator_links(&game_id)?;
+    if let Some(existing) = links.iter().find(|link| link.author == me) {
+        return Ok(existing.create_link_hash.clone());
+    }
+    if links.len() >= MAX_SPECTATORS {
+        return Err(wasm_error!(WasmErrorInner::Guest(format!(
+            "Spectator limit reached ({} max)", MAX_SPECTATORS
+        ))));
+    }
+
+    create_link(game_id, me, LinkTypes::GameToSpectators, ())
+}
+
+/// Removes the caller's spectator subscription(s) for the game.
+#[hdk_extern]
+pub fn unsubscribe_spectator(game_id: ActionHash) -> ExternResult<()> {
+    let me = agent_info()?.agent_initial_pubkey;
+    for link in get_spectator_links(&game_id)? {
+        if link.author == me {
+            delete_link(link.create_link_hash, GetOptions::default())?;
+        }
+    }
+    Ok(())
+}
+
+/// Lists the agents watching a game, earliest subscribers first, capped at MAX_SPECTATORS.
+#[hdk_extern]
+pub fn get_spectators(game_id: ActionHash) -> ExternResult<Vec<AgentPubKey>> {
+    let mut links = get_spectator_links(&game_id)?;
+    links.sort_by_key(|link| link.timestamp);
+    let mut spectators: Vec<AgentPubKey> = Vec::new();
+    for link in links {
+        if let Some(agent) = link.target.into_agent_pub_key() {
+            if !spectators.contains(&agent) {
+                spectators.push(agent);
+            }
+        }
+        if spectators.len() >= MAX_SPECTATORS {
+            break;
+        }
+    }
+    Ok(spectators)
+}
+
+/// Forwards a live signal to the game's spectators. Only the host (player 1)
+/// forwards, so spectators receive each update once; ball updates are throttled.
+pub fn forward_to_spectators(game_id: &ActionHash, signal: &Signal) -> ExternResult<()> {
+    match signal {
+        Signal::BallUpdate { .. } => {
+            if !ball_forward_due(game_id, sys_time()?.as_millis()) {
+                return Ok(());
+            }
+        }
+        Signal::ScoreUpdate { .. } | Signal::GameOver { .. } => {}
+        _ => return Ok(()),
+    }
+
+    // player_1 never changes, so the (locally cached) original entry is enough.
+    let Some(record) = get(game_id.clone(), GetOptions::default())? else { return Ok(()) };
+    let Ok(Some(game)) = record.entry().to_app_option::<Game>() else { return Ok(()) };
+    if game.player_1 != agent_info()?.agent_initial_pubkey {
+        return Ok(());
+    }
+
+    let spectators = get_spectators(game_id.clone())?;
+    if spectators.is_empty() {
+        return Ok(());
+    }
+    // One fire-and-forget signal to all spectators; a slow or offline
+    // spectator must not hold up the player's call.
+    crate::signals::send_realtime_signal(spectators, signal)
+}
+
+/// True if `sender` is the host of a game the caller is watching and the
+/// signal is one the host forwards to spectators.
+pub fn is_watched_host(game_id: &ActionHash, sender: &AgentPubKey, signal: &Signal) -> ExternResult<bool> {
+    if !matches!(signal, Signal::BallUpdate { .. } | Signal::ScoreUpdate { .. } | Signal::GameOver { .. }) {
+        return Ok(false);
+    }
+    let Some(record) = get(game_id.clone(), GetOptions::default())? else { return Ok(false) };
+    let Ok(Some(game)) = record.entry().to_app_option::<Game>() else { return Ok(false) };
+    if game.player_1 != *sender {
+        return Ok(false);
+    }
+    let me = agent_info()?.agent_initial_pubkey;
+    let subscribed = get_links(
+        LinkQuery::try_new(game_id.clone(), LinkTypes::GameToSpectators)?,
+        GetStrategy::Local,
+    )?
+    .into_iter()
+    .any(|link| link.author == me);
+    Ok(subscribed)
+}
+
+// Records the forward and returns true if the game's last ball update went out at
+// least an interval ago.
+fn ball_forward_due(game_id: &ActionHash, now_ms: i64) -> bool {
+    LAST_BALL_FORWARD_MS.with(|last| {
+        let mut last = last.borrow_mut();
+        if last.get(game_id).is_some_and(|sent_ms| now_ms - sent_ms < SPECTATOR_BALL_INTERVAL_MS) {
+            return false;
+        }
+        last.insert(game_id.clone(), now_ms);
+        true
+    })
+}
+
+fn get_spectator_links(game_id: &ActionHash) -> ExternResult<Vec<Link>> {
+    get_links(
+        LinkQuery::try_new(game_id.clone(), LinkTypes::GameToSpectators)?,
+        GetStrategy::default(),
+    )
+}
+
+fn get_current_game(game_id: &ActionHash) -> ExternResult<Game> {
+    let original_hash = get_game_hash_by_id(game_id)?
+        .ok_or(wasm_error!(WasmErrorInner::Guest(format!("Game ID does not exist: {}", game_id))))?;
+    Ok(get_latest_game_entry(&original_hash)?.1)
+}
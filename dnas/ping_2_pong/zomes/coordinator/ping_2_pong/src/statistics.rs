@@ -28,13 +28,7 @@ pub fn create_statistics(mut statistics: Statistics) -> ExternResult<Record> { /
         .ok_or(wasm_error!(WasmErrorInner::Guest(format!("Game ID does not exist: {}", statistics.game_id))))?;
 
     // Fetch the *latest* Game record to check status
-    let game_record = crate::game::get_latest_game(game_action_hash)?
-        .ok_or(wasm_error!(WasmErrorInner::Guest("Game record not found for statistics check".into())))?;
-    let game = game_record
-        .entry()
-        .to_app_option::<Game>()
-        .map_err(|e| wasm_error!(WasmErrorInner::Serialize(e)))?
-        .ok_or(wasm_error!(WasmErrorInner::Guest("Invalid Game entry format for statistics check".into())))?;
+    let (_, game) = crate::game::get_latest_game_entry(&game_action_hash)?;
 
     // Ensure the game is Finished.
     if game.game_status != GameStatus::Finished {
@@ -0,0 +1,364 @@
+// ping_2_pong/dnas/ping_2_pong/zomes/coordinator/ping_2_pong/src/tournament.rs
+use hdk::prelude::*;
+use ping_2_pong_integrity::*;
+use ping_2_pong_integrity::game::NetcodeMode;
+use ping_2_pong_integrity::game_rules::GameRules;
+use ping_2_pong_integrity::tournament::{TournamentFormat, TournamentGameTag, TournamentResult, TournamentStatus};
+use std::collections::{BTreeMap, BTreeSet};
+use crate::game::{create_game, get_latest_game, CreateGameInput};
+use crate::invitations::{send_invitation, InvitationPayload};
+use crate::match_result::get_match_results_for_game;
+use crate::rating::current_rating;
+use crate::tournament_bracket::{build_bracket, Bracket, BracketSide, LinkedGame};
+use crate::utils::{anchor_for, player_exists};
+
+const TOURNAMENTS_ANCHOR: &str = "all_tournaments";
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CreateTournamentInput {
+    pub name: String,
+    pub format: TournamentFormat,
+    pub max_players: u32,
+}
+
+/// Current tournament state together with its derived bracket.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TournamentBracket {
+    pub tournament_id: ActionHash,
+    pub tournament: Tournament,
+    pub participants: Vec<AgentPubKey>,
+    pub bracket: Bracket,
+}
+
+#[hdk_extern]
+pub fn create_tournament(input: CreateTournamentInput) -> ExternResult<Record> {
+    let my_pub_key = agent_info()?.agent_initial_pubkey;
+    if !player_exists(&my_pub_key)? {
+        return Err(wasm_error!(WasmErrorInner::Guest("Only registered players can organise tournaments".into())));
+    }
+    let tournament = Tournament {
+        name: input.name.trim().to_string(),
+        organiser: my_pub_key,
+        format: input.format,
+        max_players: input.max_players,
+        status: TournamentStatus::Registration,
+        seeds: vec![],
+        winner: None,
+        registrations: vec![],
+        deciding_results: vec![],
+        created_at: sys_time()?,
+    };
+    let tournament_hash = create_entry(&EntryTypes::Tournament(tournament))?;
+    create_link(anchor_for(TOURNAMENTS_ANCHOR)?, tournament_hash.clone(), LinkTypes::AllTournaments, ())?;
+
+    get(tournament_hash, GetOptions::default())?
+        .ok_or(wasm_error!(WasmErrorInner::Guest("Could not find the newly created Tournament".into())))
+}
+
+/// Latest revision of every tournament.
+#[hdk_extern]
+pub fn get_all_tournaments(_: ()) -> ExternResult<Vec<Record>> {
+    let links = get_links(
+        LinkQuery::try_new(anchor_for(TOURNAMENTS_ANCHOR)?, LinkTypes::AllTournaments)?,
+        GetStrategy::default(),
+    )?;
+    let mut records = Vec::new();
+    for hash in links.into_iter().filter_map(|link| link.target.into_action_hash()) {
+        if let Some(record) = get_latest_tournament(hash)? {
+            records.push(record);
+        }
+    }
+    Ok(records)
+}
+
+#[hdk_extern]
+pub fn get_latest_tournament(original_tournament_hash: ActionHash) -> ExternResult<Option<Record>> {
+    let links = get_links(
+        LinkQuery::try_new(original_tournament_hash.clone(), LinkTypes::TournamentUpdates)?,
+        GetStrategy::default(),
+    )?;
+    let latest_hash = match links.into_iter().max_by(|a, b| a.timestamp.cmp(&b.timestamp)) {
+        Some(link) => link.target.into_action_hash()
+            .ok_or(wasm_error!(WasmErrorInner::Guest("TournamentUpdates link target is not an ActionHash".into())))?,
+        None => original_tournament_hash,
+    };
+    get(latest_hash, GetOptions::default())
+}
+
+#[hdk_extern]
+pub fn register_for_tournament(tournament_id: ActionHash) -> ExternResult<ActionHash> {
+    let my_pub_key = agent_info()?.agent_initial_pubkey;
+    if !player_exists(&my_pub_key)? {
+        return Err(wasm_error!(WasmErrorInner::Guest("Only registered players can join tournaments".into())));
+    }
+    let (_, tournament) = get_tournament(&tournament_id)?;
+    if tournament.status != TournamentStatus::Registration {
+        return Err(wasm_error!(WasmErrorInner::Guest("Tournament registration is closed".into())));
+    }
+    let links = get_participant_links(&tournament_id)?;
+    if let Some(existing) = links.iter().find(|link| link.author == my_pub_key) {
+        return Ok(existing.create_link_hash.clone());
+    }
+    if links.len() as u32 >= tournament.max_players {
+        return Err(wasm_error!(WasmErrorInner::Guest("Tournament is full".into())));
+    }
+    create_link(tournament_id, my_pub_key, LinkTypes::TournamentToParticipants, ())
+}
+
+#[hdk_extern]
+pub fn unregister_from_tournament(tournament_id: ActionHash) -> ExternResult<()> {
+    let my_pub_key = agent_info()?.agent_initial_pubkey;
+    let (_, tournament) = get_tournament(&tournament_id)?;
+    if tournament.status != TournamentStatus::Registration {
+        return Err(wasm_error!(WasmErrorInner::Guest("Cannot leave a tournament after it has started".into())));
+    }
+    for link in get_participant_links(&tournament_id)? {
+        if link.author == my_pub_key {
+            delete_link(link.create_link_hash, GetOptions::default())?;
+        }
+    }
+    Ok(())
+}
+
+/// Registered players in registration order (capped at the tournament size).
+#[hdk_extern]
+pub fn get_tournament_participants(tournament_id: ActionHash) -> ExternResult<Vec<AgentPubKey>> {
+    let (_, tournament) = get_tournament(&tournament_id)?;
+    Ok(get_registrations(&tournament_id, &tournament)?.into_iter().map(|(agent, _)| agent).collect())
+}
+
+/// Closes registration and seeds participants by rating (highest first). Organiser only.
+#[hdk_extern]
+pub fn start_tournament(tournament_id: ActionHash) -> ExternResult<Record> {
+    let (previous_hash, mut tournament) = get_tournament(&tournament_id)?;
+    ensure_organiser(&tournament)?;
+    if tournament.status != TournamentStatus::Registration {
+        return Err(wasm_error!(WasmErrorInner::Guest("Tournament has already started".into())));
+    }
+
+    let registrations = get_registrations(&tournament_id, &tournament)?;
+    if registrations.len() < 2 {
+        return Err(wasm_error!(WasmErrorInner::Guest("A tournament needs at least 2 players".into())));
+    }
+    let mut rated: Vec<(f64, AgentPubKey, ActionHash)> = Vec::new();
+    for (player, registration) in registrations {
+        rated.push((current_rating(&player)?, player, registration));
+    }
+    rated.sort_by(|(rating_a, a, _), (rating_b, b, _)| rating_b.total_cmp(rating_a).then_with(|| a.cmp(b)));
+
+    // Validators check every seed against the registration it cites.
+    tournament.status = TournamentStatus::InProgress;
+    (tournament.seeds, tournament.registrations) = rated.into_iter().map(|(_, player, registration)| (player, registration)).unzip();
+    let record = update_tournament(&tournament_id, &previous_hash, &tournament)?;
+
+    // Create the first round straight away for the organiser's own matches. The start
+    // itself stands either way; players can advance the bracket later.
+    if let Err(e) = advance_tournament(tournament_id) {
+        warn!("[tournament.rs] start_tournament: Could not create the first games: {:?}", e);
+    }
+    Ok(record)
+}
+
+/// Cancels a tournament that has not completed. Organiser only.
+#[hdk_extern]
+pub fn cancel_tournament(tournament_id: ActionHash) -> ExternResult<Record> {
+    let (previous_hash, mut tournament) = get_tournament(&tournament_id)?;
+    ensure_organiser(&tournament)?;
+    if matches!(tournament.status, TournamentStatus::Completed | TournamentStatus::Cancelled) {
+        return Err(wasm_error!(WasmErrorInner::Guest("Tournament is already over".into())));
+    }
+    tournament.status = TournamentStatus::Cancelled;
+    update_tournament(&tournament_id, &previous_hash, &tournament)
+}
+
+/// Derives the bracket from seeds and finished games.
+#[hdk_extern]
+pub fn get_tournament_bracket(tournament_id: ActionHash) -> ExternResult<TournamentBracket> {
+    let (_, tournament) = get_tournament(&tournament_id)?;
+    let participants = match tournament.status {
+        TournamentStatus::Registration => get_tournament_participants(tournament_id.clone())?,
+        _ => tournament.seeds.clone(),
+    };
+    let bracket = if tournament.seeds.is_empty() {
+        Bracket::default()
+    } else {
+        build_bracket(tournament.format, &tournament.seeds, &get_linked_games(&tournament_id)?)
+    };
+    Ok(TournamentBracket { tournament_id, tournament, participants, bracket })
+}
+
+/// Creates games for every ready match the caller plays in, and lets the
+/// organiser record the champion once the bracket is decided.
+/// Returns the newly created Game records.
+#[hdk_extern]
+pub fn advance_tournament(tournament_id: ActionHash) -> ExternResult<Vec<Record>> {
+    let my_pub_key = agent_info()?.agent_initial_pubkey;
+    let view = get_tournament_bracket(tournament_id.clone())?;
+    if view.tournament.status != TournamentStatus::InProgress {
+        return Ok(vec![]);
+    }
+    // Links name the revision carrying the seeds so validators can check the players.
+    let (tournament_revision, _) = get_tournament(&tournament_id)?;
+
+    if let Some(champion) = view.bracket.champion.clone() {
+        if view.tournament.organiser == my_pub_key {
+            let (previous_hash, mut tournament) = get_tournament(&tournament_id)?;
+            tournament.status = TournamentStatus::Completed;
+            tournament.winner = Some(champion);
+            tournament.deciding_results = deciding_results(&view.bracket);
+            update_tournament(&tournament_id, &previous_hash, &tournament)?;
+        }
+        return Ok(vec![]);
+    }
+
+    // A player only has one tournament game open at a time.
+    let mut busy: BTreeSet<AgentPubKey> = BTreeSet::new();
+    for m in view.bracket.matches.iter().filter(|m| m.game_live) {
+        busy.extend(m.player_1.iter().cloned());
+        busy.extend(m.player_2.iter().cloned());
+    }
+
+    let mut created = Vec::new();
+    for m in view.bracket.matches.iter().filter(|m| m.needs_game()) {
+        let (Some(player_1), Some(player_2)) = (m.player_1.clone(), m.player_2.clone()) else { continue };
+        if my_pub_key != player_1 && my_pub_key != player_2 {
+            continue;
+        }
+        if busy.contains(&player_1) || busy.contains(&player_2) {
+            continue;
+        }
+        let record = create_game(CreateGameInput {
+            player_1: player_1.clone(),
+            player_2: Some(player_2.clone()),
+            netcode: NetcodeMode::default(),
+            rules: GameRules::default(),
+            series: None,
+        })?;
+        let game_id = record.action_hashed().hash.clone();
+        let tag = TournamentGameTag { match_id: m.match_id.clone(), tournament_revision: tournament_revision.clone() };
+        let tag_bytes = SerializedBytes::try_from(tag).map_err(|e| wasm_error!(WasmErrorInner::Serialize(e)))?;
+        create_link(tournament_id.clone(), game_id.clone(), LinkTypes::TournamentToGames, LinkTag::new(tag_bytes.bytes().clone()))?;
+        busy.insert(player_1.clone());
+        busy.insert(player_2.clone());
+
+        let opponent = if my_pub_key == player_1 { player_2 } else { player_1 };
+        if let Err(e) = send_invitation(InvitationPayload {
+            game_id,
+            invitee: opponent,
+            message: format!("{}: match {}", view.tournament.name, m.match_id),
+        }) {
+            warn!("[tournament.rs] advance_tournament: Could not notify opponent: {:?}", e);
+        }
+        created.push(record);
+    }
+    Ok(created)
+}
+
+// --- Helpers ---
+
+// Returns the latest revision's hash and entry.
+fn get_tournament(tournament_id: &ActionHash) -> ExternResult<(ActionHash, Tournament)> {
+    let record = get_latest_tournament(tournament_id.clone())?
+        .ok_or(wasm_error!(WasmErrorInner::Guest(format!("Tournament does not exist: {}", tournament_id))))?;
+    let tournament = record
+        .entry()
+        .to_app_option::<Tournament>()
+        .map_err(|e| wasm_error!(WasmErrorInner::Serialize(e)))?
+        .ok_or(wasm_error!(WasmErrorInner::Guest("Invalid Tournament entry format".into())))?;
+    Ok((record.action_hashed().hash.clone(), tournament))
+}
+
+fn ensure_organiser(tournament: &Tournament) -> ExternResult<()> {
+    if tournament.organiser != agent_info()?.agent_initial_pubkey {
+        return Err(wasm_error!(WasmErrorInner::Guest("Only the organiser can manage this tournament".into())));
+    }
+    Ok(())
+}
+
+fn update_tournament(tournament_id: &ActionHash, previous_hash: &ActionHash, tournament: &Tournament) -> ExternResult<Record> {
+    let updated_hash = update_entry(previous_hash.clone(), tournament)?;
+    create_link(tournament_id.clone(), updated_hash.clone(), LinkTypes::TournamentUpdates, ())?;
+    get(updated_hash, GetOptions::default())?
+        .ok_or(wasm_error!(WasmErrorInner::Guest("Could not find the updated Tournament record".into())))
+}
+
+fn get_participant_links(tournament_id: &ActionHash) -> ExternResult<Vec<Link>> {
+    get_links(
+        LinkQuery::try_new(tournament_id.clone(), LinkTypes::TournamentToParticipants)?,
+        GetStrategy::default(),
+    )
+}
+
+// Registered players in registration order (capped at the tournament size), each
+// with the CreateLink that registered them.
+fn get_registrations(tournament_id: &ActionHash, tournament: &Tournament) -> ExternResult<Vec<(AgentPubKey, ActionHash)>> {
+    let mut links = get_participant_links(tournament_id)?;
+    links.sort_by_key(|link| link.timestamp);
+    let mut registrations: Vec<(AgentPubKey, ActionHash)> = Vec::new();
+    for link in links {
+        let Some(agent) = link.target.into_agent_pub_key() else { continue };
+        if registrations.len() as u32 >= tournament.max_players {
+            break;
+        }
+        if !registrations.iter().any(|(registered, _)| *registered == agent) {
+            registrations.push((agent, link.create_link_hash));
+        }
+    }
+    Ok(registrations)
+}
+
+// Results validators need to confirm the champion: the winners-bracket final and the
+// grand finals of an elimination bracket, every match of a round robin.
+fn deciding_results(bracket: &Bracket) -> Vec<TournamentResult> {
+    let final_round = bracket.matches.iter().filter(|m| m.side == BracketSide::Winners).map(|m| m.round).max();
+    bracket
+        .matches
+        .iter()
+        .filter(|m| match m.side {
+            BracketSide::Winners => Some(m.round) == final_round,
+            BracketSide::Losers => false,
+            BracketSide::GrandFinal | BracketSide::RoundRobin => true,
+        })
+        .filter_map(|m| m.result.clone())
+        .collect()
+}
+
+// Games linked to the tournament, grouped by match id, with their current status and winner.
+fn get_linked_games(tournament_id: &ActionHash) -> ExternResult<BTreeMap<String, Vec<LinkedGame>>> {
+    let links = get_links(
+        LinkQuery::try_new(tournament_id.clone(), LinkTypes::TournamentToGames)?,
+        GetStrategy::default(),
+    )?;
+    let mut games: BTreeMap<String, Vec<LinkedGame>> = BTreeMap::new();
+    for link in links {
+        let game_link = link.create_link_hash.clone();
+        let Some(game_id) = link.target.into_action_hash() else { continue };
+        let Ok(tag) = TournamentGameTag::try_from(SerializedBytes::from(UnsafeBytes::from(link.tag.into_inner()))) else { continue };
+        let Some(record) = get_latest_game(game_id.clone())? else { continue };
+        let Ok(Some(game)) = record.entry().to_app_option::<Game>() else { continue };
+
+        // The countersigned result is authoritative for the winner.
+        let result_record = get_match_results_for_game(game_id.clone())?
+            .into_iter()
+            .find_map(|r| Some((r.action_address().clone(), r.entry().to_app_option::<MatchResult>().ok().flatten()?)));
+        let (result_hash, result) = result_record.unzip();
+        // A forfeit cannot be countersigned (the opponent is gone); its evidence settles the game.
+        let forfeit_winner = game.forfeit.as_ref().map(|evidence| evidence.winner.clone());
+        let decided_by = match forfeit_winner {
+            Some(_) if result.is_none() => Some(record.action_address().clone()),
+            _ => result_hash,
+        };
+        games.entry(tag.match_id).or_default().push(LinkedGame {
+            game_id,
+            game_link,
+            player_1: game.player_1,
+            player_2: game.player_2,
+            status: game.game_status,
+            created_at: game.created_at,
+            result_recorded: result.is_some() || forfeit_winner.is_some(),
+            winner: result.and_then(|result| result.winner).or(forfeit_winner),
+            decided_by,
+        });
+    }
+    Ok(games)
+}
@@ -0,0 +1,333 @@
+// ping_2_pong/dnas/ping_2_pong/zomes/coordinator/ping_2_pong/src/tournament_bracket.rs
+//! Deterministic bracket derivation.
+//!
+//! A bracket is never stored: it is recomputed from the tournament's seeds and
+//! the outcomes of the games linked to each match id. Match ids are
+//! `W<round>-<index>` (winners bracket), `L<round>-<index>` (losers bracket),
+//! `GF` (grand final), `GF2` (grand-final reset, played when the losers-bracket
+//! champion wins `GF`) and `R<round>-<index>` (round robin), all 1-based rounds.
+use hdk::prelude::*;
+use ping_2_pong_integrity::game::GameStatus;
+use ping_2_pong_integrity::tournament::{round_robin_rounds, seed_order, TournamentFormat, TournamentResult};
+use std::collections::BTreeMap;
+
+/// A game played for a match, with its countersigned winner if any.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LinkedGame {
+    pub game_id: ActionHash,
+    pub game_link: ActionHash, // The TournamentToGames CreateLink
+    pub player_1: AgentPubKey,
+    pub player_2: Option<AgentPubKey>,
+    pub status: GameStatus,
+    pub created_at: Timestamp,
+    pub winner: Option<AgentPubKey>,
+    pub result_recorded: bool, // A countersigned MatchResult (or forfeit) exists
+    pub decided_by: Option<ActionHash>, // The MatchResult, or the Game revision with the forfeit
+}
+
+impl LinkedGame {
+    // Finished and countersigned; only these can decide a match.
+    fn is_settled(&self) -> bool {
+        self.status == GameStatus::Finished && self.result_recorded
+    }
+
+    // Still being played, or finished but awaiting the countersigned result.
+    fn is_live(&self) -> bool {
+        matches!(self.status, GameStatus::Waiting | GameStatus::InProgress)
+            || (self.status == GameStatus::Finished && !self.result_recorded)
+    }
+
+    // Played by exactly these two players, in either order.
+    fn is_between(&self, a: &AgentPubKey, b: &AgentPubKey) -> bool {
+        let p2 = self.player_2.as_ref();
+        (self.player_1 == *a && p2 == Some(b)) || (self.player_1 == *b && p2 == Some(a))
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BracketSide {
+    Winners,
+    Losers,
+    GrandFinal,
+    RoundRobin,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BracketMatch {
+    pub match_id: String,
+    pub side: BracketSide,
+    pub round: u32,
+    pub player_1: Option<AgentPubKey>,
+    pub player_2: Option<AgentPubKey>,
+    pub is_bye: bool,                // One side is a bye; the other advances without playing
+    pub decided: bool,
+    pub winner: Option<AgentPubKey>, // None on a decided round-robin draw
+    pub game_id: Option<ActionHash>, // Most recent game played for this match
+    pub game_live: bool,             // A game for this match is underway or awaiting its result
+    pub result: Option<TournamentResult>, // Link and result of the deciding game; None for byes
+}
+
+impl BracketMatch {
+    /// Both players known, no result yet and no game underway.
+    pub fn needs_game(&self) -> bool {
+        self.player_1.is_some() && self.player_2.is_some() && !self.decided && !self.game_live
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Standing {
+    pub player: AgentPubKey,
+    pub played: u32,
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+    pub points: u32, // 3 per win, 1 per draw
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Bracket {
+    pub matches: Vec<BracketMatch>,
+    pub standings: Vec<Standing>,
+    pub champion: Option<AgentPubKey>,
+}
+
+// Occupant of a bracket slot while resolving.
+#[derive(Debug, Clone, PartialEq)]
+enum Slot {
+    Pending,
+    Bye,
+    Player(AgentPubKey),
+}
+
+impl Slot {
+    fn player(&self) -> Option<AgentPubKey> {
+        match self {
+            Slot::Player(p) => Some(p.clone()),
+            _ => None,
+        }
+    }
+}
+
+pub fn build_bracket(
+    format: TournamentFormat,
+    seeds: &[AgentPubKey],
+    games: &BTreeMap<String, Vec<LinkedGame>>,
+) -> Bracket {
+    if seeds.len() < 2 {
+        return Bracket::default();
+    }
+    match format {
+        TournamentFormat::SingleElimination => build_elimination(seeds, games, false),
+        TournamentFormat::DoubleElimination => build_elimination(seeds, games, true),
+        TournamentFormat::RoundRobin => build_round_robin(seeds, games),
+    }
+}
+
+struct Resolver<'a> {
+    games: &'a BTreeMap<String, Vec<LinkedGame>>,
+    matches: Vec<BracketMatch>,
+}
+
+impl Resolver<'_> {
+    // Resolves one match and returns (winner, loser) slots for the next rounds.
+    fn resolve(&mut self, match_id: String, side: BracketSide, round: u32, a: Slot, b: Slot, allow_draw: bool) -> (Slot, Slot) {
+        // Only games between the match's two players count; a game linked under the
+        // wrong match id (or before its players are known) is ignored.
+        let games: Vec<&LinkedGame> = match (&a, &b) {
+            (Slot::Player(p1), Slot::Player(p2)) => self
+                .games
+                .get(&match_id)
+                .map(|games| games.iter().filter(|g| g.is_between(p1, p2)).collect())
+                .unwrap_or_default(),
+            _ => Vec::new(),
+        };
+        let latest = games.iter().max_by_key(|g| g.created_at);
+        let mut bracket_match = BracketMatch {
+            match_id,
+            side,
+            round,
+            player_1: a.player(),
+            player_2: b.player(),
+            is_bye: false,
+            decided: false,
+            winner: None,
+            game_id: latest.map(|g| g.game_id.clone()),
+            game_live: games.iter().any(|g| g.is_live()),
+            result: None,
+        };
+
+        let outcome = match (&a, &b) {
+            (Slot::Pending, _) | (_, Slot::Pending) => (Slot::Pending, Slot::Pending),
+            (Slot::Bye, Slot::Bye) => {
+                bracket_match.is_bye = true;
+                bracket_match.decided = true;
+                (Slot::Bye, Slot::Bye)
+            }
+            (Slot::Player(p), Slot::Bye) | (Slot::Bye, Slot::Player(p)) => {
+                bracket_match.is_bye = true;
+                bracket_match.decided = true;
+                bracket_match.winner = Some(p.clone());
+                (Slot::Player(p.clone()), Slot::Bye)
+            }
+            (Slot::Player(p1), Slot::Player(p2)) => {
+                // The first finished game with a valid winner decides the match;
+                // elimination draws are replayed, round-robin draws stand.
+                let mut finished: Vec<&LinkedGame> = games.iter().copied().filter(|g| g.is_settled()).collect();
+                finished.sort_by_key(|g| g.created_at);
+                let deciding = finished.into_iter().find(|g| match &g.winner {
+                    Some(w) => w == p1 || w == p2,
+                    None => allow_draw,
+                });
+                match deciding {
+                    Some(game) => {
+                        bracket_match.decided = true;
+                        bracket_match.winner = game.winner.clone();
+                        bracket_match.game_id = Some(game.game_id.clone());
+                        bracket_match.result = game.decided_by.clone().map(|result| TournamentResult {
+                            game_link: game.game_link.clone(),
+                            result,
+                        });
+                        match &game.winner {
+                            Some(w) if w == p1 => (a.clone(), b.clone()),
+                            Some(_) => (b.clone(), a.clone()),
+                            None => (Slot::Pending, Slot::Pending),
+                        }
+                    }
+                    None => (Slot::Pending, Slot::Pending),
+                }
+            }
+        };
+        self.matches.push(bracket_match);
+        outcome
+    }
+}
+
+fn build_elimination(seeds: &[AgentPubKey], games: &BTreeMap<String, Vec<LinkedGame>>, double: bool) -> Bracket {
+    let size = seeds.len().next_power_of_two();
+    let rounds = size.trailing_zeros();
+    let mut resolver = Resolver { games, matches: Vec::new() };
+
+    // Winners bracket: outcomes[r][i] = (winner, loser) of W(r+1)-(i+1)
+    let order = seed_order(size);
+    let mut slots: Vec<Slot> = order
+        .iter()
+        .map(|&s| seeds.get(s).cloned().map(Slot::Player).unwrap_or(Slot::Bye))
+        .collect();
+    let mut winners_outcomes: Vec<Vec<(Slot, Slot)>> = Vec::new();
+    for round in 1..=rounds {
+        let outcomes: Vec<(Slot, Slot)> = slots
+            .chunks(2)
+            .enumerate()
+            .map(|(i, pair)| {
+                let id = format!("W{}-{}", round, i + 1);
+                resolver.resolve(id, BracketSide::Winners, round, pair[0].clone(), pair[1].clone(), false)
+            })
+            .collect();
+        slots = outcomes.iter().map(|(w, _)| w.clone()).collect();
+        winners_outcomes.push(outcomes);
+    }
+    let winners_champion = slots.into_iter().next().unwrap_or(Slot::Pending);
+
+    if !double {
+        return Bracket {
+            champion: winners_champion.player(),
+            matches: resolver.matches,
+            standings: vec![],
+        };
+    }
+
+    // Losers bracket: odd rounds pair up survivors, even rounds bring in
+    // the losers of the next winners round (in reverse order to avoid rematches).
+    let losers_of = |round: usize| -> Vec<Slot> { winners_outcomes[round].iter().map(|(_, l)| l.clone()).collect() };
+    let mut survivors: Vec<Slot> = Vec::new();
+    let losers_rounds = 2 * (rounds - 1);
+    for round in 1..=losers_rounds {
+        let pairs: Vec<(Slot, Slot)> = if round == 1 {
+            losers_of(0).chunks(2).map(|p| (p[0].clone(), p[1].clone())).collect()
+        } else if round % 2 == 0 {
+            let dropping = losers_of((round / 2) as usize);
+            survivors.iter().cloned().zip(dropping.into_iter().rev()).collect()
+        } else {
+            survivors.chunks(2).map(|p| (p[0].clone(), p[1].clone())).collect()
+        };
+        survivors = pairs
+            .into_iter()
+            .enumerate()
+            .map(|(i, (a, b))| {
+                let id = format!("L{}-{}", round, i + 1);
+                resolver.resolve(id, BracketSide::Losers, round, a, b, false).0
+            })
+            .collect();
+    }
+    let losers_champion = if rounds == 1 {
+        winners_outcomes[0][0].1.clone()
+    } else {
+        survivors.into_iter().next().unwrap_or(Slot::Pending)
+    };
+
+    // The winners-bracket champion has not lost yet: if they lose the grand final,
+    // the same two players meet again in the reset match.
+    let (gf_winner, gf_loser) = resolver.resolve(
+        "GF".into(),
+        BracketSide::GrandFinal,
+        rounds + 1,
+        winners_champion.clone(),
+        losers_champion,
+        false,
+    );
+    let champion = if gf_winner == Slot::Pending || gf_winner == winners_champion {
+        gf_winner
+    } else {
+        resolver.resolve("GF2".into(), BracketSide::GrandFinal, rounds + 2, gf_winner, gf_loser, false).0
+    };
+    Bracket {
+        champion: champion.player(),
+        matches: resolver.matches,
+        standings: vec![],
+    }
+}
+
+fn build_round_robin(seeds: &[AgentPubKey], games: &BTreeMap<String, Vec<LinkedGame>>) -> Bracket {
+    let mut resolver = Resolver { games, matches: Vec::new() };
+    for (round, pairs) in round_robin_rounds(seeds).into_iter().enumerate() {
+        let round = round as u32 + 1;
+        for (index, (a, b)) in pairs.into_iter().enumerate() {
+            let id = format!("R{}-{}", round, index + 1);
+            resolver.resolve(id, BracketSide::RoundRobin, round, Slot::Player(a), Slot::Player(b), true);
+        }
+    }
+
+    let mut standings: Vec<Standing> = seeds
+        .iter()
+        .map(|p| Standing { player: p.clone(), played: 0, wins: 0, draws: 0, losses: 0, points: 0 })
+        .collect();
+    for m in resolver.matches.iter().filter(|m| m.decided) {
+        for standing in standings.iter_mut() {
+            let is_player = m.player_1.as_ref() == Some(&standing.player) || m.player_2.as_ref() == Some(&standing.player);
+            if !is_player {
+                continue;
+            }
+            standing.played += 1;
+            match &m.winner {
+                Some(w) if *w == standing.player => {
+                    standing.wins += 1;
+                    standing.points += 3;
+                }
+                Some(_) => standing.losses += 1,
+                None => {
+                    standing.draws += 1;
+                    standing.points += 1;
+                }
+            }
+        }
+    }
+    // Ties keep seed order (stable sort).
+    standings.sort_by(|a, b| b.points.cmp(&a.points).then_with(|| b.wins.cmp(&a.wins)));
+
+    let all_decided = resolver.matches.iter().all(|m| m.decided);
+    Bracket {
+        champion: if all_decided { standings.first().map(|s| s.player.clone()) } else { None },
+        matches: resolver.matches,
+        standings,
+    }
+}
@@ -20,25 +20,20 @@ pub fn anchor_for(input: &str) -> ExternResult<AnyLinkableHash> {
 
 
 // Helper function to get game hash by game_id (original ActionHash of the game entry).
+// Resolves the action directly instead of scanning a global index.
 pub fn get_game_hash_by_id(game_id: &ActionHash) -> ExternResult<Option<ActionHash>> {
-    // Now uses the local `anchor_for` which delegates
-    let games_anchor = anchor_for("games")?;
-    let links = get_links(
-        LinkQuery::try_new(games_anchor, LinkTypes::GameIdToGame)?,
-        GetStrategy::default(),
-    )?;
-
-    for link in links {
-        if let Some(target_hash) = link.target.into_action_hash() {
-            if &target_hash == game_id {
-                return Ok(Some(target_hash));
-            }
-        }
+    let Some(record) = get(game_id.clone(), GetOptions::default())? else {
+        return Ok(None);
+    };
+    if !matches!(record.action(), Action::Create(_)) {
+        return Ok(None);
+    }
+    match record.entry().to_app_option::<Game>() {
+        Ok(Some(_)) => Ok(Some(record.action_hashed().hash.clone())),
+        _ => Ok(None),
     }
-    Ok(None)
 }
 
-
 // Helper function to check if a player exists (based on Player entry linked from AgentPubKey).
 pub fn player_exists(agent_pub_key: &AgentPubKey) -> ExternResult<bool> {
     let links = get_links(
@@ -69,7 +64,8 @@ pub fn is_player_in_ongoing_game(player_pub_key: &AgentPubKey) -> ExternResult<b
                      if let Ok(game) = Game::try_from(entry_data.clone()) { // Assuming Game from ping_2_pong_integrity
                          debug!("[utils.rs] is_player_in_ongoing_game: P1 Loop - Game {:?} deserialized. Status: {:?}, P1: {:?}, P2: {:?}", game_action_hash, game.game_status, game.player_1, game.player_2.is_some());
                          // *** FIX: Only return true if the game status is InProgress ***
-                         if game.game_status == GameStatus::InProgress { // Assuming GameStatus from ping_2_pong_integrity::game
+                         // Games past their resume grace period no longer count (see resume.rs)
+                         if game.game_status == GameStatus::InProgress && !crate::resume::is_stale(&game_action_hash, &record)? {
                             debug!("[utils.rs] is_player_in_ongoing_game: P1 Loop - Player {:?} IS in InProgress game {:?}. Returning true.", player_pub_key, game_action_hash);
                             return Ok(true);
                         }
@@ -96,7 +92,8 @@ pub fn is_player_in_ongoing_game(player_pub_key: &AgentPubKey) -> ExternResult<b
                       if let Ok(game) = Game::try_from(entry_data.clone()) { // Assuming Game from ping_2_pong_integrity
                           debug!("[utils.rs] is_player_in_ongoing_game: P2 Loop - Game {:?} deserialized. Status: {:?}, P1: {:?}, P2: {:?}", game_action_hash, game.game_status, game.player_1, game.player_2.is_some());
                           // *** FIX: Only return true if the game status is InProgress ***
-                          if game.game_status == GameStatus::InProgress { // Assuming GameStatus from ping_2_pong_integrity::game
+                          // Games past their resume grace period no longer count (see resume.rs)
+                          if game.game_status == GameStatus::InProgress && !crate::resume::is_stale(&game_action_hash, &record)? {
                              debug!("[utils.rs] is_player_in_ongoing_game: P2 Loop - Player {:?} IS in InProgress game {:?}. Returning true.", player_pub_key, game_action_hash);
                              return Ok(true);
                          }
@@ -0,0 +1,128 @@
+// ping_2_pong/dnas/ping_2_pong/zomes/coordinator/ping_2_pong/tests/game_validation.rs
+// Sweettest coverage for Game update validation, forfeit claims included. Needs the packed DNA, which
+// `npm test` builds before running `cargo test` (as CI does).
+use holochain::sweettest::*;
+use holochain::prelude::*;
+use ping_2_pong::game::{CreateGameInput, UpdateGameInput};
+use ping_2_pong_integrity::game::{ForfeitEvidence, GameStatus};
+use ping_2_pong_integrity::{Game, Player};
+use std::path::PathBuf;
+use std::time::Duration;
+
+fn dna_path() -> PathBuf {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../../workdir/ping_2_pong.dna");
+    assert!(path.exists(), "{} is missing: run `npm run build:happ` first, or use `npm test`", path.display());
+    path
+}
+
+// One conductor with Alice registered and a Waiting game she created.
+async fn alice_with_waiting_game() -> (SweetConductor, SweetZome, Record) {
+    let mut conductor = SweetConductor::from_standard_config().await;
+    let dna = SweetDnaFile::from_bundle(&dna_path()).await.unwrap();
+    let app = conductor.setup_app("ping2pong", &[dna]).await.unwrap();
+    let (cell,) = app.into_tuple();
+    let zome = cell.zome("ping_2_pong");
+    let alice = cell.agent_pubkey().clone();
+
+    let _: Record = conductor
+        .call(&zome, "create_player", Player { player_key: alice.clone(), player_name: "alice".into() })
+        .await;
+    let game: Record = conductor
+        .call(
+            &zome,
+            "create_game",
+            CreateGameInput { player_1: alice, player_2: None, netcode: Default::default(), rules: Default::default(), series: None },
+        )
+        .await;
+    (conductor, zome, game)
+}
+
+fn game_of(record: &Record) -> Game {
+    record.entry().to_app_option::<Game>().unwrap().unwrap()
+}
+
+async fn try_update(conductor: &SweetConductor, zome: &SweetZome, original: &Record, previous: &Record, updated: Game) -> Result<Record, String> {
+    conductor
+        .call_fallible::<_, Record>(
+            zome,
+            "update_game",
+            UpdateGameInput {
+                original_game_hash: original.action_address().clone(),
+                previous_game_hash: previous.action_address().clone(),
+                updated_game: updated,
+            },
+        )
+        .await
+        .map_err(|e| format!("{:?}", e))
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn update_changing_player_1_is_rejected() {
+    let (conductor, zome, game) = alice_with_waiting_game().await;
+    // Leaving the lobby is a legal transition; handing the game to someone else is not.
+    let mut hijacked = game_of(&game);
+    hijacked.game_status = GameStatus::Abandoned;
+    hijacked.player_1 = SweetAgents::one(conductor.keystore()).await;
+
+    let error = try_update(&conductor, &zome, &game, &game, hijacked).await.unwrap_err();
+    assert!(error.contains("Cannot change player_1"), "unexpected error: {}", error);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn update_reviving_a_terminal_game_is_rejected() {
+    let (conductor, zome, game) = alice_with_waiting_game().await;
+    let abandoned: Record = conductor.call(&zome, "abandon_game", game.action_address().clone()).await;
+    assert_eq!(game_of(&abandoned).game_status, GameStatus::Abandoned);
+
+    let mut revived = game_of(&abandoned);
+    revived.game_status = GameStatus::Waiting;
+    let error = try_update(&conductor, &zome, &game, &abandoned, revived).await.unwrap_err();
+    assert!(error.contains("Invalid game status transition"), "unexpected error: {}", error);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn legal_update_is_accepted() {
+    let (conductor, zome, game) = alice_with_waiting_game().await;
+    let mut left = game_of(&game);
+    left.game_status = GameStatus::Abandoned;
+
+    let record = try_update(&conductor, &zome, &game, &game, left).await.unwrap();
+    assert_eq!(game_of(&record).game_status, GameStatus::Abandoned);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn forfeit_citing_the_game_create_action_is_rejected() {
+    let mut conductor = SweetConductor::from_standard_config().await;
+    let dna = SweetDnaFile::from_bundle(&dna_path()).await.unwrap();
+    let apps = conductor.setup_apps("ping2pong", 2, &[dna]).await.unwrap();
+    let ((alice_cell,), (bob_cell,)) = apps.into_tuples();
+    let (alice_zome, bob_zome) = (alice_cell.zome("ping_2_pong"), bob_cell.zome("ping_2_pong"));
+    let (alice, bob) = (alice_cell.agent_pubkey().clone(), bob_cell.agent_pubkey().clone());
+
+    for (zome, agent, name) in [(&alice_zome, &alice, "alice"), (&bob_zome, &bob, "bob")] {
+        let _: Record = conductor.call(zome, "create_player", Player { player_key: agent.clone(), player_name: name.into() }).await;
+    }
+    let game: Record = conductor
+        .call(
+            &alice_zome,
+            "create_game",
+            CreateGameInput { player_1: alice.clone(), player_2: None, netcode: Default::default(), rules: Default::default(), series: None },
+        )
+        .await;
+    await_consistency(Duration::from_secs(30), [&alice_cell, &bob_cell]).await.unwrap();
+    let joined: Record = conductor.call(&bob_zome, "join_game", game.action_address().clone()).await;
+    await_consistency(Duration::from_secs(30), [&alice_cell, &bob_cell]).await.unwrap();
+
+    // Alice created the game, so its create action says nothing about Bob.
+    let mut claimed = game_of(&joined);
+    claimed.game_status = GameStatus::Finished;
+    claimed.forfeit = Some(ForfeitEvidence {
+        winner: alice,
+        winner_last_seen: Timestamp::now(),
+        opponent_last_seen: game.action().timestamp(),
+        opponent_last_action: game.action_address().clone(),
+        grace_period_secs: 120,
+    });
+    let error = try_update(&conductor, &alice_zome, &game, &joined, claimed).await.unwrap_err();
+    assert!(error.contains("must be an action of the opponent"), "unexpected error: {}", error);
+}
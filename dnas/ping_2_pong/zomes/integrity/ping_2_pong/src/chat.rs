@@ -1,9 +1,40 @@
 use hdi::prelude::*;
 
+// Where a chat message is posted. Each channel has its own history tree.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub enum ChatChannel {
+    #[default]
+    Global,
+    Game(ActionHash),                 // Original Game ActionHash; players and spectators
+    Direct(AgentPubKey, AgentPubKey), // 1:1 conversation, agents in sorted order; content sealed
+}
+
+impl ChatChannel {
+    /// The direct channel between two agents, whichever way round they are given.
+    pub fn direct(a: AgentPubKey, b: AgentPubKey) -> Self {
+        if a <= b { ChatChannel::Direct(a, b) } else { ChatChannel::Direct(b, a) }
+    }
+
+    /// Root of the channel's hour-bucketed history (root.year.month.day.hour).
+    pub fn history_root(&self) -> String {
+        match self {
+            ChatChannel::Global => "chat_history.global".to_string(),
+            ChatChannel::Game(game_id) => format!("chat_history.game.{}", game_id),
+            ChatChannel::Direct(a, b) => format!("chat_history.dm.{}.{}", a, b),
+        }
+    }
+}
+
 #[hdk_entry_helper]
 #[derive(Clone, PartialEq)]
 pub struct ChatMessage {
     pub sender: AgentPubKey,
     pub content: String,
     pub timestamp: Timestamp,
+    #[serde(default)] // Messages from before channels existed were global
+    pub channel: ChatChannel,
+    // Direct messages only: the content encrypted between the two agents, with
+    // `content` left empty, since entries are readable by anyone on the DHT
+    #[serde(default)]
+    pub sealed: Option<XSalsa20Poly1305EncryptedData>,
 }
@@ -0,0 +1,329 @@
+// ping_2_pong/dnas/ping_2_pong/zomes/integrity/ping_2_pong/src/chat_validation.rs
+use hdi::prelude::*;
+use crate::chat::{ChatChannel, ChatMessage};
+use crate::game::Game;
+use crate::properties::dna_properties;
+use crate::time_index::{hour_bucket, time_tag};
+use crate::{LinkTypes, UnitEntryTypes};
+
+// Encryption overhead of a sealed direct message (the Poly1305 tag).
+const SEALED_OVERHEAD_BYTES: usize = 16;
+
+// Actions fetched per step while walking the author's chain back to the start
+// of the rate-limit window.
+const RATE_LIMIT_SCAN_PAGE: u32 = 100;
+
+// Validate creation of a ChatMessage entry.
+pub fn validate_create_chat_message(
+    action: &TypedAction<CreateData>,
+    message: ChatMessage,
+) -> ExternResult<ValidateCallbackResult> {
+    if message.sender != *action.author() {
+        return Ok(ValidateCallbackResult::Invalid("Chat messages can only be sent as their author".into()));
+    }
+    let properties = dna_properties()?;
+    if let Some(reason) = check_body(&message, properties.max_chat_message_length) {
+        return Ok(ValidateCallbackResult::Invalid(reason));
+    }
+    if let Some(reason) = check_rate_limit(action.author(), action.prev_action(), action.timestamp())? {
+        return Ok(ValidateCallbackResult::Invalid(reason));
+    }
+    match &message.channel {
+        ChatChannel::Global => {}
+        ChatChannel::Game(game_id) => {
+            let game_record = must_get_valid_record(game_id.clone())?;
+            if !matches!(game_record.entry().to_app_option::<Game>(), Ok(Some(_))) {
+                return Ok(ValidateCallbackResult::Invalid("Game chat channel must reference a Game".into()));
+            }
+        }
+        ChatChannel::Direct(a, b) => {
+            if a >= b {
+                return Ok(ValidateCallbackResult::Invalid("Direct chat channel agents must be distinct and sorted".into()));
+            }
+            if a != action.author() && b != action.author() {
+                return Ok(ValidateCallbackResult::Invalid("Only the two agents of a direct chat can post in it".into()));
+            }
+        }
+    }
+    Ok(ValidateCallbackResult::Valid)
+}
+
+// Edits (updates of the original message) by its sender: only the content may change.
+pub fn validate_update_chat_message(
+    action: &SignedActionHashed,
+    update: &Update,
+    updated: ChatMessage,
+    original: &ChatMessage,
+) -> ExternResult<ValidateCallbackResult> {
+    if original.sender != *action.action().author() {
+        return Ok(ValidateCallbackResult::Invalid("Chat messages can only be edited by their sender".into()));
+    }
+    // Edits always point at the original message, so readers find them all in one place.
+    if !matches!(must_get_valid_record(update.original_action_address.clone())?.action(), Action::Create(_)) {
+        return Ok(ValidateCallbackResult::Invalid("Chat message edits must update the original message".into()));
+    }
+    if updated.sender != original.sender || updated.channel != original.channel || updated.timestamp != original.timestamp {
+        return Ok(ValidateCallbackResult::Invalid("Only the content of a chat message can be edited".into()));
+    }
+    if let Some(reason) = check_body(&updated, dna_properties()?.max_chat_message_length) {
+        return Ok(ValidateCallbackResult::Invalid(reason));
+    }
+    let edit = action.action();
+    if let Some(previous) = edit.prev_action() {
+        if let Some(reason) = check_rate_limit(edit.author(), previous, edit.timestamp())? {
+            return Ok(ValidateCallbackResult::Invalid(reason));
+        }
+    }
+    Ok(ValidateCallbackResult::Valid)
+}
+
+// Chat messages can only be deleted by their sender; readers show a tombstone.
+pub fn validate_delete_chat_message(action: &SignedActionHashed, original: ChatMessage) -> ExternResult<ValidateCallbackResult> {
+    if original.sender != *action.action().author() {
+        return Ok(ValidateCallbackResult::Invalid("Chat messages can only be deleted by their sender".into()));
+    }
+    Ok(ValidateCallbackResult::Valid)
+}
+
+// Reason the message body cannot be posted, if any: direct messages are sealed,
+// the others are plain text.
+fn check_body(message: &ChatMessage, max_length: usize) -> Option<String> {
+    match (&message.channel, &message.sealed) {
+        (ChatChannel::Direct(_, _), Some(sealed)) => {
+            if !message.content.is_empty() {
+                return Some("Direct messages carry their content sealed only".into());
+            }
+            // At most four UTF-8 bytes per character
+            if sealed.as_encrypted_data_ref().len() > max_length * 4 + SEALED_OVERHEAD_BYTES {
+                return Some(format!("Chat messages are limited to {} characters", max_length));
+            }
+            None
+        }
+        (ChatChannel::Direct(_, _), None) => Some("Direct messages must be sealed".into()),
+        (_, Some(_)) => Some("Only direct messages are sealed".into()),
+        (_, None) => check_content(&message.content, max_length),
+    }
+}
+
+fn check_content(content: &str, max_length: usize) -> Option<String> {
+    if content.trim().is_empty() {
+        return Some("Chat message content cannot be empty".into());
+    }
+    if content.chars().count() > max_length {
+        return Some(format!("Chat messages are limited to {} characters", max_length));
+    }
+    None
+}
+
+// Reason an author posting, editing or reacting at `now` is over the chat rate
+// limit, if they are. Messages, edits and reactions all count.
+fn check_rate_limit(author: &AgentPubKey, prev_action: &ActionHash, now: Timestamp) -> ExternResult<Option<String>> {
+    let properties = dna_properties()?;
+    let window_start = now.as_micros() - (properties.chat_rate_window_secs as i64).saturating_mul(1_000_000);
+    if count_recent_chat_actions(author, prev_action, window_start, properties.chat_rate_limit)? >= properties.chat_rate_limit {
+        return Ok(Some(format!(
+            "Chat rate limit exceeded: at most {} messages, edits or reactions per {} seconds",
+            properties.chat_rate_limit, properties.chat_rate_window_secs
+        )));
+    }
+    Ok(None)
+}
+
+// Whether a prior action counts towards the chat rate limit: a ChatMessage create
+// or edit, or a reaction (counted once, by its ChatMessageToReactions link).
+fn is_chat_action(prior: &Action, chat_message_type: &EntryType) -> ExternResult<bool> {
+    if prior.entry_type() == Some(chat_message_type) {
+        return Ok(true);
+    }
+    let Action::CreateLink(create_link) = prior else {
+        return Ok(false);
+    };
+    Ok(matches!(
+        LinkTypes::from_type(create_link.zome_index, create_link.link_type)?,
+        Some(LinkTypes::ChatMessageToReactions)
+    ))
+}
+
+// Chat actions the author made before `prev_action` (inclusive), at or after
+// `window_start` (micros), counted until `limit` is reached. Chain timestamps only
+// go up, so the walk back ends at the first page reaching past the window,
+// however long the chain is.
+fn count_recent_chat_actions(author: &AgentPubKey, prev_action: &ActionHash, window_start: i64, limit: u32) -> ExternResult<u32> {
+    let chat_message_type: EntryType = UnitEntryTypes::ChatMessage.try_into()?;
+    let in_window = |prior: &Action| prior.timestamp().as_micros() >= window_start;
+    let mut count = 0;
+    let mut from = prev_action.clone();
+    loop {
+        let filter = ChainFilter::new(from).take(RATE_LIMIT_SCAN_PAGE);
+        let activity = must_get_agent_activity(author.clone(), filter)?;
+        for prior in activity.iter().map(|item| item.action.action()) {
+            if in_window(prior) && is_chat_action(prior, &chat_message_type)? {
+                count += 1;
+            }
+        }
+        let oldest = activity.iter().map(|item| item.action.action()).min_by_key(|prior| prior.action_seq());
+        let Some(oldest) = oldest else {
+            return Ok(count);
+        };
+        let reached_window_start = !in_window(oldest);
+        match oldest.prev_action() {
+            Some(previous) if count < limit && !reached_window_start && activity.len() == RATE_LIMIT_SCAN_PAGE as usize => {
+                from = previous.clone();
+            }
+            _ => return Ok(count),
+        }
+    }
+}
+
+// AgentToDirectChats: either agent of a direct chat may list it for both.
+pub fn validate_agent_to_direct_chat_link(action: &TypedAction<CreateLinkData>) -> ExternResult<ValidateCallbackResult> {
+    let Some(base_agent) = action.base_address.clone().into_agent_pub_key() else {
+        return Ok(ValidateCallbackResult::Invalid("AgentToDirectChats base must be an AgentPubKey".into()));
+    };
+    let Some(target_agent) = action.target_address.clone().into_agent_pub_key() else {
+        return Ok(ValidateCallbackResult::Invalid("AgentToDirectChats target must be an AgentPubKey".into()));
+    };
+    if base_agent == target_agent {
+        return Ok(ValidateCallbackResult::Invalid("A direct chat needs two different agents".into()));
+    }
+    if *action.author() != base_agent && *action.author() != target_agent {
+        return Ok(ValidateCallbackResult::Invalid("Only an agent of a direct chat can link it".into()));
+    }
+    Ok(ValidateCallbackResult::Valid)
+}
+
+// ChatTimePath: path tree links between a channel's history buckets.
+pub fn validate_chat_time_path_link(action: &TypedAction<CreateLinkData>) -> ExternResult<ValidateCallbackResult> {
+    if action.base_address.clone().into_entry_hash().is_none() {
+        return Ok(ValidateCallbackResult::Invalid("ChatTimePath base must be an EntryHash (path)".into()));
+    }
+    if action.target_address.clone().into_entry_hash().is_none() {
+        return Ok(ValidateCallbackResult::Invalid("ChatTimePath target must be an EntryHash (path)".into()));
+    }
+    Ok(ValidateCallbackResult::Valid)
+}
+
+// TimeBucketToChatMessage: the message is linked by its sender into the hour bucket
+// of its own channel's history for the time it was sent, which the tag carries.
+pub fn validate_time_bucket_to_chat_message_link(action: &TypedAction<CreateLinkData>) -> ExternResult<ValidateCallbackResult> {
+    let Some(bucket_hash) = action.base_address.clone().into_entry_hash() else {
+        return Ok(ValidateCallbackResult::Invalid("TimeBucketToChatMessage base must be an EntryHash (path)".into()));
+    };
+    let Some(message_hash) = action.target_address.clone().into_action_hash() else {
+        return Ok(ValidateCallbackResult::Invalid("TimeBucketToChatMessage target must be a ChatMessage ActionHash".into()));
+    };
+    let message_record = must_get_valid_record(message_hash)?;
+    let Ok(Some(message)) = message_record.entry().to_app_option::<ChatMessage>() else {
+        return Ok(ValidateCallbackResult::Invalid("TimeBucketToChatMessage target is not a ChatMessage".into()));
+    };
+    if message.sender != *action.author() {
+        return Ok(ValidateCallbackResult::Invalid("Chat messages can only be linked by their sender".into()));
+    }
+    if !matches!(message_record.action(), Action::Create(_)) {
+        return Ok(ValidateCallbackResult::Invalid("Only the original chat message is linked into the history".into()));
+    }
+    if hour_bucket(&message.channel.history_root(), message.timestamp).path_entry_hash()? != bucket_hash {
+        return Ok(ValidateCallbackResult::Invalid("Chat message linked from another channel's or hour's bucket".into()));
+    }
+    if action.tag != time_tag(message.timestamp) {
+        return Ok(ValidateCallbackResult::Invalid("TimeBucketToChatMessage tag must be the message's timestamp".into()));
+    }
+    Ok(ValidateCallbackResult::Valid)
+}
+
+// Reaction: reacting AgentPubKey -> ChatMessage, tagged with the emoji.
+pub fn validate_reaction_link(action: &TypedAction<CreateLinkData>) -> ExternResult<ValidateCallbackResult> {
+    let Some(agent) = action.base_address.clone().into_agent_pub_key() else {
+        return Ok(ValidateCallbackResult::Invalid("Reaction base must be an AgentPubKey".into()));
+    };
+    let Some(message_hash) = action.target_address.clone().into_action_hash() else {
+        return Ok(ValidateCallbackResult::Invalid("Reaction target must be a ChatMessage ActionHash".into()));
+    };
+    validate_reaction(action, &agent, message_hash)
+}
+
+// ChatMessageToReactions: ChatMessage -> reacting AgentPubKey, tagged with the emoji.
+pub fn validate_chat_message_to_reactions_link(action: &TypedAction<CreateLinkData>) -> ExternResult<ValidateCallbackResult> {
+    let Some(message_hash) = action.base_address.clone().into_action_hash() else {
+        return Ok(ValidateCallbackResult::Invalid("ChatMessageToReactions base must be a ChatMessage ActionHash".into()));
+    };
+    let Some(agent) = action.target_address.clone().into_agent_pub_key() else {
+        return Ok(ValidateCallbackResult::Invalid("ChatMessageToReactions target must be an AgentPubKey".into()));
+    };
+    validate_reaction(action, &agent, message_hash)
+}
+
+/// The emoji a reaction link tag carries: a single emoji of up to eight characters,
+/// which may be a keycap, a flag, or pictographs with modifiers joined by ZWJ.
+pub fn reaction_emoji(tag: &LinkTag) -> Option<String> {
+    let emoji = std::str::from_utf8(&tag.0).ok()?;
+    let chars: Vec<char> = emoji.chars().collect();
+    if chars.is_empty() || chars.len() > 8 || chars[chars.len() - 1] == '\u{200D}' {
+        return None;
+    }
+    let keycap = matches!(chars[0], '0'..='9' | '#' | '*');
+    if keycap && (chars[chars.len() - 1] != '\u{20E3}' || !chars[1..].iter().all(|c| matches!(*c, '\u{FE0F}' | '\u{20E3}'))) {
+        return None;
+    }
+    if !keycap && !is_pictograph(chars[0]) {
+        return None;
+    }
+    for (index, c) in chars.iter().enumerate().skip(1) {
+        let joined = chars[index - 1] == '\u{200D}';
+        if joined && !is_pictograph(*c) {
+            return None;
+        }
+        if !is_pictograph(*c) && !is_emoji_modifier(*c) {
+            return None;
+        }
+    }
+    Some(emoji.to_string())
+}
+
+// Characters that are emoji on their own (pictographs, symbols, regional indicators).
+fn is_pictograph(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x1F000..=0x1FAFF // Pictographs, emoticons, transport, flags' regional indicators...
+            | 0x2600..=0x27BF // Miscellaneous symbols, dingbats
+            | 0x2300..=0x23FF // Miscellaneous technical (watch, hourglass, ...)
+            | 0x2B00..=0x2BFF // Arrows and stars
+            | 0x2190..=0x21FF // Arrows
+            | 0x25A0..=0x25FF // Geometric shapes
+            | 0x2934 | 0x2935 | 0x3030 | 0x303D | 0x3297 | 0x3299
+            | 0x00A9 | 0x00AE | 0x203C | 0x2049 | 0x2122 | 0x2139 | 0x24C2
+    ) && !matches!(c as u32, 0x1F3FB..=0x1F3FF)
+}
+
+// Characters that only modify or join the emoji before them.
+fn is_emoji_modifier(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x200D // Zero-width joiner
+            | 0xFE0F // Emoji presentation
+            | 0x20E3 // Combining keycap
+            | 0x1F3FB..=0x1F3FF // Skin tones
+            | 0xE0020..=0xE007F // Tag characters (subdivision flags)
+    )
+}
+
+fn validate_reaction(
+    action: &TypedAction<CreateLinkData>,
+    agent: &AgentPubKey,
+    message_hash: ActionHash,
+) -> ExternResult<ValidateCallbackResult> {
+    if agent != action.author() {
+        return Ok(ValidateCallbackResult::Invalid("Agents can only react for themselves".into()));
+    }
+    if reaction_emoji(&action.tag).is_none() {
+        return Ok(ValidateCallbackResult::Invalid("Reaction tag must be a single emoji".into()));
+    }
+    if let Some(reason) = check_rate_limit(action.author(), action.prev_action(), action.timestamp())? {
+        return Ok(ValidateCallbackResult::Invalid(reason));
+    }
+    let message_record = must_get_valid_record(message_hash)?;
+    if !matches!(message_record.entry().to_app_option::<ChatMessage>(), Ok(Some(_))) {
+        return Ok(ValidateCallbackResult::Invalid("Reactions must reference a ChatMessage".into()));
+    }
+    Ok(ValidateCallbackResult::Valid)
+}
@@ -0,0 +1,16 @@
+// ping_2_pong/dnas/ping_2_pong/zomes/integrity/ping_2_pong/src/checkpoint.rs
+use hdi::prelude::*;
+
+// Periodic snapshot of an in-progress game, published by either player.
+// Used to resume after a reconnect and as evidence that a player was still active.
+#[hdk_entry_helper]
+#[derive(Clone, PartialEq)]
+pub struct GameCheckpoint {
+    pub game_id: ActionHash,          // Original Game ActionHash
+    pub game_revision: ActionHash,    // A Game revision naming both players
+    pub player: AgentPubKey,          // Author of the checkpoint
+    pub score_1: u32,
+    pub score_2: u32,
+    pub serve_to: Option<AgentPubKey>, // Player the next serve goes towards, if between points
+    pub taken_at: Timestamp,
+}
@@ -0,0 +1,51 @@
+// ping_2_pong/dnas/ping_2_pong/zomes/integrity/ping_2_pong/src/checkpoint_validation.rs
+use hdi::prelude::*;
+use crate::checkpoint::GameCheckpoint;
+use crate::game_validation::game_at_revision;
+use core::time::Duration;
+use std::ops::{Add, Sub};
+
+// Validate creation of a GameCheckpoint entry.
+pub fn validate_create_game_checkpoint(
+    action: &TypedAction<CreateData>,
+    checkpoint: GameCheckpoint,
+) -> ExternResult<ValidateCallbackResult> {
+    let author = action.author();
+    // 1. Check Author: Checkpoints are published by the player they claim.
+    if checkpoint.player != *author {
+        return Ok(ValidateCallbackResult::Invalid("Checkpoint player must be its author".to_string()));
+    }
+
+    // 2. Check Participation: Only the two players of the game can checkpoint it.
+    // Player 2 of a game joined from the lobby is only named in a later revision.
+    let Some(game) = game_at_revision(&checkpoint.game_id, &checkpoint.game_revision)? else {
+        return Ok(ValidateCallbackResult::Invalid(
+            "Checkpoint game_revision is not a revision of its Game".to_string(),
+        ));
+    };
+    let Some(player_2) = game.player_2.as_ref() else {
+        return Ok(ValidateCallbackResult::Invalid("Checkpoint game_revision must name both players".to_string()));
+    };
+    if game.player_1 != *author && player_2 != author {
+        return Ok(ValidateCallbackResult::Invalid("Only game participants can checkpoint a game".to_string()));
+    }
+    if let Some(serve_to) = &checkpoint.serve_to {
+        if *serve_to != game.player_1 && serve_to != player_2 {
+            return Ok(ValidateCallbackResult::Invalid("Serve must go towards a game participant".to_string()));
+        }
+    }
+
+    // 3. Check Timestamp: taken_at must be close to the action time (+/- 5 mins).
+    let five_minutes = Duration::from_secs(300);
+    let lower_bound = action.timestamp().sub(five_minutes)
+        .map_err(|e| wasm_error!(WasmErrorInner::Guest(format!("Timestamp subtraction error: {}", e))))?;
+    let upper_bound = action.timestamp().add(five_minutes)
+        .map_err(|e| wasm_error!(WasmErrorInner::Guest(format!("Timestamp addition error: {}", e))))?;
+    if checkpoint.taken_at < lower_bound || checkpoint.taken_at > upper_bound {
+        return Ok(ValidateCallbackResult::Invalid(
+            "Checkpoint taken_at timestamp is too far from action timestamp".to_string(),
+        ));
+    }
+
+    Ok(ValidateCallbackResult::Valid)
+}
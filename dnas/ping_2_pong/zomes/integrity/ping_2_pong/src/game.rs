@@ -1,8 +1,9 @@
 // ping_2_pong/dnas/ping_2_pong/zomes/integrity/ping_2_pong/src/game.rs
 use hdi::prelude::*;
+use crate::game_rules::GameRules;
 
 // Define the Game Status enum.
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum GameStatus {
     Waiting,    // Waiting for Player 2 or matchmaking
     InProgress, // Game actively being played
@@ -11,6 +12,27 @@ pub enum GameStatus {
     // Canceled? // Optional status
 }
 
+// How the two peers keep the match in sync.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NetcodeMode {
+    #[default]
+    BallStreaming, // The host streams ball state (send_ball_update)
+    Lockstep,      // Peers exchange paddle inputs only and both simulate (send_input_frame)
+}
+
+// Evidence recorded when a player wins because their opponent disappeared
+// (claim_forfeit). Last-seen times come from the players' game checkpoints.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ForfeitEvidence {
+    pub winner: AgentPubKey,           // The claimant; always the author of the forfeit update
+    pub winner_last_seen: Timestamp,
+    pub opponent_last_seen: Timestamp,
+    // The opponent's latest activity, timestamped opponent_last_seen: their checkpoint
+    // of the game, their presence link, or the game revision they were last seen in
+    pub opponent_last_action: ActionHash,
+    pub grace_period_secs: u64,        // Grace period in force when the claim was made
+}
+
 // Define the Game entry structure.
 // Note: Paddle/Ball positions here are informational defaults or latest *saved* state,
 // not the real-time state which is handled by signals.
@@ -26,6 +48,14 @@ pub struct Game {
     pub player_2_paddle: u32,
     pub ball_x: u32,
     pub ball_y: u32,
+    #[serde(default)] // Games created before netcode modes existed stream the ball
+    pub netcode: NetcodeMode,
+    #[serde(default)] // Games created before per-game rules use the standard ones
+    pub rules: GameRules,
+    #[serde(default)] // Set only when the game was won by forfeit
+    pub forfeit: Option<ForfeitEvidence>,
+    #[serde(default)] // Original Series ActionHash for a game of a series, fixed at creation
+    pub series: Option<ActionHash>,
     // pub initial_ball_vector_x: i32, // Maybe store initial vector? Optional.
     // pub initial_ball_vector_y: i32,
 }
\ No newline at end of file
@@ -0,0 +1,161 @@
+// ping_2_pong/dnas/ping_2_pong/zomes/integrity/ping_2_pong/src/game_lifecycle.rs
+use hdi::prelude::*;
+use crate::game::{ForfeitEvidence, Game, GameStatus};
+
+// Single source of truth for Game status transitions.
+// Used by `validate_update_game` / `validate_delete_game` and by every coordinator
+// function that changes a game's status, so the two can't drift apart.
+
+/// Who may author a transition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuthorRole {
+    /// Player 1 or the Player 2 being set by this very update (joining a Waiting game).
+    /// Player 1 can only start a game whose Player 2 is already named.
+    Player1OrJoiner,
+    /// Player 1 or Player 2 of the game.
+    Participant,
+}
+
+/// A legal status transition and what it requires.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Transition {
+    pub from: GameStatus,
+    pub to: GameStatus,
+    pub author: AuthorRole,
+    /// Player 2 must be set on the updated game.
+    pub requires_player_2: bool,
+    /// Player 2 may go from None to Some in this transition.
+    pub sets_player_2: bool,
+    /// Informational paddle/ball positions may be saved in this transition.
+    pub saves_positions: bool,
+}
+
+pub const TRANSITIONS: &[Transition] = &[
+    // Join (join_game, matchmaking)
+    Transition { from: GameStatus::Waiting, to: GameStatus::InProgress, author: AuthorRole::Player1OrJoiner, requires_player_2: true, sets_player_2: true, saves_positions: false },
+    // Leave the lobby before anyone joined, or walk away mid-game (abandon_game)
+    Transition { from: GameStatus::Waiting, to: GameStatus::Abandoned, author: AuthorRole::Participant, requires_player_2: false, sets_player_2: false, saves_positions: false },
+    Transition { from: GameStatus::InProgress, to: GameStatus::Abandoned, author: AuthorRole::Participant, requires_player_2: false, sets_player_2: false, saves_positions: false },
+    // Game over (finish_game); repeating it is harmless when both players report
+    Transition { from: GameStatus::InProgress, to: GameStatus::Finished, author: AuthorRole::Participant, requires_player_2: true, sets_player_2: false, saves_positions: true },
+    Transition { from: GameStatus::Finished, to: GameStatus::Finished, author: AuthorRole::Participant, requires_player_2: true, sets_player_2: false, saves_positions: false },
+];
+
+/// Looks up the transition between two statuses, if it is legal at all.
+pub fn find_transition(from: &GameStatus, to: &GameStatus) -> Option<&'static Transition> {
+    TRANSITIONS.iter().find(|t| t.from == *from && t.to == *to)
+}
+
+/// Checks that `author` may move `original` to `updated`.
+/// Returns a human-readable reason when the update is not allowed.
+pub fn check_transition(original: &Game, updated: &Game, author: &AgentPubKey) -> Result<&'static Transition, String> {
+    let transition = find_transition(&original.game_status, &updated.game_status).ok_or(format!(
+        "Invalid game status transition from {:?} to {:?}", original.game_status, updated.game_status
+    ))?;
+
+    // --- Author Check ---
+    let is_player1 = original.player_1 == *author;
+    let is_player2 = original.player_2.as_ref() == Some(author);
+    let is_joiner = transition.sets_player_2 && updated.player_2.as_ref() == Some(author);
+    let allowed = match transition.author {
+        AuthorRole::Player1OrJoiner => is_player1 || is_player2 || is_joiner,
+        AuthorRole::Participant => is_player1 || is_player2,
+    };
+    if !allowed {
+        return Err(format!(
+            "Author is not allowed to move a game from {:?} to {:?}", transition.from, transition.to
+        ));
+    }
+
+    // Only the joiner can name themselves Player 2, so nobody becomes a participant
+    // of a game they never agreed to play.
+    if transition.sets_player_2 && !is_joiner && updated.player_2 != original.player_2 {
+        return Err("Only the joining agent can set themselves as Player 2".to_string());
+    }
+
+    // --- Immutability Check ---
+    if updated.player_1 != original.player_1 || updated.created_at != original.created_at {
+        return Err("Cannot change player_1 or created_at".to_string());
+    }
+    if updated.netcode != original.netcode {
+        return Err("Cannot change the netcode mode of a game".to_string());
+    }
+    if updated.rules != original.rules {
+        return Err("Cannot change the rules of a game".to_string());
+    }
+    if updated.series != original.series {
+        return Err("Cannot change the series of a game".to_string());
+    }
+    if updated.player_2 != original.player_2 {
+        if !transition.sets_player_2 || original.player_2.is_some() || updated.player_2.is_none() {
+            return Err("Player 2 can only be changed from None to Some when joining".to_string());
+        }
+    }
+    if updated.forfeit != original.forfeit {
+        let finishing = transition.from == GameStatus::InProgress && transition.to == GameStatus::Finished;
+        if !finishing || original.forfeit.is_some() {
+            return Err("Forfeit evidence can only be added when finishing an in-progress game".to_string());
+        }
+    }
+    if transition.requires_player_2 && updated.player_2.is_none() {
+        return Err(format!("Cannot transition to {:?} without Player 2 being set", transition.to));
+    }
+    if updated.player_2.as_ref() == Some(&updated.player_1) {
+        return Err("Player 1 and Player 2 cannot be the same agent".to_string());
+    }
+
+    // --- Prevent Real-time State Updates via DHT ---
+    let positions_changed = updated.player_1_paddle != original.player_1_paddle
+        || updated.player_2_paddle != original.player_2_paddle
+        || updated.ball_x != original.ball_x
+        || updated.ball_y != original.ball_y;
+    if positions_changed && !transition.saves_positions {
+        return Err("Cannot update paddle/ball positions via DHT entry update (use signals)".to_string());
+    }
+
+    Ok(transition)
+}
+
+/// Checks a forfeit claim added to `game` by `author` at `now`: the claimant is
+/// the winner and the opponent was last seen more than the grace period ago,
+/// and before the claimant. That the opponent's last-seen time belongs to
+/// `opponent_last_action` needs the DHT and is checked in game_validation.rs.
+pub fn check_forfeit(
+    game: &Game,
+    evidence: &ForfeitEvidence,
+    author: &AgentPubKey,
+    now: Timestamp,
+    grace_period_secs: u64,
+) -> Result<(), String> {
+    if evidence.winner != *author {
+        return Err("Only the winner can claim a forfeit".to_string());
+    }
+    if game.player_1 != *author && game.player_2.as_ref() != Some(author) {
+        return Err("Forfeit winner must be a participant of the game".to_string());
+    }
+    if evidence.winner_last_seen > now || evidence.opponent_last_seen > now {
+        return Err("Forfeit last-seen times cannot be in the future".to_string());
+    }
+    if evidence.opponent_last_seen >= evidence.winner_last_seen {
+        return Err("The opponent was seen after the claimant".to_string());
+    }
+    if evidence.grace_period_secs < grace_period_secs {
+        return Err(format!("Forfeit grace period must be at least {} seconds", grace_period_secs));
+    }
+    let idle_ms = now.as_millis() - evidence.opponent_last_seen.as_millis();
+    if idle_ms <= (grace_period_secs as i64).saturating_mul(1_000) {
+        return Err("The opponent has not been away for the grace period yet".to_string());
+    }
+    Ok(())
+}
+
+/// Checks that `author` may delete `game`: only participants, only while Waiting.
+pub fn check_delete(game: &Game, author: &AgentPubKey) -> Result<(), String> {
+    if game.player_1 != *author && game.player_2.as_ref() != Some(author) {
+        return Err("Only game participants can delete the game".to_string());
+    }
+    if game.game_status != GameStatus::Waiting {
+        return Err("Only games in 'Waiting' status can be deleted".to_string());
+    }
+    Ok(())
+}
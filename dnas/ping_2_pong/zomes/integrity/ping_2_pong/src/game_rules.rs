@@ -0,0 +1,86 @@
+// ping_2_pong/dnas/ping_2_pong/zomes/integrity/ping_2_pong/src/game_rules.rs
+use hdi::prelude::*;
+
+// Rules of a single game, fixed when it is created.
+// Used by `validate_create_game` (sane ranges), by MatchResult validation
+// (the final score must be one these rules can produce) and, as a `pong_sim`
+// config, by the coordinator's simulation. Longer matches are a `Series`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default)] // Omitted fields take the standard value
+pub struct GameRules {
+    pub points_to_win: u32,
+    pub win_by_two: bool,         // Play on past points_to_win until one player leads by two
+    pub serve_speed_tenths: u32,  // Horizontal serve speed, tenths of a pixel per frame
+    pub max_ball_speed: u32,      // Pixels per frame
+    pub acceleration_pct: u32,    // Speed gained per paddle hit, percent
+    pub paddle_height: u32,       // Pixels
+    pub field_width: u32,         // Pixels
+    pub field_height: u32,        // Pixels
+}
+
+// The rules the UI and `pong_sim` were built around.
+impl Default for GameRules {
+    fn default() -> Self {
+        GameRules {
+            points_to_win: 10,
+            win_by_two: false,
+            serve_speed_tenths: 25,
+            max_ball_speed: 12,
+            acceleration_pct: 8,
+            paddle_height: 100,
+            field_width: 800,
+            field_height: 600,
+        }
+    }
+}
+
+impl GameRules {
+    /// Starting position of the paddles' top edge (centred).
+    pub fn paddle_start(&self) -> u32 {
+        (self.field_height - self.paddle_height) / 2
+    }
+
+    /// Starting ball position (centre of the field).
+    pub fn ball_start(&self) -> (u32, u32) {
+        (self.field_width / 2, self.field_height / 2)
+    }
+}
+
+const MAX_POINTS_TO_WIN: u32 = 100;
+
+/// Checks that the rules are playable.
+/// Returns a human-readable reason when they are not.
+pub fn check_rules(rules: &GameRules) -> Result<(), String> {
+    if rules.points_to_win == 0 || rules.points_to_win > MAX_POINTS_TO_WIN {
+        return Err(format!("points_to_win must be between 1 and {}", MAX_POINTS_TO_WIN));
+    }
+    if !(200..=4000).contains(&rules.field_width) || !(150..=3000).contains(&rules.field_height) {
+        return Err("Field must be 200-4000 pixels wide and 150-3000 pixels high".to_string());
+    }
+    if rules.paddle_height < 10 || rules.paddle_height > rules.field_height / 2 {
+        return Err("paddle_height must be between 10 pixels and half the field height".to_string());
+    }
+    if rules.serve_speed_tenths == 0 || rules.max_ball_speed == 0 || rules.serve_speed_tenths > rules.max_ball_speed * 10 {
+        return Err("Ball speeds must be positive and the serve no faster than max_ball_speed".to_string());
+    }
+    if rules.max_ball_speed > 50 || rules.acceleration_pct > 100 {
+        return Err("max_ball_speed is limited to 50 and acceleration_pct to 100".to_string());
+    }
+    Ok(())
+}
+
+/// Checks that a final score is one the rules can produce: somebody won, and
+/// play stopped right there.
+pub fn check_final_score(rules: &GameRules, score_1: u32, score_2: u32) -> Result<(), String> {
+    let (high, low) = (score_1.max(score_2), score_1.min(score_2));
+    if rules.win_by_two {
+        // First to points_to_win with a two-point lead; past it, play stops at exactly two.
+        let won = high >= rules.points_to_win && high - low >= 2;
+        if !won || (high > rules.points_to_win && high - low != 2) {
+            return Err(format!("Score {}-{} is not a finished game to {} (win by two)", score_1, score_2, rules.points_to_win));
+        }
+    } else if high != rules.points_to_win || low >= rules.points_to_win {
+        return Err(format!("Score {}-{} is not a finished game to {}", score_1, score_2, rules.points_to_win));
+    }
+    Ok(())
+}
@@ -1,6 +1,12 @@
 // ping_2_pong/dnas/ping_2_pong/zomes/integrity/ping_2_pong/src/game_validation.rs
 use hdi::prelude::*;
-use crate::game::{Game, GameStatus};
+use crate::checkpoint::GameCheckpoint;
+use crate::game::{ForfeitEvidence, Game, GameStatus};
+use crate::game_lifecycle;
+use crate::game_rules;
+use crate::properties::dna_properties;
+use crate::series::Series;
+use crate::LinkTypes;
 // Use core::time::Duration for stability if hdk::prelude::Duration is problematic
 use core::time::Duration;
 // Import Add/Sub traits for Timestamp arithmetic
@@ -26,6 +32,15 @@ pub fn validate_create_game(
         ));
     }
 
+    if let Err(reason) = game_rules::check_rules(&game.rules) {
+        return Ok(ValidateCallbackResult::Invalid(reason));
+    }
+    if game.forfeit.is_some() {
+        return Ok(ValidateCallbackResult::Invalid(
+            "Game cannot be created with forfeit evidence".to_string(),
+        ));
+    }
+
      // 3. Check Player 1 != Player 2
      if let Some(p2) = &game.player_2 {
          if game.player_1 == *p2 {
@@ -54,112 +69,165 @@ pub fn validate_create_game(
          ));
      }
 
+    // 5. Check Series: a series game is between its players, under its rules, after it started.
+    if let Some(series_hash) = &game.series {
+        if let Err(reason) = check_series_game(series_hash, &game)? {
+            return Ok(ValidateCallbackResult::Invalid(reason));
+        }
+    }
+
     Ok(ValidateCallbackResult::Valid)
 }
 
+/// Checks that `game` can be a game of the series created by `series_hash`.
+pub fn check_series_game(series_hash: &ActionHash, game: &Game) -> ExternResult<Result<(), String>> {
+    let record = must_get_valid_record(series_hash.clone())?;
+    if !matches!(record.action(), Action::Create(_)) {
+        return Ok(Err("A game's series must be the Series create action".to_string()));
+    }
+    let Ok(Some(series)) = record.entry().to_app_option::<Series>() else {
+        return Ok(Err("A game's series must reference a Series".to_string()));
+    };
+    let same_players = game.player_2.as_ref().is_some_and(|player_2| {
+        series.is_player(&game.player_1) && series.is_player(player_2)
+    });
+    if !same_players {
+        return Ok(Err("Series games are played between the series' players".to_string()));
+    }
+    if game.rules != series.rules || game.netcode != series.netcode {
+        return Ok(Err("Series games must be played under the series rules and netcode".to_string()));
+    }
+    if game.created_at < series.created_at {
+        return Ok(Err("A series game cannot predate its series".to_string()));
+    }
+    Ok(Ok(()))
+}
+
 // Validate updating a Game entry.
+// Transition rules (author role, required fields) live in `game_lifecycle`.
 pub fn validate_update_game(
     action: &SignedActionHashed,
     updated_game: Game,
     original_game: &Game,
 ) -> ExternResult<ValidateCallbackResult> {
-
     let author = action.action().author();
-
-    // --- Author Check ---
-    // Allow update if:
-    // 1. Author is Player 1
-    // 2. Author is the (existing) Player 2
-    // 3. Game is Waiting->InProgress AND Author is the NEW Player 2 being added
-    let is_player1 = original_game.player_1 == *author;
-    let is_existing_player2 = original_game.player_2.as_ref() == Some(author);
-    let is_new_player2_joining = original_game.game_status == GameStatus::Waiting
-                                 && updated_game.game_status == GameStatus::InProgress
-                                 && updated_game.player_2.as_ref() == Some(author);
-
-    if !is_player1 && !is_existing_player2 && !is_new_player2_joining {
-         return Ok(ValidateCallbackResult::Invalid(
-             "Update author must be Player 1, existing Player 2, or new Player 2 joining a Waiting game".to_string(),
-         ));
+    if let Err(reason) = game_lifecycle::check_transition(original_game, &updated_game, author) {
+        return Ok(ValidateCallbackResult::Invalid(reason));
     }
-
-    // --- Immutability Check ---
-    if updated_game.player_1 != original_game.player_1
-        || updated_game.created_at != original_game.created_at
-        // Allow player_2 to change ONLY when going from Waiting -> InProgress
-        || (updated_game.player_2 != original_game.player_2 && !(original_game.game_status == GameStatus::Waiting && updated_game.game_status == GameStatus::InProgress))
-    {
-        return Ok(ValidateCallbackResult::Invalid(
-            "Cannot change player_1, created_at, or player_2 (except when joining)".to_string(),
-        ));
+    // A newly added forfeit claim must cite real activity of the opponent, and hold
+    // at the time of the update.
+    if let (Some(evidence), None) = (&updated_game.forfeit, &original_game.forfeit) {
+        let Action::Update(update) = action.action() else {
+            return Ok(ValidateCallbackResult::Invalid("A forfeit can only be claimed by updating the game".to_string()));
+        };
+        let game_id = original_action_of(&update.original_action_address)?;
+        if let Err(reason) = check_forfeit_activity(&game_id, &update.original_action_address, &updated_game, evidence)? {
+            return Ok(ValidateCallbackResult::Invalid(reason));
+        }
+        let grace_period_secs = dna_properties()?.resume_grace_period_secs;
+        if let Err(reason) = game_lifecycle::check_forfeit(&updated_game, evidence, author, action.action().timestamp(), grace_period_secs) {
+            return Ok(ValidateCallbackResult::Invalid(reason));
+        }
     }
-    // Ensure if player_2 changed, it went from None to Some
-     if updated_game.player_2 != original_game.player_2 {
-          if original_game.player_2.is_some() || updated_game.player_2.is_none() {
-                return Ok(ValidateCallbackResult::Invalid("Player 2 can only be changed from None to Some when joining".into()));
-          }
-          // Also check the author was the new player 2 (already covered by author check above)
-     }
-
+    Ok(ValidateCallbackResult::Valid)
+}
 
-     // --- Prevent Real-time State Updates via DHT ---
-     if updated_game.player_1_paddle != original_game.player_1_paddle
-         || updated_game.player_2_paddle != original_game.player_2_paddle
-         || updated_game.ball_x != original_game.ball_x
-         || updated_game.ball_y != original_game.ball_y
-     {
-          if updated_game.game_status == GameStatus::Finished && original_game.game_status != GameStatus::Finished {
-                // Allowing update to paddle/ball positions as game transitions to Finished state
-          } else if updated_game.game_status == GameStatus::Finished && original_game.game_status == GameStatus::Finished {
-                return Ok(ValidateCallbackResult::Invalid( "Cannot update paddle/ball positions on an already Finished game".to_string() ));
-          } else {
-                return Ok(ValidateCallbackResult::Invalid( "Cannot update paddle/ball positions via DHT entry update (use signals)".to_string() ));
-          }
-     }
+// The opponent's last-seen time must be that of one of their own actions on their
+// valid chain: their checkpoint of this game, their presence link, or a revision of
+// this game they authored. It cannot predate their newest revision of the game,
+// which the update chain up to `previous_revision` shows. Newer checkpoints and
+// presence are only reachable through links, so `claim_forfeit` cites the newest
+// it can see.
+fn check_forfeit_activity(
+    game_id: &ActionHash,
+    previous_revision: &ActionHash,
+    game: &Game,
+    evidence: &ForfeitEvidence,
+) -> ExternResult<Result<(), String>> {
+    let opponent = if game.player_1 == evidence.winner {
+        match &game.player_2 {
+            Some(player_2) => player_2.clone(),
+            None => return Ok(Err("A forfeit needs an opponent".to_string())),
+        }
+    } else {
+        game.player_1.clone()
+    };
+    let record = must_get_valid_record(evidence.opponent_last_action.clone())?;
+    if record.action().author() != &opponent {
+        return Ok(Err("Forfeit opponent_last_action must be an action of the opponent".to_string()));
+    }
+    if record.action().timestamp() != evidence.opponent_last_seen {
+        return Ok(Err("Forfeit opponent_last_seen is not the time of opponent_last_action".to_string()));
+    }
 
-    // --- Status Transitions Check ---
-    match (&original_game.game_status, &updated_game.game_status) {
-        (GameStatus::Waiting, GameStatus::InProgress) => {
-             if updated_game.player_2.is_none() { return Ok(ValidateCallbackResult::Invalid("Cannot transition to InProgress without Player 2 being set".into())); }
-             // Check author IS the new player 2 (already covered by refined author check)
-             // if updated_game.player_2.as_ref() != Some(author) { return Ok(ValidateCallbackResult::Invalid("Join must be performed by Player 2".into())); }
+    let is_activity = match record.action() {
+        Action::CreateLink(create_link) => {
+            matches!(LinkTypes::from_type(create_link.zome_index, create_link.link_type)?, Some(LinkTypes::Presence))
+        }
+        _ => match record.entry().to_app_option::<GameCheckpoint>() {
+            Ok(Some(checkpoint)) => checkpoint.game_id == *game_id,
+            _ => {
+                record.entry().to_app_option::<Game>().ok().flatten().is_some()
+                    && original_action_of(&evidence.opponent_last_action)? == *game_id
+            }
         },
-        (GameStatus::InProgress, GameStatus::Finished) => { /* Allow */ },
-        (GameStatus::Finished, GameStatus::Finished) => { /* Allow */ },
-        // Disallow other transitions explicitly for clarity
-        (GameStatus::Waiting, GameStatus::Waiting) => return Ok(ValidateCallbackResult::Invalid("No valid updates allowed for 'Waiting' game status".into())),
-        (GameStatus::InProgress, GameStatus::InProgress) => return Ok(ValidateCallbackResult::Invalid("No valid updates allowed for 'InProgress' game status".into())),
-        (from, to) => { return Ok(ValidateCallbackResult::Invalid(format!( "Invalid game status transition from {:?} to {:?}", from, to ))); }
+    };
+    if !is_activity {
+        return Ok(Err(
+            "Forfeit opponent_last_action must be the opponent's checkpoint or presence, or a revision of the game".to_string(),
+        ));
+    }
+    let filter = ChainFilter::new(evidence.opponent_last_action.clone()).take(1);
+    let on_chain = must_get_agent_activity(opponent.clone(), filter)?
+        .first()
+        .is_some_and(|item| item.action.hashed.hash == evidence.opponent_last_action);
+    if !on_chain {
+        return Ok(Err("Forfeit opponent_last_action is not on the opponent's chain".to_string()));
     }
 
-    Ok(ValidateCallbackResult::Valid)
+    // Walk the revisions being replaced back to the create action.
+    let mut hash = previous_revision.clone();
+    loop {
+        let revision = must_get_valid_record(hash)?;
+        if revision.action().author() == &opponent && revision.action().timestamp() > evidence.opponent_last_seen {
+            return Ok(Err("The opponent updated the game after their cited opponent_last_action".to_string()));
+        }
+        match revision.action() {
+            Action::Update(update) => hash = update.original_action_address.clone(),
+            _ => break,
+        }
+    }
+    Ok(Ok(()))
 }
 
 // Validate deleting a Game entry.
-// Signature matches call from lib.rs where original_game is deserialized first
+// Called from lib.rs with the original game resolved via `must_get_valid_record`
 pub fn validate_delete_game(
     action: &SignedActionHashed, // Action performing the delete
     original_game: Game,         // The game state being deleted
 ) -> ExternResult<ValidateCallbackResult> {
-
-    // 1. Check Author: Only players involved can delete the game.
-    let author = action.action().author();
-     if original_game.player_1 != *author && original_game.player_2.as_ref() != Some(author) {
-        return Ok(ValidateCallbackResult::Invalid(
-            "Only game participants can delete the game".to_string(),
-        ));
+    match game_lifecycle::check_delete(&original_game, action.action().author()) {
+        Ok(()) => Ok(ValidateCallbackResult::Valid),
+        Err(reason) => Ok(ValidateCallbackResult::Invalid(reason)),
     }
+}
 
-    // 2. Check Status: Only allow deleting 'Waiting' games
-    if original_game.game_status != GameStatus::Waiting {
-        return Ok(ValidateCallbackResult::Invalid(
-            "Only games in 'Waiting' status can be deleted".to_string(),
-        ));
+// Follows an update chain back to the create action.
+pub fn original_action_of(action_hash: &ActionHash) -> ExternResult<ActionHash> {
+    let mut hash = action_hash.clone();
+    while let Action::Update(update) = must_get_valid_record(hash.clone())?.action() {
+        hash = update.original_action_address.clone();
     }
-
-    Ok(ValidateCallbackResult::Valid)
+    Ok(hash)
 }
 
-
-// FIX: Remove helper function that uses `get`
-// fn must_get_valid_record(action_hash: ActionHash) -> ExternResult<Record> { ... }
\ No newline at end of file
+// The Game as of `revision`, which must be the create action of `game_id` or one of
+// its updates. Player 2 of a game joined from the lobby is only named in a later
+// revision, and the lifecycle never changes Player 2 once set.
+pub fn game_at_revision(game_id: &ActionHash, revision: &ActionHash) -> ExternResult<Option<Game>> {
+    if original_action_of(revision)? != *game_id {
+        return Ok(None);
+    }
+    let record = must_get_valid_record(revision.clone())?;
+    Ok(record.entry().to_app_option::<Game>().ok().flatten())
+}
@@ -11,6 +11,8 @@ getrandom::register_custom_getrandom!(custom_getrandom);
 // Import entry definitions
 pub mod game;
 pub use game::Game;
+pub mod game_lifecycle;
+pub mod game_rules;
 pub mod player;
 pub use player::Player;
 pub mod score;
@@ -22,7 +24,27 @@ pub use presence::Presence;
 pub mod anchor_path;
 pub use anchor_path::AnchorPath;
 pub mod chat;
-pub use chat::ChatMessage;
+pub use chat::{ChatChannel, ChatMessage};
+pub mod match_result;
+pub use match_result::MatchResult;
+pub mod replay;
+pub use replay::{ReplayBuffer, ReplayChunk};
+pub mod tournament;
+pub use tournament::Tournament;
+pub mod seen_signal;
+pub use seen_signal::SeenSignal;
+pub mod outbox;
+pub use outbox::{InboxMessage, OutboxMessage};
+pub mod checkpoint;
+pub use checkpoint::GameCheckpoint;
+pub mod properties;
+pub mod series;
+pub use series::Series;
+pub mod moderation;
+pub use moderation::{ChatFilters, Report};
+pub mod rating;
+pub use rating::Rating;
+pub mod time_index;
 
 // Import validation functions for entries
 pub mod game_validation;
@@ -30,6 +52,14 @@ pub mod player_validation;
 pub mod score_validation;
 pub mod statistics_validation;
 pub mod presence_validation;
+pub mod match_result_validation;
+pub mod replay_validation;
+pub mod tournament_validation;
+pub mod checkpoint_validation;
+pub mod series_validation;
+pub mod chat_validation;
+pub mod moderation_validation;
+pub mod rating_validation;
 
 // Define EntryTypes enum with Serde derives
 #[hdk_entry_types]
@@ -50,6 +80,30 @@ pub enum EntryTypes {
     AnchorPath(AnchorPath),
     #[entry_type(visibility = "public")]
     ChatMessage(ChatMessage),
+    #[entry_type(visibility = "public")]
+    MatchResult(MatchResult),
+    #[entry_type(visibility = "private")]
+    ReplayBuffer(ReplayBuffer),
+    #[entry_type(visibility = "public")]
+    ReplayChunk(ReplayChunk),
+    #[entry_type(visibility = "public")]
+    Tournament(Tournament),
+    #[entry_type(visibility = "private")]
+    SeenSignal(SeenSignal),
+    #[entry_type(visibility = "private")]
+    OutboxMessage(OutboxMessage),
+    #[entry_type(visibility = "private")]
+    InboxMessage(InboxMessage),
+    #[entry_type(visibility = "public")]
+    GameCheckpoint(GameCheckpoint),
+    #[entry_type(visibility = "public")]
+    Series(Series),
+    #[entry_type(visibility = "private")]
+    ChatFilters(ChatFilters),
+    #[entry_type(visibility = "public")]
+    Report(Report),
+    #[entry_type(visibility = "public")]
+    Rating(Rating),
 }
 
 // Define LinkTypes enum with Serde derives
@@ -68,7 +122,31 @@ pub enum LinkTypes {
     PlayerToScores,
     Presence,
     AllPlayersAnchorToAgentPubKey, // For linking the "all_players" anchor to each player's AgentPubKey
-    AllChatMessagesAnchorToMessage,
+    AllChatMessagesAnchorToMessage, // Global chat before channels existed; read-only history
+    GameToMatchResults,
+    MatchmakingQueue, // "matchmaking_queue" anchor -> queued AgentPubKey
+    GameTimePath,     // Path tree links for the time-bucketed game index (root.year.month.day.hour)
+    TimeBucketToGame, // Hour bucket -> original Game ActionHash
+    GameToReplayChunks,
+    GameToSpectators, // Original Game ActionHash -> spectating AgentPubKey
+    AllTournaments,           // "all_tournaments" anchor -> original Tournament ActionHash
+    TournamentUpdates,        // Original Tournament ActionHash -> updated Tournament ActionHash
+    TournamentToParticipants, // Original Tournament ActionHash -> registered AgentPubKey
+    TournamentToGames,        // Original Tournament ActionHash -> Game ActionHash (tag = TournamentGameTag)
+    GameToCheckpoints,        // Original Game ActionHash -> GameCheckpoint ActionHash
+    PlayerToSeries,           // Player AgentPubKey -> original Series ActionHash
+    SeriesUpdates,            // Original Series ActionHash -> updated Series ActionHash
+    SeriesToGames,            // Original Series ActionHash -> Game ActionHash
+    GameToSeries,             // Game ActionHash -> original Series ActionHash
+    AgentToDirectChats,       // AgentPubKey -> the other AgentPubKey of a direct chat
+    ChatTimePath,             // Path tree links for each channel's chat history (root.year.month.day.hour)
+    TimeBucketToChatMessage,  // Hour bucket -> ChatMessage ActionHash
+    ChatReports,              // "chat_reports" anchor -> Report ActionHash
+    HiddenChatMessages,       // "hidden_chat_messages" anchor -> ChatMessage ActionHash hidden by a moderator
+    Reaction,                 // Reacting AgentPubKey -> ChatMessage ActionHash (tag = emoji)
+    ChatMessageToReactions,   // ChatMessage ActionHash -> reacting AgentPubKey (tag = emoji)
+    PlayerToRatings,          // Player AgentPubKey -> Rating ActionHash, one per rated game
+    MatchmakingClaims,        // Queue ticket (MatchmakingQueue CreateLink hash) -> claiming AgentPubKey
 }
 
 
@@ -83,24 +161,31 @@ pub fn validate(op: Op) -> ExternResult<ValidateCallbackResult> {
             EntryTypes::Statistics(statistics) => statistics_validation::validate_create_statistics(&action, statistics),
             EntryTypes::Presence(presence) => presence_validation::validate_create_presence(&action, presence),
             EntryTypes::AnchorPath(_) => Ok(ValidateCallbackResult::Valid),
-            EntryTypes::ChatMessage(msg) => {
-                if msg.content.trim().is_empty() {
-                    return Ok(ValidateCallbackResult::Invalid("Chat message content cannot be empty".into()));
-                }
-                Ok(ValidateCallbackResult::Valid)
-            }
+            EntryTypes::ChatMessage(msg) => chat_validation::validate_create_chat_message(&action, msg),
+            EntryTypes::MatchResult(result) => match_result_validation::validate_create_match_result(&action, result, op_entry(&op)),
+            EntryTypes::ReplayBuffer(_) => Ok(ValidateCallbackResult::Valid),
+            EntryTypes::ReplayChunk(chunk) => replay_validation::validate_create_replay_chunk(&action, chunk),
+            EntryTypes::Tournament(tournament) => tournament_validation::validate_create_tournament(&action, tournament),
+            EntryTypes::SeenSignal(_) => Ok(ValidateCallbackResult::Valid),
+            EntryTypes::OutboxMessage(_) => Ok(ValidateCallbackResult::Valid),
+            EntryTypes::InboxMessage(_) => Ok(ValidateCallbackResult::Valid),
+            EntryTypes::GameCheckpoint(checkpoint) => checkpoint_validation::validate_create_game_checkpoint(&action, checkpoint),
+            EntryTypes::Series(series) => series_validation::validate_create_series(&action, series),
+            EntryTypes::ChatFilters(_) => Ok(ValidateCallbackResult::Valid),
+            EntryTypes::Report(report) => moderation_validation::validate_create_report(&action, report),
+            EntryTypes::Rating(rating) => rating_validation::validate_create_rating(&action, rating),
         },
         FlatOp::Link(OpLink::CreateLink { link_type, action }) => match link_type {
             LinkTypes::GameIdToGame => validate_gameid_to_game_link(&action),
             LinkTypes::Player1ToGames => validate_player1_to_game_link(&action),
             LinkTypes::Player2ToGames => validate_player2_to_game_link(&action),
             LinkTypes::GameUpdates => validate_game_updates_link(&action),
-            LinkTypes::GameToScores => validate_game_to_score_link(&action),
+            LinkTypes::GameToScores => score_validation::validate_score_link(&action, "GameToScores"),
             LinkTypes::GameToStatistics => validate_game_to_statistics_link(&action),
             LinkTypes::PlayerToPlayers => validate_player_to_players_link(&action),
             LinkTypes::PlayerNameToPlayer => validate_playername_to_player_link(&action),
             LinkTypes::PlayerUpdates => validate_player_updates_link(&action),
-            LinkTypes::PlayerToScores => validate_player_to_scores_link(&action),
+            LinkTypes::PlayerToScores => score_validation::validate_score_link(&action, "PlayerToScores"),
             LinkTypes::Presence => validate_presence_link(&action),
             LinkTypes::AllPlayersAnchorToAgentPubKey => {
                 if action.base_address.clone().into_entry_hash().is_none() {
@@ -120,11 +205,310 @@ pub fn validate(op: Op) -> ExternResult<ValidateCallbackResult> {
                 }
                 Ok(ValidateCallbackResult::Valid)
             }
+            LinkTypes::GameToMatchResults => validate_game_to_match_result_link(&action),
+            LinkTypes::MatchmakingQueue => validate_matchmaking_queue_link(&action),
+            LinkTypes::GameTimePath => validate_game_time_path_link(&action),
+            LinkTypes::TimeBucketToGame => validate_time_bucket_to_game_link(&action),
+            LinkTypes::GameToReplayChunks => validate_game_to_replay_chunk_link(&action),
+            LinkTypes::GameToSpectators => validate_game_to_spectator_link(&action),
+            LinkTypes::AllTournaments => validate_all_tournaments_link(&action),
+            LinkTypes::TournamentUpdates => validate_tournament_updates_link(&action),
+            LinkTypes::TournamentToParticipants => validate_tournament_to_participant_link(&action),
+            LinkTypes::TournamentToGames => validate_tournament_to_game_link(&action),
+            LinkTypes::GameToCheckpoints => validate_game_to_checkpoint_link(&action),
+            LinkTypes::PlayerToSeries => validate_player_to_series_link(&action),
+            LinkTypes::SeriesUpdates => validate_series_updates_link(&action),
+            LinkTypes::SeriesToGames => validate_series_game_link(&action, false),
+            LinkTypes::GameToSeries => validate_series_game_link(&action, true),
+            LinkTypes::AgentToDirectChats => chat_validation::validate_agent_to_direct_chat_link(&action),
+            LinkTypes::ChatTimePath => chat_validation::validate_chat_time_path_link(&action),
+            LinkTypes::TimeBucketToChatMessage => chat_validation::validate_time_bucket_to_chat_message_link(&action),
+            LinkTypes::ChatReports => moderation_validation::validate_chat_reports_link(&action),
+            LinkTypes::HiddenChatMessages => moderation_validation::validate_hidden_chat_messages_link(&action),
+            LinkTypes::Reaction => chat_validation::validate_reaction_link(&action),
+            LinkTypes::ChatMessageToReactions => chat_validation::validate_chat_message_to_reactions_link(&action),
+            LinkTypes::PlayerToRatings => rating_validation::validate_player_to_rating_link(&action),
+            LinkTypes::MatchmakingClaims => validate_matchmaking_claim_link(&action),
         },
+        // Updates, deletes and link deletes are validated against the raw op below,
+        // resolving the original record with `must_get_valid_record`.
+        _ => match &op {
+            Op::StoreRecord(StoreRecord { record }) => validate_record(record),
+            Op::RegisterUpdate(RegisterUpdate { update, new_entry }) => {
+                let signed_action = SignedActionHashed::raw_from_same_hash(update.clone());
+                validate_update(&signed_action, &update.hashed.content, new_entry.as_ref())
+            }
+            Op::RegisterDelete(RegisterDelete { delete }) => {
+                let signed_action = SignedActionHashed::raw_from_same_hash(delete.clone());
+                validate_delete(&signed_action, &delete.hashed.content)
+            }
+            Op::RegisterDeleteLink(RegisterDeleteLink { delete_link, create_link }) => {
+                let signed_action = SignedActionHashed::raw_from_same_hash(delete_link.clone());
+                validate_delete_link(&signed_action, create_link)
+            }
+            _ => Ok(ValidateCallbackResult::Valid),
+        },
+    }
+}
+
+// --- Update / Delete Validation ---
+
+// StoreRecord ops carry the full record; only mutations need checking here since
+// creates are already covered by the StoreEntry / CreateLink ops above.
+fn validate_record(record: &Record) -> ExternResult<ValidateCallbackResult> {
+    match record.action() {
+        Action::Update(update) => validate_update(&record.signed_action, update, record.entry().as_option()),
+        Action::Delete(delete) => validate_delete(&record.signed_action, delete),
+        Action::DeleteLink(delete_link) => {
+            let create_link = get_original_create_link(&delete_link.link_add_address)?;
+            validate_delete_link(&record.signed_action, &create_link)
+        }
         _ => Ok(ValidateCallbackResult::Valid),
     }
 }
 
+// Raw entry carried by a create op, needed to inspect countersigning session data.
+fn op_entry(op: &Op) -> Option<&Entry> {
+    match op {
+        Op::StoreEntry(StoreEntry { entry, .. }) => Some(entry),
+        Op::StoreRecord(StoreRecord { record }) => record.entry().as_option(),
+        _ => None,
+    }
+}
+
+// Deserialize an app entry of this zome from its action's entry type.
+fn app_entry_from_action(action: &Action, entry: &Entry) -> ExternResult<Option<EntryTypes>> {
+    match action.entry_type() {
+        Some(EntryType::App(AppEntryDef { zome_index, entry_index, .. })) => {
+            EntryTypes::deserialize_from_type(*zome_index, *entry_index, entry)
+        }
+        _ => Ok(None),
+    }
+}
+
+// Resolve the app entry of a previously validated record (the target of an update or delete).
+fn get_original_app_entry(action_hash: &ActionHash) -> ExternResult<Option<EntryTypes>> {
+    let record = must_get_valid_record(action_hash.clone())?;
+    let Some(entry) = record.entry().as_option() else {
+        return Ok(None);
+    };
+    app_entry_from_action(record.action(), entry)
+}
+
+// How far back a re-indexing DeleteLink looks for the join update in the same call.
+const JOIN_LOOKBACK: u32 = 10;
+
+// True if the author committed, shortly before this action, the update that made
+// them Player 2 of the game.
+fn joined_game_just_before(action: &SignedActionHashed, game_hash: &ActionHash) -> ExternResult<bool> {
+    let Some(prev_action) = action.action().prev_action() else {
+        return Ok(false);
+    };
+    let author = action.action().author();
+    let filter = ChainFilter::new(prev_action.clone()).take(JOIN_LOOKBACK);
+    for item in must_get_agent_activity(author.clone(), filter)? {
+        let Action::Update(update) = item.action.action() else { continue };
+        let Ok(game) = Game::try_from(must_get_entry(update.entry_hash.clone())?.content) else { continue };
+        if game.player_2.as_ref() == Some(author) && game_validation::original_action_of(&update.original_action_address)? == *game_hash {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+fn get_original_create_link(create_link_hash: &ActionHash) -> ExternResult<CreateLink> {
+    let record = must_get_valid_record(create_link_hash.clone())?;
+    match record.action() {
+        Action::CreateLink(create_link) => Ok(create_link.clone()),
+        _ => Err(wasm_error!(WasmErrorInner::Guest("DeleteLink must reference a CreateLink action".into()))),
+    }
+}
+
+fn validate_update(
+    action: &SignedActionHashed,
+    update: &Update,
+    new_entry: Option<&Entry>,
+) -> ExternResult<ValidateCallbackResult> {
+    let Some(entry) = new_entry else {
+        // Private entries are not shared; nothing to check against.
+        return Ok(ValidateCallbackResult::Valid);
+    };
+    let Some(updated) = app_entry_from_action(action.action(), entry)? else {
+        return Ok(ValidateCallbackResult::Valid);
+    };
+    let Some(original) = get_original_app_entry(&update.original_action_address)? else {
+        return Ok(ValidateCallbackResult::Invalid("Original entry for update could not be resolved".into()));
+    };
+
+    match (updated, original) {
+        (EntryTypes::Game(updated_game), EntryTypes::Game(original_game)) => {
+            game_validation::validate_update_game(action, updated_game, &original_game)
+        }
+        (EntryTypes::Player(updated_player), EntryTypes::Player(original_player)) => {
+            player_validation::validate_update_player(action, updated_player, &original_player)
+        }
+        (EntryTypes::Tournament(updated_tournament), EntryTypes::Tournament(original_tournament)) => {
+            tournament_validation::validate_update_tournament(action, updated_tournament, &original_tournament)
+        }
+        (EntryTypes::OutboxMessage(updated_message), EntryTypes::OutboxMessage(original_message)) => {
+            // Only the retry bookkeeping may change.
+            if updated_message.recipient != original_message.recipient
+                || updated_message.game_id != original_message.game_id
+                || updated_message.seq != original_message.seq
+                || updated_message.payload != original_message.payload
+                || updated_message.created_at != original_message.created_at
+            {
+                return Ok(ValidateCallbackResult::Invalid("Only retry state of an outbox message can be updated".into()));
+            }
+            Ok(ValidateCallbackResult::Valid)
+        }
+        (EntryTypes::Series(updated_series), EntryTypes::Series(original_series)) => {
+            series_validation::validate_update_series(action, updated_series, &original_series)
+        }
+        (EntryTypes::ChatFilters(_), EntryTypes::ChatFilters(_)) => Ok(ValidateCallbackResult::Valid),
+        (EntryTypes::ChatMessage(updated_message), EntryTypes::ChatMessage(original_message)) => {
+            chat_validation::validate_update_chat_message(action, update, updated_message, &original_message)
+        }
+        (EntryTypes::Game(_), _) | (EntryTypes::Player(_), _) | (EntryTypes::Tournament(_), _) | (EntryTypes::Series(_), _) | (EntryTypes::OutboxMessage(_), _) | (EntryTypes::ChatFilters(_), _) | (EntryTypes::ChatMessage(_), _) => Ok(ValidateCallbackResult::Invalid(
+            "Entry type cannot change on update".into(),
+        )),
+        (EntryTypes::Score(_), _) => Ok(ValidateCallbackResult::Invalid("Score entries cannot be updated".into())),
+        (EntryTypes::Statistics(_), _) => Ok(ValidateCallbackResult::Invalid("Statistics entries cannot be updated".into())),
+        (EntryTypes::Presence(_), _) => Ok(ValidateCallbackResult::Invalid("Presence entries cannot be updated".into())),
+        (EntryTypes::AnchorPath(_), _) => Ok(ValidateCallbackResult::Invalid("Anchor entries cannot be updated".into())),
+        (EntryTypes::MatchResult(_), _) => Ok(ValidateCallbackResult::Invalid("Match results cannot be updated".into())),
+        (EntryTypes::ReplayBuffer(_), _) => Ok(ValidateCallbackResult::Invalid("Replay buffers cannot be updated".into())),
+        (EntryTypes::ReplayChunk(_), _) => Ok(ValidateCallbackResult::Invalid("Replay chunks cannot be updated".into())),
+        (EntryTypes::SeenSignal(_), _) => Ok(ValidateCallbackResult::Invalid("Seen signals cannot be updated".into())),
+        (EntryTypes::InboxMessage(_), _) => Ok(ValidateCallbackResult::Invalid("Inbox messages cannot be updated".into())),
+        (EntryTypes::GameCheckpoint(_), _) => Ok(ValidateCallbackResult::Invalid("Game checkpoints cannot be updated".into())),
+        (EntryTypes::Report(_), _) => Ok(ValidateCallbackResult::Invalid("Reports cannot be updated".into())),
+        (EntryTypes::Rating(_), _) => Ok(ValidateCallbackResult::Invalid("Ratings cannot be updated".into())),
+    }
+}
+
+fn validate_delete(action: &SignedActionHashed, delete: &Delete) -> ExternResult<ValidateCallbackResult> {
+    let Some(original) = get_original_app_entry(&delete.deletes_address)? else {
+        return Ok(ValidateCallbackResult::Valid);
+    };
+
+    match original {
+        EntryTypes::Game(game) => game_validation::validate_delete_game(action, game),
+        EntryTypes::Player(player) => player_validation::validate_delete_player(action, player),
+        EntryTypes::Score(_) => Ok(ValidateCallbackResult::Invalid("Score entries cannot be deleted".into())),
+        EntryTypes::Statistics(_) => Ok(ValidateCallbackResult::Invalid("Statistics entries cannot be deleted".into())),
+        EntryTypes::Presence(presence) => {
+            if presence.agent_pubkey != *action.action().author() {
+                return Ok(ValidateCallbackResult::Invalid("Presence can only be deleted by its agent".into()));
+            }
+            Ok(ValidateCallbackResult::Valid)
+        }
+        EntryTypes::AnchorPath(_) => Ok(ValidateCallbackResult::Invalid("Anchor entries cannot be deleted".into())),
+        EntryTypes::ChatMessage(message) => chat_validation::validate_delete_chat_message(action, message),
+        EntryTypes::MatchResult(_) => Ok(ValidateCallbackResult::Invalid("Match results cannot be deleted".into())),
+        EntryTypes::ReplayBuffer(_) => Ok(ValidateCallbackResult::Valid),
+        EntryTypes::ReplayChunk(_) => Ok(ValidateCallbackResult::Invalid("Replay chunks cannot be deleted".into())),
+        EntryTypes::SeenSignal(_) => Ok(ValidateCallbackResult::Valid),
+        EntryTypes::OutboxMessage(_) | EntryTypes::InboxMessage(_) => Ok(ValidateCallbackResult::Valid),
+        EntryTypes::GameCheckpoint(_) => Ok(ValidateCallbackResult::Invalid("Game checkpoints cannot be deleted".into())),
+        EntryTypes::Tournament(_) => Ok(ValidateCallbackResult::Invalid("Tournaments cannot be deleted, cancel them instead".into())),
+        EntryTypes::Series(_) => Ok(ValidateCallbackResult::Invalid("Series cannot be deleted, cancel them instead".into())),
+        EntryTypes::ChatFilters(_) => Ok(ValidateCallbackResult::Valid),
+        EntryTypes::Report(_) => Ok(ValidateCallbackResult::Invalid("Reports cannot be deleted".into())),
+        EntryTypes::Rating(_) => Ok(ValidateCallbackResult::Invalid("Ratings cannot be deleted".into())),
+    }
+}
+
+fn validate_delete_link(
+    action: &SignedActionHashed,
+    create_link: &CreateLink,
+) -> ExternResult<ValidateCallbackResult> {
+    let Some(link_type) = LinkTypes::from_type(create_link.zome_index, create_link.link_type)? else {
+        return Ok(ValidateCallbackResult::Valid);
+    };
+    let author = action.action().author();
+    let is_link_author = create_link.author == *author;
+
+    match link_type {
+        // Game index links may be cleaned up by either participant (see `delete_game`).
+        LinkTypes::GameIdToGame
+        | LinkTypes::TimeBucketToGame
+        | LinkTypes::Player1ToGames
+        | LinkTypes::Player2ToGames => {
+            if is_link_author {
+                return Ok(ValidateCallbackResult::Valid);
+            }
+            let Some(game_hash) = create_link.target_address.clone().into_action_hash() else {
+                return Ok(ValidateCallbackResult::Invalid(format!("{:?} target must be an ActionHash", link_type)));
+            };
+            let is_participant = match get_original_app_entry(&game_hash)? {
+                Some(EntryTypes::Game(game)) => {
+                    game.player_1 == *author
+                        || game.player_2.as_ref() == Some(author)
+                        // Whoever joins an open game re-indexes it right after joining.
+                        || (game.player_2.is_none() && joined_game_just_before(action, &game_hash)?)
+                }
+                _ => false,
+            };
+            match is_participant {
+                true => Ok(ValidateCallbackResult::Valid),
+                false => Ok(ValidateCallbackResult::Invalid(format!(
+                    "{:?} link can only be deleted by its author or a game participant", link_type
+                ))),
+            }
+        }
+        // History, score and statistics links are permanent records.
+        LinkTypes::GameUpdates
+        | LinkTypes::PlayerUpdates
+        | LinkTypes::GameToScores
+        | LinkTypes::PlayerToScores
+        | LinkTypes::GameToStatistics
+        | LinkTypes::GameToMatchResults
+        | LinkTypes::GameTimePath
+        | LinkTypes::GameToReplayChunks
+        | LinkTypes::AllTournaments
+        | LinkTypes::TournamentUpdates
+        | LinkTypes::TournamentToGames
+        | LinkTypes::GameToCheckpoints
+        | LinkTypes::PlayerToSeries
+        | LinkTypes::SeriesUpdates
+        | LinkTypes::SeriesToGames
+        | LinkTypes::GameToSeries
+        | LinkTypes::ChatTimePath
+        | LinkTypes::TimeBucketToChatMessage
+        | LinkTypes::ChatReports
+        | LinkTypes::PlayerToRatings
+        | LinkTypes::AllChatMessagesAnchorToMessage => Ok(ValidateCallbackResult::Invalid(format!(
+            "{:?} links cannot be deleted", link_type
+        ))),
+        // Any moderator may unhide a message.
+        LinkTypes::HiddenChatMessages => {
+            if !properties::dna_properties()?.is_moderator(author) {
+                return Ok(ValidateCallbackResult::Invalid("Only moderators can unhide chat messages".into()));
+            }
+            Ok(ValidateCallbackResult::Valid)
+        }
+        LinkTypes::PlayerToPlayers
+        | LinkTypes::PlayerNameToPlayer
+        | LinkTypes::Presence
+        | LinkTypes::MatchmakingQueue
+        | LinkTypes::MatchmakingClaims
+        | LinkTypes::GameToSpectators
+        | LinkTypes::TournamentToParticipants
+        | LinkTypes::AgentToDirectChats
+        | LinkTypes::Reaction
+        | LinkTypes::ChatMessageToReactions
+        | LinkTypes::AllPlayersAnchorToAgentPubKey => {
+            if !is_link_author {
+                return Ok(ValidateCallbackResult::Invalid(format!(
+                    "{:?} link can only be deleted by its author", link_type
+                )));
+            }
+            Ok(ValidateCallbackResult::Valid)
+        }
+    }
+}
+
 // --- Simplified Link Validations (No `get` calls inside) ---
 
 fn validate_gameid_to_game_link(action: &TypedAction<CreateLinkData>) -> ExternResult<ValidateCallbackResult> {
@@ -135,6 +519,33 @@ fn validate_gameid_to_game_link(action: &TypedAction<CreateLinkData>) -> ExternR
     Ok(ValidateCallbackResult::Valid)
 }
 
+fn validate_game_time_path_link(action: &TypedAction<CreateLinkData>) -> ExternResult<ValidateCallbackResult> {
+    if action.base_address.clone().into_entry_hash().is_none() {
+        return Ok(ValidateCallbackResult::Invalid("GameTimePath base must be an EntryHash (path)".into()));
+    }
+    if action.target_address.clone().into_entry_hash().is_none() {
+        return Ok(ValidateCallbackResult::Invalid("GameTimePath target must be an EntryHash (path)".into()));
+    }
+    Ok(ValidateCallbackResult::Valid)
+}
+
+fn validate_time_bucket_to_game_link(action: &TypedAction<CreateLinkData>) -> ExternResult<ValidateCallbackResult> {
+    if action.base_address.clone().into_entry_hash().is_none() {
+        return Ok(ValidateCallbackResult::Invalid("TimeBucketToGame base must be an EntryHash (path)".into()));
+    }
+    let Some(game_hash) = action.target_address.clone().into_action_hash() else {
+        return Ok(ValidateCallbackResult::Invalid("TimeBucketToGame target must be an ActionHash".into()));
+    };
+    // Games are bucketed and paged by their created_at, carried in the tag.
+    let Some(EntryTypes::Game(game)) = get_original_app_entry(&game_hash)? else {
+        return Ok(ValidateCallbackResult::Invalid("TimeBucketToGame target is not a Game".into()));
+    };
+    if time_index::tag_time(&action.tag) != Some(game.created_at) {
+        return Ok(ValidateCallbackResult::Invalid("TimeBucketToGame tag must be the game's created_at".into()));
+    }
+    Ok(ValidateCallbackResult::Valid)
+}
+
 fn validate_player1_to_game_link(action: &TypedAction<CreateLinkData>) -> ExternResult<ValidateCallbackResult> {
     let base_agent = action.base_address.clone().into_agent_pub_key()
          .ok_or(wasm_error!(WasmErrorInner::Guest("Player1ToGames base must be an AgentPubKey".into())))?;
@@ -156,36 +567,311 @@ fn validate_player2_to_game_link(action: &TypedAction<CreateLinkData>) -> Extern
     Ok(ValidateCallbackResult::Valid)
 }
 
+// GameUpdates: get_latest_game trusts the newest link, so the target must be an
+// update of the base game, linked by one of the players named in it.
 fn validate_game_updates_link(action: &TypedAction<CreateLinkData>) -> ExternResult<ValidateCallbackResult> {
-     if action.base_address.clone().into_action_hash().is_none() {
+    let Some(game_hash) = action.base_address.clone().into_action_hash() else {
         return Ok(ValidateCallbackResult::Invalid("GameUpdates base must be an ActionHash".into()));
+    };
+    let Some(revision_hash) = action.target_address.clone().into_action_hash() else {
+        return Ok(ValidateCallbackResult::Invalid("GameUpdates target must be an ActionHash".into()));
+    };
+    let record = must_get_valid_record(revision_hash.clone())?;
+    if !matches!(record.action(), Action::Update(_)) || game_validation::original_action_of(&revision_hash)? != game_hash {
+        return Ok(ValidateCallbackResult::Invalid("GameUpdates target must be an update of the base game".into()));
+    }
+    let Ok(Some(game)) = record.entry().to_app_option::<Game>() else {
+        return Ok(ValidateCallbackResult::Invalid("GameUpdates target is not a Game".into()));
+    };
+    if game.player_1 != *action.author() && game.player_2.as_ref() != Some(action.author()) {
+        return Ok(ValidateCallbackResult::Invalid("Only a player of the game can link its updates".into()));
+    }
+    Ok(ValidateCallbackResult::Valid)
+}
+
+fn validate_game_to_statistics_link(action: &TypedAction<CreateLinkData>) -> ExternResult<ValidateCallbackResult> {
+      if action.base_address.clone().into_action_hash().is_none() {
+         return Ok(ValidateCallbackResult::Invalid("GameToStatistics base must be a Game ActionHash".into()));
      }
      if action.target_address.clone().into_action_hash().is_none() {
-        return Ok(ValidateCallbackResult::Invalid("GameUpdates target must be an ActionHash".into()));
+         return Ok(ValidateCallbackResult::Invalid("GameToStatistics target must be a Statistics ActionHash".into()));
      }
     Ok(ValidateCallbackResult::Valid)
 }
 
-fn validate_game_to_score_link(action: &TypedAction<CreateLinkData>) -> ExternResult<ValidateCallbackResult> {
+fn validate_game_to_match_result_link(action: &TypedAction<CreateLinkData>) -> ExternResult<ValidateCallbackResult> {
      if action.base_address.clone().into_action_hash().is_none() {
-         return Ok(ValidateCallbackResult::Invalid("GameToScores base must be a Game ActionHash".into()));
+         return Ok(ValidateCallbackResult::Invalid("GameToMatchResults base must be a Game ActionHash".into()));
      }
      if action.target_address.clone().into_action_hash().is_none() {
-         return Ok(ValidateCallbackResult::Invalid("GameToScores target must be a Score ActionHash".into()));
+         return Ok(ValidateCallbackResult::Invalid("GameToMatchResults target must be a MatchResult ActionHash".into()));
      }
     Ok(ValidateCallbackResult::Valid)
 }
 
-fn validate_game_to_statistics_link(action: &TypedAction<CreateLinkData>) -> ExternResult<ValidateCallbackResult> {
-      if action.base_address.clone().into_action_hash().is_none() {
-         return Ok(ValidateCallbackResult::Invalid("GameToStatistics base must be a Game ActionHash".into()));
+fn validate_game_to_replay_chunk_link(action: &TypedAction<CreateLinkData>) -> ExternResult<ValidateCallbackResult> {
+     if action.base_address.clone().into_action_hash().is_none() {
+         return Ok(ValidateCallbackResult::Invalid("GameToReplayChunks base must be a Game ActionHash".into()));
      }
      if action.target_address.clone().into_action_hash().is_none() {
-         return Ok(ValidateCallbackResult::Invalid("GameToStatistics target must be a Statistics ActionHash".into()));
+         return Ok(ValidateCallbackResult::Invalid("GameToReplayChunks target must be a ReplayChunk ActionHash".into()));
      }
     Ok(ValidateCallbackResult::Valid)
 }
 
+fn validate_game_to_checkpoint_link(action: &TypedAction<CreateLinkData>) -> ExternResult<ValidateCallbackResult> {
+    let Some(game_hash) = action.base_address.clone().into_action_hash() else {
+        return Ok(ValidateCallbackResult::Invalid("GameToCheckpoints base must be a Game ActionHash".into()));
+    };
+    let Some(checkpoint_hash) = action.target_address.clone().into_action_hash() else {
+        return Ok(ValidateCallbackResult::Invalid("GameToCheckpoints target must be a GameCheckpoint ActionHash".into()));
+    };
+    // The checkpoint must belong to the linked game and be linked by its author.
+    let checkpoint_record = must_get_valid_record(checkpoint_hash)?;
+    let Ok(Some(checkpoint)) = checkpoint_record.entry().to_app_option::<GameCheckpoint>() else {
+        return Ok(ValidateCallbackResult::Invalid("GameToCheckpoints target is not a GameCheckpoint".into()));
+    };
+    if checkpoint.game_id != game_hash || checkpoint.player != *action.author() {
+        return Ok(ValidateCallbackResult::Invalid("Checkpoint link must be created by its author from its own game".into()));
+    }
+    Ok(ValidateCallbackResult::Valid)
+}
+
+fn validate_matchmaking_queue_link(action: &TypedAction<CreateLinkData>) -> ExternResult<ValidateCallbackResult> {
+    if action.base_address.clone().into_entry_hash().is_none() {
+        return Ok(ValidateCallbackResult::Invalid("MatchmakingQueue base must be an EntryHash (anchor)".into()));
+    }
+    let Some(target_agent) = action.target_address.clone().into_agent_pub_key() else {
+        return Ok(ValidateCallbackResult::Invalid("MatchmakingQueue target must be an AgentPubKey".into()));
+    };
+    if action.author() != &target_agent {
+        return Ok(ValidateCallbackResult::Invalid("Players can only queue themselves for matchmaking".into()));
+    }
+    Ok(ValidateCallbackResult::Valid)
+}
+
+// A queued player claims their own ticket and their opponent's before pairing.
+fn validate_matchmaking_claim_link(action: &TypedAction<CreateLinkData>) -> ExternResult<ValidateCallbackResult> {
+    let Some(ticket_hash) = action.base_address.clone().into_action_hash() else {
+        return Ok(ValidateCallbackResult::Invalid("MatchmakingClaims base must be a ticket ActionHash".into()));
+    };
+    let Some(claimer) = action.target_address.clone().into_agent_pub_key() else {
+        return Ok(ValidateCallbackResult::Invalid("MatchmakingClaims target must be an AgentPubKey".into()));
+    };
+    if action.author() != &claimer {
+        return Ok(ValidateCallbackResult::Invalid("Players can only claim tickets for themselves".into()));
+    }
+    let ticket = must_get_valid_record(ticket_hash)?;
+    let is_ticket = match ticket.action() {
+        Action::CreateLink(create_link) => matches!(
+            LinkTypes::from_type(create_link.zome_index, create_link.link_type)?,
+            Some(LinkTypes::MatchmakingQueue)
+        ),
+        _ => false,
+    };
+    if !is_ticket {
+        return Ok(ValidateCallbackResult::Invalid("MatchmakingClaims base must be a matchmaking queue ticket".into()));
+    }
+    Ok(ValidateCallbackResult::Valid)
+}
+
+fn validate_game_to_spectator_link(action: &TypedAction<CreateLinkData>) -> ExternResult<ValidateCallbackResult> {
+    if action.base_address.clone().into_action_hash().is_none() {
+        return Ok(ValidateCallbackResult::Invalid("GameToSpectators base must be a Game ActionHash".into()));
+    }
+    let Some(target_agent) = action.target_address.clone().into_agent_pub_key() else {
+        return Ok(ValidateCallbackResult::Invalid("GameToSpectators target must be an AgentPubKey".into()));
+    };
+    if action.author() != &target_agent {
+        return Ok(ValidateCallbackResult::Invalid("Agents can only subscribe themselves as spectators".into()));
+    }
+    Ok(ValidateCallbackResult::Valid)
+}
+
+fn validate_all_tournaments_link(action: &TypedAction<CreateLinkData>) -> ExternResult<ValidateCallbackResult> {
+    if action.base_address.clone().into_entry_hash().is_none() {
+        return Ok(ValidateCallbackResult::Invalid("AllTournaments base must be an EntryHash (anchor)".into()));
+    }
+    if action.target_address.clone().into_action_hash().is_none() {
+        return Ok(ValidateCallbackResult::Invalid("AllTournaments target must be a Tournament ActionHash".into()));
+    }
+    Ok(ValidateCallbackResult::Valid)
+}
+
+fn validate_tournament_updates_link(action: &TypedAction<CreateLinkData>) -> ExternResult<ValidateCallbackResult> {
+    let Some(base_hash) = action.base_address.clone().into_action_hash() else {
+        return Ok(ValidateCallbackResult::Invalid("TournamentUpdates base must be a Tournament ActionHash".into()));
+    };
+    if action.target_address.clone().into_action_hash().is_none() {
+        return Ok(ValidateCallbackResult::Invalid("TournamentUpdates target must be a Tournament ActionHash".into()));
+    }
+    let original = must_get_valid_record(base_hash)?;
+    if let Ok(Some(tournament)) = original.entry().to_app_option::<Tournament>() {
+        if tournament.organiser != *action.author() {
+            return Ok(ValidateCallbackResult::Invalid("Only the organiser can link tournament updates".into()));
+        }
+    }
+    Ok(ValidateCallbackResult::Valid)
+}
+
+fn validate_tournament_to_participant_link(action: &TypedAction<CreateLinkData>) -> ExternResult<ValidateCallbackResult> {
+    if action.base_address.clone().into_action_hash().is_none() {
+        return Ok(ValidateCallbackResult::Invalid("TournamentToParticipants base must be a Tournament ActionHash".into()));
+    }
+    let Some(target_agent) = action.target_address.clone().into_agent_pub_key() else {
+        return Ok(ValidateCallbackResult::Invalid("TournamentToParticipants target must be an AgentPubKey".into()));
+    };
+    if action.author() != &target_agent {
+        return Ok(ValidateCallbackResult::Invalid("Players can only register themselves for a tournament".into()));
+    }
+    Ok(ValidateCallbackResult::Valid)
+}
+
+fn validate_tournament_to_game_link(action: &TypedAction<CreateLinkData>) -> ExternResult<ValidateCallbackResult> {
+    let Some(tournament_id) = action.base_address.clone().into_action_hash() else {
+        return Ok(ValidateCallbackResult::Invalid("TournamentToGames base must be a Tournament ActionHash".into()));
+    };
+    let Some(game_hash) = action.target_address.clone().into_action_hash() else {
+        return Ok(ValidateCallbackResult::Invalid("TournamentToGames target must be a Game ActionHash".into()));
+    };
+    let Ok(tag) = tournament::TournamentGameTag::try_from(SerializedBytes::from(UnsafeBytes::from(action.tag.0.clone()))) else {
+        return Ok(ValidateCallbackResult::Invalid("TournamentToGames tag must be a TournamentGameTag".into()));
+    };
+    // The seeds are fixed by the revision that started the tournament.
+    if game_validation::original_action_of(&tag.tournament_revision)? != tournament_id {
+        return Ok(ValidateCallbackResult::Invalid("TournamentToGames tag names a revision of another tournament".into()));
+    }
+    let revision = must_get_valid_record(tag.tournament_revision.clone())?;
+    let Ok(Some(tournament)) = revision.entry().to_app_option::<Tournament>() else {
+        return Ok(ValidateCallbackResult::Invalid("TournamentToGames tag revision is not a Tournament".into()));
+    };
+    if tournament.seeds.is_empty() {
+        return Ok(ValidateCallbackResult::Invalid("Games can only be linked to a started tournament".into()));
+    }
+
+    // Tournament games are created with both players named.
+    let game_record = must_get_valid_record(game_hash)?;
+    let Ok(Some(game)) = game_record.entry().to_app_option::<Game>() else {
+        return Ok(ValidateCallbackResult::Invalid("TournamentToGames target is not a Game".into()));
+    };
+    let Some(player_2) = game.player_2.clone() else {
+        return Ok(ValidateCallbackResult::Invalid("A tournament game must name both players".into()));
+    };
+    // Only a player of the linked game may attach it to a bracket.
+    if game.player_1 != *action.author() && player_2 != *action.author() {
+        return Ok(ValidateCallbackResult::Invalid("Only a player of the game can link it to a tournament".into()));
+    }
+    if game.player_1 == player_2 || !tournament.seeds.contains(&game.player_1) || !tournament.seeds.contains(&player_2) {
+        return Ok(ValidateCallbackResult::Invalid("Both players of a tournament game must be seeds of the tournament".into()));
+    }
+
+    // Matches fixed by the seeds must be played by exactly their two players. Later
+    // elimination matches depend on earlier results, which validation cannot see; the
+    // bracket ignores games whose players are not that match's players.
+    let is_player = |p: &AgentPubKey| *p == game.player_1 || *p == player_2;
+    match tournament::seeded_pairing(tournament.format, &tournament.seeds, &tag.match_id) {
+        Some((a, b)) if is_player(&a) && is_player(&b) => {}
+        Some(_) => {
+            return Ok(ValidateCallbackResult::Invalid(format!("The game's players are not the players of match {}", tag.match_id)));
+        }
+        None if is_result_dependent_match(tournament.format, &tournament.seeds, &tag.match_id) => {}
+        None => {
+            return Ok(ValidateCallbackResult::Invalid(format!("Match {} is not part of this tournament", tag.match_id)));
+        }
+    }
+    Ok(ValidateCallbackResult::Valid)
+}
+
+// Elimination matches whose players come from earlier results: W<round>-<index> after
+// the first round, and (double elimination) L<round>-<index>, GF and the GF2 reset.
+fn is_result_dependent_match(format: tournament::TournamentFormat, seeds: &[AgentPubKey], match_id: &str) -> bool {
+    let rounds = seeds.len().next_power_of_two().trailing_zeros() as usize;
+    let double = match format {
+        tournament::TournamentFormat::SingleElimination => false,
+        tournament::TournamentFormat::DoubleElimination => true,
+        tournament::TournamentFormat::RoundRobin => return false,
+    };
+    if match_id == "GF" || match_id == "GF2" {
+        return double;
+    }
+    let Some((round, index)) = match_id
+        .get(1..)
+        .and_then(|rest| rest.split_once('-'))
+        .and_then(|(round, index)| Some((round.parse::<usize>().ok()?, index.parse::<usize>().ok()?)))
+    else {
+        return false;
+    };
+    match match_id.get(..1) {
+        Some("W") => (2..=rounds).contains(&round) && index >= 1 && index <= 1 << (rounds - round),
+        Some("L") => double && (1..=2 * (rounds - 1)).contains(&round) && index >= 1 && index <= 1 << (rounds - 1 - (round + 1) / 2),
+        _ => false,
+    }
+}
+
+fn validate_player_to_series_link(action: &TypedAction<CreateLinkData>) -> ExternResult<ValidateCallbackResult> {
+    let Some(player) = action.base_address.clone().into_agent_pub_key() else {
+        return Ok(ValidateCallbackResult::Invalid("PlayerToSeries base must be an AgentPubKey".into()));
+    };
+    let Some(series_hash) = action.target_address.clone().into_action_hash() else {
+        return Ok(ValidateCallbackResult::Invalid("PlayerToSeries target must be a Series ActionHash".into()));
+    };
+    let series_record = must_get_valid_record(series_hash)?;
+    let Ok(Some(series)) = series_record.entry().to_app_option::<Series>() else {
+        return Ok(ValidateCallbackResult::Invalid("PlayerToSeries target is not a Series".into()));
+    };
+    if !series.is_player(&player) || !series.is_player(action.author()) {
+        return Ok(ValidateCallbackResult::Invalid("A series can only be linked to its players, by its players".into()));
+    }
+    Ok(ValidateCallbackResult::Valid)
+}
+
+fn validate_series_updates_link(action: &TypedAction<CreateLinkData>) -> ExternResult<ValidateCallbackResult> {
+    let Some(base_hash) = action.base_address.clone().into_action_hash() else {
+        return Ok(ValidateCallbackResult::Invalid("SeriesUpdates base must be a Series ActionHash".into()));
+    };
+    if action.target_address.clone().into_action_hash().is_none() {
+        return Ok(ValidateCallbackResult::Invalid("SeriesUpdates target must be a Series ActionHash".into()));
+    }
+    let original = must_get_valid_record(base_hash)?;
+    if let Ok(Some(series)) = original.entry().to_app_option::<Series>() {
+        if !series.is_player(action.author()) {
+            return Ok(ValidateCallbackResult::Invalid("Only a player of the series can link its updates".into()));
+        }
+    }
+    Ok(ValidateCallbackResult::Valid)
+}
+
+// SeriesToGames and GameToSeries: the game must be played by the series' players
+// and linked by one of them.
+fn validate_series_game_link(action: &TypedAction<CreateLinkData>, from_game: bool) -> ExternResult<ValidateCallbackResult> {
+    let (series_address, game_address) = if from_game {
+        (action.target_address.clone(), action.base_address.clone())
+    } else {
+        (action.base_address.clone(), action.target_address.clone())
+    };
+    let (Some(series_hash), Some(game_hash)) = (series_address.into_action_hash(), game_address.into_action_hash()) else {
+        return Ok(ValidateCallbackResult::Invalid("Series game links must connect a Series and a Game ActionHash".into()));
+    };
+    let Ok(Some(series)) = must_get_valid_record(series_hash.clone())?.entry().to_app_option::<Series>() else {
+        return Ok(ValidateCallbackResult::Invalid("Series game link does not reference a Series".into()));
+    };
+    let game_record = must_get_valid_record(game_hash)?;
+    let Ok(Some(game)) = game_record.entry().to_app_option::<Game>() else {
+        return Ok(ValidateCallbackResult::Invalid("Series game link does not reference a Game".into()));
+    };
+    if !series.is_player(action.author()) {
+        return Ok(ValidateCallbackResult::Invalid("Only the series' players can link their own games to it".into()));
+    }
+    // The game names its one series at creation, so it can only be linked to that one.
+    if !matches!(game_record.action(), Action::Create(_)) || game.series.as_ref() != Some(&series_hash) {
+        return Ok(ValidateCallbackResult::Invalid("Only a game created for this series can be linked to it".into()));
+    }
+    if let Err(reason) = game_validation::check_series_game(&series_hash, &game)? {
+        return Ok(ValidateCallbackResult::Invalid(reason));
+    }
+    Ok(ValidateCallbackResult::Valid)
+}
+
 fn validate_player_to_players_link(action: &TypedAction<CreateLinkData>) -> ExternResult<ValidateCallbackResult> {
      let base_agent = action.base_address.clone().into_agent_pub_key()
          .ok_or(wasm_error!(WasmErrorInner::Guest("PlayerToPlayers base must be an AgentPubKey".into())))?;
@@ -218,15 +904,6 @@ fn validate_player_updates_link(action: &TypedAction<CreateLinkData>) -> ExternR
     Ok(ValidateCallbackResult::Valid)
 }
 
-fn validate_player_to_scores_link(action: &TypedAction<CreateLinkData>) -> ExternResult<ValidateCallbackResult> {
-     let _base_agent = action.base_address.clone().into_agent_pub_key()
-         .ok_or(wasm_error!(WasmErrorInner::Guest("PlayerToScores base must be an AgentPubKey".into())))?;
-    if action.target_address.clone().into_action_hash().is_none() {
-         return Ok(ValidateCallbackResult::Invalid("PlayerToScores target must be a Score ActionHash".into()));
-     }
-    Ok(ValidateCallbackResult::Valid)
-}
-
 fn validate_presence_link(action: &TypedAction<CreateLinkData>) -> ExternResult<ValidateCallbackResult> {
     if action.base_address.clone().into_entry_hash().is_none() && action.base_address.clone().into_agent_pub_key().is_none() {
          return Ok(ValidateCallbackResult::Invalid("Base for Presence link must be an EntryHash or AgentPubKey".into()));
@@ -0,0 +1,20 @@
+// ping_2_pong/dnas/ping_2_pong/zomes/integrity/ping_2_pong/src/match_result.rs
+use hdi::prelude::*;
+
+// Authoritative outcome of a finished game, countersigned by both players.
+// Scores and leaderboard data are derived from this entry only.
+#[hdk_entry_helper]
+#[derive(Clone, PartialEq)]
+pub struct MatchResult {
+    pub game_id: ActionHash,         // Original Game create action
+    pub game_revision: ActionHash,   // InProgress or Finished revision of the game naming Player 2
+    pub player_1: AgentPubKey,
+    pub player_2: AgentPubKey,
+    pub player_1_score: u32,
+    pub player_2_score: u32,
+    pub winner: Option<AgentPubKey>, // None for a draw
+    pub created_at: Timestamp,       // Chosen by the proposer, identical on both chains
+    // Each player's latest Rating going into the match (None before their first rated game).
+    pub player_1_rating: Option<ActionHash>,
+    pub player_2_rating: Option<ActionHash>,
+}
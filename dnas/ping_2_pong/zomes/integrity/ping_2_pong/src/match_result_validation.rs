@@ -0,0 +1,145 @@
+// ping_2_pong/dnas/ping_2_pong/zomes/integrity/ping_2_pong/src/match_result_validation.rs
+use hdi::prelude::*;
+use crate::game::GameStatus;
+use crate::game_rules::check_final_score;
+use crate::game_validation::game_at_revision;
+use crate::match_result::MatchResult;
+use crate::rating::Rating;
+use crate::rating_validation::check_latest_rating;
+use core::time::Duration;
+use std::ops::{Add, Sub};
+
+// Validate creation of a MatchResult entry.
+// `entry` is the raw entry from the op so the countersigning session can be inspected.
+pub fn validate_create_match_result(
+    action: &TypedAction<CreateData>,
+    result: MatchResult,
+    entry: Option<&Entry>,
+) -> ExternResult<ValidateCallbackResult> {
+    // 1. Check Author: Must be one of the two players.
+    let author = action.author();
+    if result.player_1 != *author && result.player_2 != *author {
+        return Ok(ValidateCallbackResult::Invalid(
+            "MatchResult can only be committed by one of its players".to_string(),
+        ));
+    }
+    if result.player_1 == result.player_2 {
+        return Ok(ValidateCallbackResult::Invalid(
+            "Player 1 and Player 2 cannot be the same agent".to_string(),
+        ));
+    }
+
+    // 2. Check Countersigning: Both players must be signing agents of the session.
+    let Some(Entry::CounterSign(session_data, _)) = entry else {
+        return Ok(ValidateCallbackResult::Invalid(
+            "MatchResult must be countersigned by both players".to_string(),
+        ));
+    };
+    let signing_agents: Vec<&AgentPubKey> = session_data
+        .preflight_request()
+        .signing_agents
+        .iter()
+        .map(|(agent, _roles)| agent)
+        .collect();
+    if signing_agents.len() != 2
+        || !signing_agents.contains(&&result.player_1)
+        || !signing_agents.contains(&&result.player_2)
+    {
+        return Ok(ValidateCallbackResult::Invalid(
+            "MatchResult signing agents must be exactly Player 1 and Player 2".to_string(),
+        ));
+    }
+
+    // 3. Check Winner: Must be consistent with the scores.
+    let expected_winner = match result.player_1_score.cmp(&result.player_2_score) {
+        core::cmp::Ordering::Greater => Some(&result.player_1),
+        core::cmp::Ordering::Less => Some(&result.player_2),
+        core::cmp::Ordering::Equal => None,
+    };
+    if result.winner.as_ref() != expected_winner {
+        return Ok(ValidateCallbackResult::Invalid(
+            "MatchResult winner does not match the final scores".to_string(),
+        ));
+    }
+
+    // 4. Check Game: Players must match a revision of the game that has started.
+    //    Lobby games only name Player 2 from the join onwards, so the original is not enough.
+    let Some(game) = game_at_revision(&result.game_id, &result.game_revision)? else {
+        return Ok(ValidateCallbackResult::Invalid(
+            "MatchResult game_revision is not a revision of the game_id Game".to_string(),
+        ));
+    };
+    if !matches!(game.game_status, GameStatus::InProgress | GameStatus::Finished) {
+        return Ok(ValidateCallbackResult::Invalid(
+            "MatchResult game_revision must be an InProgress or Finished revision".to_string(),
+        ));
+    }
+    if game.player_1 != result.player_1 {
+        return Ok(ValidateCallbackResult::Invalid("MatchResult Player 1 does not match the game".to_string()));
+    }
+    if game.player_2.as_ref() != Some(&result.player_2) {
+        return Ok(ValidateCallbackResult::Invalid("MatchResult Player 2 does not match the game".to_string()));
+    }
+
+    // 5. Check Scores: Must be a finished game under the game's rules.
+    if let Err(reason) = check_final_score(&game.rules, result.player_1_score, result.player_2_score) {
+        return Ok(ValidateCallbackResult::Invalid(format!("MatchResult scores: {}", reason)));
+    }
+
+    // 6. Check Timestamp plausibility
+    let action_time = action.timestamp();
+    let five_minutes = Duration::from_secs(300);
+    let lower_bound = action_time.sub(five_minutes)
+        .map_err(|e| wasm_error!(WasmErrorInner::Guest(format!("Timestamp subtraction error: {}", e))))?;
+    let upper_bound = action_time.add(five_minutes)
+        .map_err(|e| wasm_error!(WasmErrorInner::Guest(format!("Timestamp addition error: {}", e))))?;
+    if result.created_at < lower_bound || result.created_at > upper_bound {
+        return Ok(ValidateCallbackResult::Invalid(
+            "MatchResult created_at timestamp is too far from action timestamp (+/- 5 mins)".to_string()
+        ));
+    }
+
+    // 7. Check Rating: the author's pre-match rating is their latest, and their last game was rated.
+    let own_rating = if result.player_1 == *author { &result.player_1_rating } else { &result.player_2_rating };
+    if let Some(reason) = check_latest_rating(action, own_rating)? {
+        return Ok(ValidateCallbackResult::Invalid(reason));
+    }
+
+    // 8. Check Uniqueness: the players have not already recorded a result for this game.
+    if has_earlier_result_for_game(author, own_rating, &result.game_id, game.created_at)? {
+        return Ok(ValidateCallbackResult::Invalid(
+            "A MatchResult has already been recorded for this game".to_string(),
+        ));
+    }
+
+    Ok(ValidateCallbackResult::Valid)
+}
+
+// The author's earlier MatchResults are all rated (see check_latest_rating), so they
+// are found by following the pre-match Ratings back, as far as the game's creation.
+fn has_earlier_result_for_game(
+    author: &AgentPubKey,
+    latest_rating: &Option<ActionHash>,
+    game_id: &ActionHash,
+    game_created_at: Timestamp,
+) -> ExternResult<bool> {
+    let mut rating_hash = latest_rating.clone();
+    while let Some(hash) = rating_hash {
+        let Ok(Some(rating)) = must_get_valid_record(hash)?.entry().to_app_option::<Rating>() else {
+            return Ok(false);
+        };
+        let Ok(Some(result)) = must_get_valid_record(rating.match_result)?.entry().to_app_option::<MatchResult>() else {
+            return Ok(false);
+        };
+        if result.game_id == *game_id {
+            return Ok(true);
+        }
+        if result.created_at < game_created_at {
+            return Ok(false);
+        }
+        rating_hash = if result.player_1 == *author { result.player_1_rating } else { result.player_2_rating };
+    }
+    Ok(false)
+}
+
+// MatchResults are final once countersigned: no updates or deletes.
@@ -0,0 +1,28 @@
+// ping_2_pong/dnas/ping_2_pong/zomes/integrity/ping_2_pong/src/moderation.rs
+use hdi::prelude::*;
+
+// Private per-agent chat filters. Blocked agents' chat messages and invitations
+// are dropped; muted agents' chat messages are hidden. Updated in place.
+#[hdk_entry_helper]
+#[derive(Clone, PartialEq, Default)]
+pub struct ChatFilters {
+    pub blocked: Vec<AgentPubKey>,
+    pub muted: Vec<AgentPubKey>,
+}
+
+impl ChatFilters {
+    /// True if chat messages from `agent` should not be shown.
+    pub fn hides_chat_from(&self, agent: &AgentPubKey) -> bool {
+        self.blocked.contains(agent) || self.muted.contains(agent)
+    }
+}
+
+// A public complaint about a chat message, for moderators to review.
+#[hdk_entry_helper]
+#[derive(Clone, PartialEq)]
+pub struct Report {
+    pub reporter: AgentPubKey,
+    pub message: ActionHash, // ChatMessage ActionHash
+    pub reason: String,
+    pub created_at: Timestamp,
+}
@@ -0,0 +1,78 @@
+// ping_2_pong/dnas/ping_2_pong/zomes/integrity/ping_2_pong/src/moderation_validation.rs
+use hdi::prelude::*;
+use core::time::Duration;
+use std::ops::{Add, Sub};
+use crate::chat::ChatMessage;
+use crate::moderation::Report;
+use crate::properties::dna_properties;
+
+pub const MAX_REPORT_REASON_LENGTH: usize = 500;
+
+// Validate creation of a Report entry.
+pub fn validate_create_report(
+    action: &TypedAction<CreateData>,
+    report: Report,
+) -> ExternResult<ValidateCallbackResult> {
+    if report.reporter != *action.author() {
+        return Ok(ValidateCallbackResult::Invalid("Reports can only be filed by their reporter".into()));
+    }
+    if report.reason.trim().is_empty() || report.reason.chars().count() > MAX_REPORT_REASON_LENGTH {
+        return Ok(ValidateCallbackResult::Invalid(format!(
+            "Report reason must be between 1 and {} characters", MAX_REPORT_REASON_LENGTH
+        )));
+    }
+    let message_record = must_get_valid_record(report.message.clone())?;
+    let Ok(Some(message)) = message_record.entry().to_app_option::<ChatMessage>() else {
+        return Ok(ValidateCallbackResult::Invalid("Reports must reference a ChatMessage".into()));
+    };
+    if message.sender == report.reporter {
+        return Ok(ValidateCallbackResult::Invalid("You cannot report your own message".into()));
+    }
+
+    let action_time = action.timestamp();
+    let five_minutes = Duration::from_secs(300);
+    let lower_bound = action_time.sub(five_minutes)
+        .map_err(|e| wasm_error!(WasmErrorInner::Guest(format!("Timestamp error: {}", e))))?;
+    let upper_bound = action_time.add(five_minutes)
+        .map_err(|e| wasm_error!(WasmErrorInner::Guest(format!("Timestamp error: {}", e))))?;
+    if report.created_at < lower_bound || report.created_at > upper_bound {
+        return Ok(ValidateCallbackResult::Invalid("Report created_at must be within 5 minutes of the action timestamp".into()));
+    }
+    Ok(ValidateCallbackResult::Valid)
+}
+
+// ChatReports: "chat_reports" anchor -> Report, linked by the reporter.
+pub fn validate_chat_reports_link(action: &TypedAction<CreateLinkData>) -> ExternResult<ValidateCallbackResult> {
+    if action.base_address.clone().into_entry_hash().is_none() {
+        return Ok(ValidateCallbackResult::Invalid("ChatReports base must be an EntryHash (anchor)".into()));
+    }
+    let Some(report_hash) = action.target_address.clone().into_action_hash() else {
+        return Ok(ValidateCallbackResult::Invalid("ChatReports target must be a Report ActionHash".into()));
+    };
+    let report_record = must_get_valid_record(report_hash)?;
+    let Ok(Some(report)) = report_record.entry().to_app_option::<Report>() else {
+        return Ok(ValidateCallbackResult::Invalid("ChatReports target is not a Report".into()));
+    };
+    if report.reporter != *action.author() {
+        return Ok(ValidateCallbackResult::Invalid("Reports can only be linked by their reporter".into()));
+    }
+    Ok(ValidateCallbackResult::Valid)
+}
+
+// HiddenChatMessages: "hidden_chat_messages" anchor -> ChatMessage, only by a moderator.
+pub fn validate_hidden_chat_messages_link(action: &TypedAction<CreateLinkData>) -> ExternResult<ValidateCallbackResult> {
+    if !dna_properties()?.is_moderator(action.author()) {
+        return Ok(ValidateCallbackResult::Invalid("Only moderators can hide chat messages".into()));
+    }
+    if action.base_address.clone().into_entry_hash().is_none() {
+        return Ok(ValidateCallbackResult::Invalid("HiddenChatMessages base must be an EntryHash (anchor)".into()));
+    }
+    let Some(message_hash) = action.target_address.clone().into_action_hash() else {
+        return Ok(ValidateCallbackResult::Invalid("HiddenChatMessages target must be a ChatMessage ActionHash".into()));
+    };
+    let message_record = must_get_valid_record(message_hash)?;
+    if !matches!(message_record.entry().to_app_option::<ChatMessage>(), Ok(Some(_))) {
+        return Ok(ValidateCallbackResult::Invalid("HiddenChatMessages target is not a ChatMessage".into()));
+    }
+    Ok(ValidateCallbackResult::Valid)
+}
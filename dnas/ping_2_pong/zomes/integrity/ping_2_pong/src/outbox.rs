@@ -0,0 +1,27 @@
+// ping_2_pong/dnas/ping_2_pong/zomes/integrity/ping_2_pong/src/outbox.rs
+use hdi::prelude::*;
+
+// Private copy of a critical signal waiting for the recipient's acknowledgement.
+// Each failed attempt updates it with the next retry time; delivery deletes it.
+#[hdk_entry_helper]
+#[derive(Clone, PartialEq)]
+pub struct OutboxMessage {
+    pub recipient: AgentPubKey,
+    pub game_id: Option<ActionHash>, // Sent under this game's session when set
+    pub seq: i64,                    // Kept across retries so the recipient can drop duplicates
+    pub payload: Vec<u8>,            // Encoded signal
+    pub created_at: Timestamp,
+    pub attempts: u32,
+    pub next_attempt_at: Timestamp,
+}
+
+// Private copy of a critical signal received while the UI may not have been
+// listening, kept until the UI drains it.
+#[hdk_entry_helper]
+#[derive(Clone, PartialEq)]
+pub struct InboxMessage {
+    pub sender: AgentPubKey,
+    pub seq: i64,
+    pub payload: Vec<u8>, // Encoded signal
+    pub received_at: Timestamp,
+}
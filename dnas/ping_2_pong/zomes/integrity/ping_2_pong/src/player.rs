@@ -7,6 +7,5 @@ use hdi::prelude::*;
 pub struct Player {
     pub player_key: AgentPubKey, // The agent this profile belongs to
     pub player_name: String,     // Chosen nickname
-                                 // Add other profile fields? Elo rating? Avatar URL?
-                                 // pub elo_rating: Option<u32>,
+                                 // Ratings are derived from match results (see coordinator rating.rs)
 }
\ No newline at end of file
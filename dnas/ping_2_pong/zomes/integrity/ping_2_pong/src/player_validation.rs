@@ -66,7 +66,7 @@ pub fn validate_update_player(
 }
 
 // Validate deleting a Player entry.
-// Signature matches call from lib.rs where original_player is deserialized first
+// Called from lib.rs with the original player resolved via `must_get_valid_record`
 pub fn validate_delete_player(
     action: &SignedActionHashed,
     original_player: Player, // Passed directly now
@@ -79,7 +79,4 @@ pub fn validate_delete_player(
     }
 
     Ok(ValidateCallbackResult::Valid)
-}
-
-// FIX: Remove helper function that uses `get`
-// fn must_get_valid_record(action_hash: ActionHash) -> ExternResult<Record> { ... }
\ No newline at end of file
+}
\ No newline at end of file
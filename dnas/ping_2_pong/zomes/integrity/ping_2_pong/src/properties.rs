@@ -0,0 +1,69 @@
+// ping_2_pong/dnas/ping_2_pong/zomes/integrity/ping_2_pong/src/properties.rs
+use hdi::prelude::*;
+
+// Settings read from the DNA properties (dna.yaml `integrity.properties`).
+// Omitted settings take their defaults; malformed properties are an error, so a
+// typo cannot silently drop e.g. the moderators.
+#[derive(Serialize, Deserialize, SerializedBytes, Debug, Clone)]
+pub struct DnaProperties {
+    // How long an interrupted in-progress game can be resumed before it is
+    // treated as stale (abandoned, or forfeitable by the player who stayed).
+    #[serde(default = "default_resume_grace_period_secs")]
+    pub resume_grace_period_secs: u64,
+    // Longest chat message accepted, in characters.
+    #[serde(default = "default_max_chat_message_length")]
+    pub max_chat_message_length: usize,
+    // At most `chat_rate_limit` chat messages per author within `chat_rate_window_secs`.
+    #[serde(default = "default_chat_rate_limit")]
+    pub chat_rate_limit: u32,
+    #[serde(default = "default_chat_rate_window_secs")]
+    pub chat_rate_window_secs: u64,
+    // Agents (base64 AgentPubKeys, e.g. "uhCAk...") allowed to hide chat messages.
+    #[serde(default)]
+    pub moderators: Vec<String>,
+}
+
+fn default_resume_grace_period_secs() -> u64 {
+    120
+}
+
+fn default_max_chat_message_length() -> usize {
+    500
+}
+
+fn default_chat_rate_limit() -> u32 {
+    10
+}
+
+fn default_chat_rate_window_secs() -> u64 {
+    60
+}
+
+impl Default for DnaProperties {
+    fn default() -> Self {
+        DnaProperties {
+            resume_grace_period_secs: default_resume_grace_period_secs(),
+            max_chat_message_length: default_max_chat_message_length(),
+            chat_rate_limit: default_chat_rate_limit(),
+            chat_rate_window_secs: default_chat_rate_window_secs(),
+            moderators: Vec::new(),
+        }
+    }
+}
+
+impl DnaProperties {
+    pub fn is_moderator(&self, agent: &AgentPubKey) -> bool {
+        let agent = agent.to_string();
+        self.moderators.iter().any(|moderator| *moderator == agent)
+    }
+}
+
+pub fn dna_properties() -> ExternResult<DnaProperties> {
+    let properties = dna_info()?.modifiers.properties;
+    // No properties at all: msgpack nil, or nothing.
+    if matches!(properties.bytes().as_slice(), [] | [0xc0]) {
+        return Ok(DnaProperties::default());
+    }
+    DnaProperties::try_from(properties)
+        .map_err(|e| wasm_error!(WasmErrorInner::Guest(format!("Malformed DNA properties: {:?}", e))))
+}
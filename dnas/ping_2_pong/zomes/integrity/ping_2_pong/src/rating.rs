@@ -0,0 +1,132 @@
+// ping_2_pong/dnas/ping_2_pong/zomes/integrity/ping_2_pong/src/rating.rs
+use hdi::prelude::*;
+use crate::match_result::MatchResult;
+
+// Glicko-2 system constants (Glickman, "Example of the Glicko-2 system").
+pub const DEFAULT_RATING: f64 = 1500.0;
+const DEFAULT_DEVIATION: f64 = 350.0;
+const DEFAULT_VOLATILITY: f64 = 0.06;
+const TAU: f64 = 0.5; // Constrains volatility change over time
+const GLICKO2_SCALE: f64 = 173.7178;
+const CONVERGENCE_TOLERANCE: f64 = 0.000001;
+const MAX_ITERATIONS: u32 = 100; // Safety bound for the volatility iteration
+
+// A player's rating after one countersigned MatchResult, committed by that player.
+// Validators recompute it from the two pre-match Ratings named in the MatchResult,
+// so each player's ratings form a chain with one link per rated game.
+#[hdk_entry_helper]
+#[derive(Clone, PartialEq)]
+pub struct Rating {
+    pub player: AgentPubKey,
+    pub match_result: ActionHash, // The player's own copy of the rated MatchResult
+    pub values: Glicko2,
+}
+
+// Glicko-2 values of a player.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Glicko2 {
+    pub rating: f64,
+    pub deviation: f64,
+    pub volatility: f64,
+    pub games_rated: u32,
+}
+
+impl Default for Glicko2 {
+    // Values of a player with no rated games.
+    fn default() -> Self {
+        Glicko2 {
+            rating: DEFAULT_RATING,
+            deviation: DEFAULT_DEVIATION,
+            volatility: DEFAULT_VOLATILITY,
+            games_rated: 0,
+        }
+    }
+}
+
+// Outcome of the result for `player`: 1 for a win, 0.5 for a draw, 0 for a loss.
+pub fn outcome_for(result: &MatchResult, player: &AgentPubKey) -> f64 {
+    match &result.winner {
+        Some(winner) if winner == player => 1.0,
+        Some(_) => 0.0,
+        None => 0.5,
+    }
+}
+
+fn g(phi: f64) -> f64 {
+    1.0 / (1.0 + 3.0 * phi * phi / (std::f64::consts::PI * std::f64::consts::PI)).sqrt()
+}
+
+fn expected_score(mu: f64, mu_opponent: f64, phi_opponent: f64) -> f64 {
+    1.0 / (1.0 + (-g(phi_opponent) * (mu - mu_opponent)).exp())
+}
+
+/// Rates `player` after a single game against `opponent` with outcome `score` (1, 0.5 or 0).
+/// Each match is treated as its own rating period.
+pub fn rate(player: &Glicko2, opponent: &Glicko2, score: f64) -> Glicko2 {
+    // Step 2: convert to the Glicko-2 scale
+    let mu = (player.rating - DEFAULT_RATING) / GLICKO2_SCALE;
+    let phi = player.deviation / GLICKO2_SCALE;
+    let mu_opponent = (opponent.rating - DEFAULT_RATING) / GLICKO2_SCALE;
+    let phi_opponent = opponent.deviation / GLICKO2_SCALE;
+
+    // Steps 3-4: estimated variance and improvement
+    let g_opponent = g(phi_opponent);
+    let expected = expected_score(mu, mu_opponent, phi_opponent);
+    let variance = 1.0 / (g_opponent * g_opponent * expected * (1.0 - expected));
+    let delta = variance * g_opponent * (score - expected);
+
+    // Step 5: new volatility
+    let volatility = new_volatility(phi, player.volatility, variance, delta);
+
+    // Steps 6-8: new deviation and rating, converted back
+    let phi_star = (phi * phi + volatility * volatility).sqrt();
+    let new_phi = 1.0 / (1.0 / (phi_star * phi_star) + 1.0 / variance).sqrt();
+    let new_mu = mu + new_phi * new_phi * g_opponent * (score - expected);
+
+    Glicko2 {
+        rating: GLICKO2_SCALE * new_mu + DEFAULT_RATING,
+        deviation: GLICKO2_SCALE * new_phi,
+        volatility,
+        games_rated: player.games_rated + 1,
+    }
+}
+
+// Illinois algorithm from step 5 of the Glicko-2 paper.
+fn new_volatility(phi: f64, sigma: f64, variance: f64, delta: f64) -> f64 {
+    let a = (sigma * sigma).ln();
+    let f = |x: f64| {
+        let ex = x.exp();
+        let denom = phi * phi + variance + ex;
+        ex * (delta * delta - phi * phi - variance - ex) / (2.0 * denom * denom) - (x - a) / (TAU * TAU)
+    };
+
+    let mut big_a = a;
+    let mut big_b = if delta * delta > phi * phi + variance {
+        (delta * delta - phi * phi - variance).ln()
+    } else {
+        let mut k = 1.0;
+        while f(a - k * TAU) < 0.0 && k < MAX_ITERATIONS as f64 {
+            k += 1.0;
+        }
+        a - k * TAU
+    };
+
+    let mut f_a = f(big_a);
+    let mut f_b = f(big_b);
+    let mut iterations = 0;
+    while (big_b - big_a).abs() > CONVERGENCE_TOLERANCE && iterations < MAX_ITERATIONS {
+        let big_c = big_a + (big_a - big_b) * f_a / (f_b - f_a);
+        let f_c = f(big_c);
+        if f_c * f_b <= 0.0 {
+            big_a = big_b;
+            f_a = f_b;
+        } else {
+            f_a /= 2.0;
+        }
+        big_b = big_c;
+        f_b = f_c;
+        iterations += 1;
+    }
+
+    (big_a / 2.0).exp()
+}
@@ -0,0 +1,132 @@
+// ping_2_pong/dnas/ping_2_pong/zomes/integrity/ping_2_pong/src/rating_validation.rs
+use hdi::prelude::*;
+use crate::match_result::MatchResult;
+use crate::rating::{outcome_for, rate, Glicko2, Rating};
+use crate::UnitEntryTypes;
+
+// Validate creation of a Rating entry.
+pub fn validate_create_rating(
+    action: &TypedAction<CreateData>,
+    rating: Rating,
+) -> ExternResult<ValidateCallbackResult> {
+    // 1. Check Author: Only the player can record their own rating.
+    if rating.player != *action.author() {
+        return Ok(ValidateCallbackResult::Invalid(
+            "Rating can only be created by the player it belongs to".to_string(),
+        ));
+    }
+
+    // 2. Check MatchResult: the player's own copy of a countersigned result.
+    let result_record = must_get_valid_record(rating.match_result.clone())?;
+    let Ok(Some(result)) = result_record.entry().to_app_option::<MatchResult>() else {
+        return Ok(ValidateCallbackResult::Invalid(
+            "Rating match_result does not reference a MatchResult entry".to_string(),
+        ));
+    };
+    if result_record.action().author() != action.author() {
+        return Ok(ValidateCallbackResult::Invalid(
+            "Rating must reference the player's own copy of the MatchResult".to_string(),
+        ));
+    }
+    let (previous, opponent, opponent_previous) = if result.player_1 == rating.player {
+        (&result.player_1_rating, &result.player_2, &result.player_2_rating)
+    } else if result.player_2 == rating.player {
+        (&result.player_2_rating, &result.player_1, &result.player_1_rating)
+    } else {
+        return Ok(ValidateCallbackResult::Invalid(
+            "Rating player did not take part in the MatchResult".to_string(),
+        ));
+    };
+
+    // 3. Check Uniqueness: one Rating per MatchResult. Any earlier one sits on the
+    //    author's chain between the MatchResult and this action.
+    let rating_type: EntryType = UnitEntryTypes::Rating.try_into()?;
+    let filter = ChainFilter::new(action.prev_action().clone()).until(rating.match_result.clone());
+    let activity = must_get_agent_activity(action.author().clone(), filter)?;
+    if activity.iter().any(|item| item.action.action().entry_type() == Some(&rating_type)) {
+        return Ok(ValidateCallbackResult::Invalid(
+            "A Rating has already been recorded for this MatchResult".to_string(),
+        ));
+    }
+
+    // 4. Check Values: Glicko-2 update from the pre-match ratings the players countersigned.
+    let Some(before) = rating_values(previous, &rating.player)? else {
+        return Ok(ValidateCallbackResult::Invalid("Player's pre-match rating is not a Rating of theirs".to_string()));
+    };
+    let Some(opponent_before) = rating_values(opponent_previous, opponent)? else {
+        return Ok(ValidateCallbackResult::Invalid("Opponent's pre-match rating is not a Rating of theirs".to_string()));
+    };
+    let expected = rate(&before, &opponent_before, outcome_for(&result, &rating.player));
+    if rating.values != expected {
+        return Ok(ValidateCallbackResult::Invalid(
+            "Rating values do not follow from the pre-match ratings and the MatchResult".to_string(),
+        ));
+    }
+
+    Ok(ValidateCallbackResult::Valid)
+}
+
+// The pre-match Rating named for `player` in a MatchResult must be their latest one,
+// and every MatchResult before it must have been rated. Checked on the author's own
+// copy, so each player vouches for their own entry.
+pub fn check_latest_rating(
+    action: &TypedAction<CreateData>,
+    latest: &Option<ActionHash>,
+) -> ExternResult<Option<String>> {
+    if let Some(rating_hash) = latest {
+        let record = must_get_valid_record(rating_hash.clone())?;
+        if record.action().author() != action.author() || record.entry().to_app_option::<Rating>().ok().flatten().is_none() {
+            return Ok(Some("MatchResult names a pre-match rating that is not the player's Rating".into()));
+        }
+    }
+    let rating_type: EntryType = UnitEntryTypes::Rating.try_into()?;
+    let match_result_type: EntryType = UnitEntryTypes::MatchResult.try_into()?;
+    let mut filter = ChainFilter::new(action.prev_action().clone());
+    if let Some(rating_hash) = latest {
+        filter = filter.until(rating_hash.clone());
+    }
+    let activity = must_get_agent_activity(action.author().clone(), filter)?;
+    for item in activity.iter().filter(|item| Some(&item.action.hashed.hash) != latest.as_ref()) {
+        let entry_type = item.action.action().entry_type();
+        if entry_type == Some(&rating_type) {
+            return Ok(Some("MatchResult must name the player's latest Rating".into()));
+        }
+        if entry_type == Some(&match_result_type) {
+            return Ok(Some("The player's previous MatchResult has not been rated yet".into()));
+        }
+    }
+    Ok(None)
+}
+
+// PlayerToRatings: a player links their own Rating from their key.
+pub fn validate_player_to_rating_link(action: &TypedAction<CreateLinkData>) -> ExternResult<ValidateCallbackResult> {
+    let Some(rating_hash) = action.target_address.clone().into_action_hash() else {
+        return Ok(ValidateCallbackResult::Invalid("PlayerToRatings target must be a Rating ActionHash".into()));
+    };
+    let rating_record = must_get_valid_record(rating_hash)?;
+    let Ok(Some(rating)) = rating_record.entry().to_app_option::<Rating>() else {
+        return Ok(ValidateCallbackResult::Invalid("PlayerToRatings target is not a Rating".into()));
+    };
+    if rating.player != *action.author() {
+        return Ok(ValidateCallbackResult::Invalid("PlayerToRatings can only be created by the Rating's player".into()));
+    }
+    if action.base_address.clone().into_agent_pub_key().as_ref() != Some(&rating.player) {
+        return Ok(ValidateCallbackResult::Invalid("PlayerToRatings base must be the Rating's player".into()));
+    }
+    Ok(ValidateCallbackResult::Valid)
+}
+
+// Values of a pre-match Rating (defaults when there is none), or None if the
+// hash is not a Rating of `player`.
+fn rating_values(rating_hash: &Option<ActionHash>, player: &AgentPubKey) -> ExternResult<Option<Glicko2>> {
+    let Some(rating_hash) = rating_hash else {
+        return Ok(Some(Glicko2::default()));
+    };
+    let record = must_get_valid_record(rating_hash.clone())?;
+    match record.entry().to_app_option::<Rating>() {
+        Ok(Some(rating)) if rating.player == *player => Ok(Some(rating.values)),
+        _ => Ok(None),
+    }
+}
+
+// Ratings are final once recorded: no updates or deletes.
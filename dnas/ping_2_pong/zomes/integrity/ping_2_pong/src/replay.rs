@@ -0,0 +1,25 @@
+// ping_2_pong/dnas/ping_2_pong/zomes/integrity/ping_2_pong/src/replay.rs
+use hdi::prelude::*;
+
+// Private, per-agent buffer of encoded real-time frames recorded during a game.
+// Condensed into public ReplayChunks when the game finishes.
+#[hdk_entry_helper]
+#[derive(Clone, PartialEq)]
+pub struct ReplayBuffer {
+    pub game_id: ActionHash,
+    pub start_time: Timestamp, // Time of the first frame; frame times are deltas from here
+    pub frame_count: u32,
+    pub data: Vec<u8>,         // Delta/varint encoded frames (see coordinator codec.rs)
+}
+
+// Public, compressed slice of a game's replay, linked from the game.
+#[hdk_entry_helper]
+#[derive(Clone, PartialEq)]
+pub struct ReplayChunk {
+    pub game_id: ActionHash,
+    pub game_revision: ActionHash, // A Game revision naming the recorder as a player
+    pub chunk_index: u32,      // Order of this chunk within the recorder's replay
+    pub start_time: Timestamp,
+    pub frame_count: u32,
+    pub data: Vec<u8>,
+}
@@ -0,0 +1,40 @@
+// ping_2_pong/dnas/ping_2_pong/zomes/integrity/ping_2_pong/src/replay_validation.rs
+use hdi::prelude::*;
+use crate::game_validation::game_at_revision;
+use crate::replay::ReplayChunk;
+
+// Keep chunks well below the entry size limit so they gossip cheaply.
+pub const MAX_REPLAY_CHUNK_BYTES: usize = 256 * 1024;
+
+// Validate creation of a ReplayChunk entry.
+pub fn validate_create_replay_chunk(
+    action: &TypedAction<CreateData>,
+    chunk: ReplayChunk,
+) -> ExternResult<ValidateCallbackResult> {
+    // 1. Check Size: Chunks must stay small.
+    if chunk.data.is_empty() || chunk.frame_count == 0 {
+        return Ok(ValidateCallbackResult::Invalid("Replay chunk cannot be empty".to_string()));
+    }
+    if chunk.data.len() > MAX_REPLAY_CHUNK_BYTES {
+        return Ok(ValidateCallbackResult::Invalid(format!(
+            "Replay chunk is too large (max {} bytes)", MAX_REPLAY_CHUNK_BYTES
+        )));
+    }
+
+    // 2. Check Author: Only players of the game can publish its replay.
+    let Some(game) = game_at_revision(&chunk.game_id, &chunk.game_revision)? else {
+        return Ok(ValidateCallbackResult::Invalid(
+            "Replay chunk game_revision is not a revision of its Game".to_string(),
+        ));
+    };
+    let author = action.author();
+    if game.player_1 != *author && game.player_2.as_ref() != Some(author) {
+        return Ok(ValidateCallbackResult::Invalid(
+            "Only game participants can publish a replay".to_string(),
+        ));
+    }
+
+    Ok(ValidateCallbackResult::Valid)
+}
+
+// Replay buffers are private to their author; replay chunks are immutable.
@@ -2,10 +2,12 @@
 use hdi::prelude::*;
 
 // Score entry, recorded at the end of a game for one player.
+// Derived from a countersigned MatchResult; the points must match that result.
 #[hdk_entry_helper]
 #[derive(Clone, PartialEq)]
 pub struct Score {
     pub game_id: ActionHash, // Links back to the original Game create action
+    pub match_result: ActionHash, // The countersigned MatchResult this score is derived from
     pub player: AgentPubKey, // The player this score belongs to
     pub player_points: u32,  // Points scored by this player in the game
     pub created_at: Timestamp, // When the score was recorded
@@ -1,8 +1,8 @@
 // ping_2_pong/dnas/ping_2_pong/zomes/integrity/ping_2_pong/src/score_validation.rs
 use hdi::prelude::*;
-// Remove Game/GameStatus imports as they are no longer checked here
-// use crate::{score::Score, game::{Game, GameStatus}};
-use crate::score::Score; // Keep Score import
+use crate::score::Score;
+use crate::match_result::MatchResult;
+use crate::UnitEntryTypes;
 use std::ops::{Add, Sub};
 
 
@@ -11,39 +11,53 @@ pub fn validate_create_score(
     action: &TypedAction<CreateData>,
     score: Score,
 ) -> ExternResult<ValidateCallbackResult> {
-    // 1. REMOVED: Check Game Existence and Status.
-    //    This check MUST be performed in the coordinator zome's `create_score` function
-    //    *before* calling `create_entry`. The integrity layer cannot safely perform gets.
-    /*
-    let maybe_latest_game_record = get_latest_game_record(&score.game_id)?; // Use helper <-- REMOVED
-    // ... rest of game fetching and status check ... <-- REMOVED
-    if game.game_status != GameStatus::Finished { ... } <-- REMOVED
-    */
+    // 1. Check Author: Only the player can record their own score.
+    if score.player != *action.author() {
+        return Ok(ValidateCallbackResult::Invalid(
+            "Score can only be created by the player it belongs to".to_string(),
+        ));
+    }
 
-    // 2. REMOVED: Check Player Participation.
-    //    This check also relies on fetching the Game state and belongs in the
-    //    coordinator zome's `create_score` function.
-    /*
-    if score.player != game.player_1 && game.player_2.as_ref() != Some(&score.player) { ... } <-- REMOVED
-    */
+    // 2. Check MatchResult: The score must be derived from a countersigned result.
+    //    MatchResult validation already guarantees both players signed it.
+    let result_record = must_get_valid_record(score.match_result.clone())?;
+    let result = match result_record.entry().to_app_option::<MatchResult>() {
+        Ok(Some(result)) => result,
+        _ => {
+            return Ok(ValidateCallbackResult::Invalid(
+                "Score match_result does not reference a MatchResult entry".to_string(),
+            ))
+        }
+    };
+    // Each player scores from their own copy of the result, which is on their own chain.
+    if result_record.action().author() != action.author() {
+        return Ok(ValidateCallbackResult::Invalid(
+            "Score must reference the player's own copy of the MatchResult".to_string(),
+        ));
+    }
+    if result.game_id != score.game_id {
+        return Ok(ValidateCallbackResult::Invalid(
+            "Score game_id does not match its MatchResult".to_string(),
+        ));
+    }
 
-    // 3. REMOVED: Author Check (Optional).
-    //    If we wanted to enforce that only the player records their score,
-    //    this check is simple and could remain. However, the comment indicated
-    //    allowing anyone, so we keep it removed. Coordinator enforces who calls.
-    /*
-    let author = action.action().author();
-    if score.player != *author { ... }
-    */
+    // 3. Check Points: Must equal the player's points in the MatchResult.
+    let expected_points = if result.player_1 == score.player {
+        result.player_1_score
+    } else if result.player_2 == score.player {
+        result.player_2_score
+    } else {
+        return Ok(ValidateCallbackResult::Invalid(
+            "Score player did not take part in the MatchResult".to_string(),
+        ));
+    };
+    if score.player_points != expected_points {
+        return Ok(ValidateCallbackResult::Invalid(
+            "Score points do not match the countersigned MatchResult".to_string(),
+        ));
+    }
 
-    // 4. Check Score Sanity: Points within reasonable limits.
-    //    Keep this check as it only concerns the Score entry itself.
-     if score.player_points > 100 {
-         // Recorded score seems high, allow
-     }    // Optionally return Invalid if a hard limit is desired in integrity:
-         // return Ok(ValidateCallbackResult::Invalid("Score points seem unreasonably high (> 100)".to_string()));
-    
-    // 5. Check Timestamp plausibility
+    // 4. Check Timestamp plausibility
     //    Keep this check - compares action timestamp with entry timestamp.
      let action_time = action.timestamp();
      let five_minutes_duration = core::time::Duration::from_secs(300); // Using core::time::Duration
@@ -59,12 +73,59 @@ pub fn validate_create_score(
          ));
      }
 
+    // 5. Check Uniqueness: one Score per MatchResult. Any earlier Score for it sits on
+    //    the author's chain between the MatchResult and this action.
+    if has_prior_score(action, &score.match_result)? {
+        return Ok(ValidateCallbackResult::Invalid(
+            "A Score has already been recorded for this MatchResult".to_string(),
+        ));
+    }
+
+    Ok(ValidateCallbackResult::Valid)
+}
+
+// GameToScores / PlayerToScores: only the scoring player links their Score, from their
+// own key or from the Score's game.
+pub fn validate_score_link(action: &TypedAction<CreateLinkData>, link_name: &str) -> ExternResult<ValidateCallbackResult> {
+    let Some(score_hash) = action.target_address.clone().into_action_hash() else {
+        return Ok(ValidateCallbackResult::Invalid(format!("{} target must be a Score ActionHash", link_name)));
+    };
+    let score_record = must_get_valid_record(score_hash)?;
+    let Ok(Some(score)) = score_record.entry().to_app_option::<Score>() else {
+        return Ok(ValidateCallbackResult::Invalid(format!("{} target is not a Score", link_name)));
+    };
+    if score.player != *action.author() {
+        return Ok(ValidateCallbackResult::Invalid(format!("{} can only be created by the Score's player", link_name)));
+    }
+    let base_matches = action.base_address.clone().into_agent_pub_key().as_ref() == Some(&score.player)
+        || action.base_address.clone().into_action_hash().as_ref() == Some(&score.game_id);
+    if !base_matches {
+        return Ok(ValidateCallbackResult::Invalid(format!("{} base must be the Score's player or game", link_name)));
+    }
     Ok(ValidateCallbackResult::Valid)
 }
 
+// True if the author committed a Score for `match_result` after it and before this action.
+fn has_prior_score(action: &TypedAction<CreateData>, match_result: &ActionHash) -> ExternResult<bool> {
+    let score_type: EntryType = UnitEntryTypes::Score.try_into()?;
+    let filter = ChainFilter::new(action.prev_action().clone()).until(match_result.clone());
+    let activity = must_get_agent_activity(action.author().clone(), filter)?;
+    for item in activity {
+        let prior = item.action.action();
+        if prior.entry_type() != Some(&score_type) {
+            continue;
+        }
+        let Some(entry_hash) = prior.entry_hash() else { continue };
+        let entry = must_get_entry(entry_hash.clone())?;
+        if let Ok(prior_score) = Score::try_from(entry.content) {
+            if prior_score.match_result == *match_result {
+                return Ok(true);
+            }
+        }
+    }
+    Ok(false)
+}
+
 // REMOVE validate_update_score and validate_delete_score functions entirely
 // as Scores are intended to be immutable after creation in this design.
 
-// --- REMOVED Helper Function ---
-// fn get_latest_game_record(original_game_hash: &ActionHash) -> ExternResult<Option<Record>> { ... } // <-- REMOVED
-
@@ -0,0 +1,11 @@
+// ping_2_pong/dnas/ping_2_pong/zomes/integrity/ping_2_pong/src/seen_signal.rs
+use hdi::prelude::*;
+
+// Private record of one accepted critical signal, identified by its sender and
+// sequence number, used to drop replays and duplicate deliveries of that signal.
+#[hdk_entry_helper]
+#[derive(Clone, PartialEq)]
+pub struct SeenSignal {
+    pub sender: AgentPubKey,
+    pub seq: i64,
+}
@@ -0,0 +1,41 @@
+// ping_2_pong/dnas/ping_2_pong/zomes/integrity/ping_2_pong/src/series.rs
+use hdi::prelude::*;
+use crate::game::NetcodeMode;
+use crate::game_rules::GameRules;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeriesStatus {
+    InProgress,
+    Completed,
+    Cancelled,
+}
+
+// Best-of-N series of consecutive games between the same two players.
+// Game wins are derived from the games' results; only the final winner is stored.
+#[hdk_entry_helper]
+#[derive(Clone, PartialEq)]
+pub struct Series {
+    pub player_1: AgentPubKey,
+    pub player_2: AgentPubKey,
+    pub best_of: u32,                // Odd number of games; first to best_of / 2 + 1 wins
+    pub rules: GameRules,            // Rules for every game of the series
+    pub netcode: NetcodeMode,
+    pub status: SeriesStatus,
+    pub winner: Option<AgentPubKey>, // Set when the series completes
+    pub created_at: Timestamp,
+    // Set when the series completes: for each game the winner won, its MatchResult
+    // ActionHash, or the Game revision recording the forfeit
+    #[serde(default)]
+    pub won_games: Vec<ActionHash>,
+}
+
+impl Series {
+    /// Games a player must win to take the series.
+    pub fn wins_needed(&self) -> u32 {
+        self.best_of / 2 + 1
+    }
+
+    pub fn is_player(&self, agent: &AgentPubKey) -> bool {
+        self.player_1 == *agent || self.player_2 == *agent
+    }
+}
@@ -0,0 +1,148 @@
+// ping_2_pong/dnas/ping_2_pong/zomes/integrity/ping_2_pong/src/series_validation.rs
+use hdi::prelude::*;
+use crate::game::Game;
+use crate::game_rules::check_rules;
+use crate::game_validation::original_action_of;
+use crate::match_result::MatchResult;
+use crate::series::{Series, SeriesStatus};
+use core::time::Duration;
+use std::ops::{Add, Sub};
+
+pub const MAX_SERIES_GAMES: u32 = 9;
+
+// Validate creation of a Series entry.
+pub fn validate_create_series(
+    action: &TypedAction<CreateData>,
+    series: Series,
+) -> ExternResult<ValidateCallbackResult> {
+    // 1. Check Players: The author plays in it, against someone else.
+    if !series.is_player(action.author()) {
+        return Ok(ValidateCallbackResult::Invalid("Series creator must be one of its players".into()));
+    }
+    if series.player_1 == series.player_2 {
+        return Ok(ValidateCallbackResult::Invalid("Player 1 and Player 2 cannot be the same agent".into()));
+    }
+
+    // 2. Check Length and Rules
+    if series.best_of == 0 || series.best_of > MAX_SERIES_GAMES || series.best_of.is_multiple_of(2) {
+        return Ok(ValidateCallbackResult::Invalid(format!(
+            "A series must be best of an odd number of games, at most {}", MAX_SERIES_GAMES
+        )));
+    }
+    if let Err(reason) = check_rules(&series.rules) {
+        return Ok(ValidateCallbackResult::Invalid(reason));
+    }
+
+    // 3. Check Initial State
+    if series.status != SeriesStatus::InProgress || series.winner.is_some() || !series.won_games.is_empty() {
+        return Ok(ValidateCallbackResult::Invalid("Series must be created in progress, without a winner".into()));
+    }
+
+    // 4. Check Timestamp plausibility
+    let action_time = action.timestamp();
+    let five_minutes = Duration::from_secs(300);
+    let lower_bound = action_time.sub(five_minutes)
+        .map_err(|e| wasm_error!(WasmErrorInner::Guest(format!("Timestamp subtraction error: {}", e))))?;
+    let upper_bound = action_time.add(five_minutes)
+        .map_err(|e| wasm_error!(WasmErrorInner::Guest(format!("Timestamp addition error: {}", e))))?;
+    if series.created_at < lower_bound || series.created_at > upper_bound {
+        return Ok(ValidateCallbackResult::Invalid(
+            "Series created_at timestamp is too far from action timestamp".into(),
+        ));
+    }
+
+    Ok(ValidateCallbackResult::Valid)
+}
+
+// Validate updating a Series entry against the revision it replaces.
+pub fn validate_update_series(
+    action: &SignedActionHashed,
+    updated: Series,
+    original: &Series,
+) -> ExternResult<ValidateCallbackResult> {
+    // 1. Check Author: Either player may complete or cancel the series.
+    if !original.is_player(action.action().author()) {
+        return Ok(ValidateCallbackResult::Invalid("Only a player of the series can update it".into()));
+    }
+
+    // 2. Check Immutable Fields
+    if updated.player_1 != original.player_1
+        || updated.player_2 != original.player_2
+        || updated.best_of != original.best_of
+        || updated.rules != original.rules
+        || updated.netcode != original.netcode
+        || updated.created_at != original.created_at
+    {
+        return Ok(ValidateCallbackResult::Invalid("Series settings cannot change after creation".into()));
+    }
+
+    // 3. Check Status Transition
+    match (original.status, updated.status) {
+        (SeriesStatus::InProgress, SeriesStatus::Completed) => {
+            let Some(winner) = updated.winner.as_ref().filter(|winner| original.is_player(winner)) else {
+                return Ok(ValidateCallbackResult::Invalid(
+                    "A completed series must name one of its players as winner".into(),
+                ));
+            };
+            let Action::Update(update) = action.action() else {
+                return Ok(ValidateCallbackResult::Invalid("A series is completed by updating it".into()));
+            };
+            let series_id = original_action_of(&update.original_action_address)?;
+            if let Err(reason) = check_series_wins(&series_id, &updated, winner)? {
+                return Ok(ValidateCallbackResult::Invalid(reason));
+            }
+        }
+        (SeriesStatus::InProgress, SeriesStatus::Cancelled) => {
+            if updated.winner.is_some() || !updated.won_games.is_empty() {
+                return Ok(ValidateCallbackResult::Invalid("A cancelled series has no winner".into()));
+            }
+        }
+        (from, to) => {
+            return Ok(ValidateCallbackResult::Invalid(format!(
+                "Invalid series status transition from {:?} to {:?}", from, to
+            )));
+        }
+    }
+
+    Ok(ValidateCallbackResult::Valid)
+}
+
+// The winner must have won `wins_needed` distinct games of this series: each entry
+// of `won_games` is a MatchResult naming them winner, or a Game revision carrying
+// their forfeit win, of a game created for `series_id`.
+fn check_series_wins(series_id: &ActionHash, series: &Series, winner: &AgentPubKey) -> ExternResult<Result<(), String>> {
+    if series.won_games.len() != series.wins_needed() as usize {
+        return Ok(Err(format!(
+            "A best-of-{} series is won with {} games, {} were cited",
+            series.best_of, series.wins_needed(), series.won_games.len()
+        )));
+    }
+    let mut game_ids: Vec<ActionHash> = Vec::new();
+    for won in &series.won_games {
+        let record = must_get_valid_record(won.clone())?;
+        let game_id = if let Ok(Some(result)) = record.entry().to_app_option::<MatchResult>() {
+            if result.winner.as_ref() != Some(winner) {
+                return Ok(Err("A cited match result was not won by the series winner".to_string()));
+            }
+            result.game_id
+        } else if let Ok(Some(game)) = record.entry().to_app_option::<Game>() {
+            if game.forfeit.as_ref().map(|evidence| &evidence.winner) != Some(winner) {
+                return Ok(Err("A cited game was not won by forfeit by the series winner".to_string()));
+            }
+            original_action_of(won)?
+        } else {
+            return Ok(Err("Series wins must cite MatchResults or forfeited Games".to_string()));
+        };
+        let Ok(Some(game)) = must_get_valid_record(game_id.clone())?.entry().to_app_option::<Game>() else {
+            return Ok(Err("A cited win does not reference a Game".to_string()));
+        };
+        if game.series.as_ref() != Some(series_id) {
+            return Ok(Err("A cited win is not a game of this series".to_string()));
+        }
+        if game_ids.contains(&game_id) {
+            return Ok(Err("Each game of a series counts once".to_string()));
+        }
+        game_ids.push(game_id);
+    }
+    Ok(Ok(()))
+}
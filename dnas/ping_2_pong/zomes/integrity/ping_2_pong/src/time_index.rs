@@ -0,0 +1,42 @@
+// ping_2_pong/dnas/ping_2_pong/zomes/integrity/ping_2_pong/src/time_index.rs
+use hdi::prelude::*;
+
+// Links into an hour bucket carry the time the bucket was picked from in their tag
+// (big-endian micros), so readers can order and page on that time rather than on
+// when the link happened to be created.
+
+pub fn time_tag(timestamp: Timestamp) -> LinkTag {
+    LinkTag::new(timestamp.as_micros().to_be_bytes().to_vec())
+}
+
+pub fn tag_time(tag: &LinkTag) -> Option<Timestamp> {
+    let bytes: [u8; 8] = tag.0.as_slice().try_into().ok()?;
+    Some(Timestamp::from_micros(i64::from_be_bytes(bytes)))
+}
+
+// Splits a timestamp into zero-padded UTC [year, month, day, hour] path components.
+pub fn utc_hour_components(timestamp: Timestamp) -> [String; 4] {
+    let total_hours = timestamp.as_micros().div_euclid(3_600_000_000);
+    let days = total_hours.div_euclid(24);
+    let hour = total_hours.rem_euclid(24);
+
+    // Civil-from-days (Howard Hinnant), days counted from 1970-01-01.
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    [format!("{:04}", year), format!("{:02}", month), format!("{:02}", day), format!("{:02}", hour)]
+}
+
+/// The hour bucket (root.year.month.day.hour) a timestamp falls in. Validation
+/// uses it to check that a link sits in the bucket of the time in its tag.
+pub fn hour_bucket(root: &str, timestamp: Timestamp) -> Path {
+    let [year, month, day, hour] = utc_hour_components(timestamp);
+    Path::from(format!("{}.{}.{}.{}.{}", root, year, month, day, hour))
+}
@@ -0,0 +1,110 @@
+// ping_2_pong/dnas/ping_2_pong/zomes/integrity/ping_2_pong/src/tournament.rs
+use hdi::prelude::*;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TournamentFormat {
+    SingleElimination,
+    DoubleElimination,
+    RoundRobin,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TournamentStatus {
+    Registration,
+    InProgress,
+    Completed,
+    Cancelled,
+}
+
+#[hdk_entry_helper]
+#[derive(Clone, PartialEq)]
+pub struct Tournament {
+    pub name: String,
+    pub organiser: AgentPubKey,
+    pub format: TournamentFormat,
+    pub max_players: u32,
+    pub status: TournamentStatus,
+    pub seeds: Vec<AgentPubKey>,    // Fixed when the tournament starts; index 0 is the top seed
+    pub winner: Option<AgentPubKey>, // Set when the tournament completes
+    pub created_at: Timestamp,
+    // Set when the tournament starts: for each seed, in seed order, the
+    // TournamentToParticipants CreateLink that registered them
+    #[serde(default)]
+    pub registrations: Vec<ActionHash>,
+    // Set when the tournament completes: the results naming the winner. The final
+    // (and grand final) of an elimination bracket, every match of a round robin.
+    #[serde(default)]
+    pub deciding_results: Vec<TournamentResult>,
+}
+
+// A decided tournament match: the TournamentToGames CreateLink of the game, and its
+// MatchResult ActionHash or the Game revision recording the forfeit.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TournamentResult {
+    pub game_link: ActionHash,
+    pub result: ActionHash,
+}
+
+// Tag of a TournamentToGames link: the bracket match the game is played for and the
+// tournament revision (the one fixing the seeds) it was scheduled from.
+#[derive(Serialize, Deserialize, SerializedBytes, Debug, Clone, PartialEq)]
+pub struct TournamentGameTag {
+    pub match_id: String,
+    pub tournament_revision: ActionHash,
+}
+
+// Standard seeding order so that seed 1 and seed 2 can only meet in the final.
+pub fn seed_order(size: usize) -> Vec<usize> {
+    let mut order = vec![0];
+    while order.len() < size {
+        let next = order.len() * 2;
+        order = order.iter().flat_map(|&s| [s, next - 1 - s]).collect();
+    }
+    order
+}
+
+// Round-robin pairings by round (circle method: the first seed stays fixed, the rest
+// rotate each round). Byes are left out, so match indices only count real pairings.
+pub fn round_robin_rounds(seeds: &[AgentPubKey]) -> Vec<Vec<(AgentPubKey, AgentPubKey)>> {
+    let mut circle: Vec<Option<AgentPubKey>> = seeds.iter().cloned().map(Some).collect();
+    if circle.len() % 2 == 1 {
+        circle.push(None);
+    }
+    let count = circle.len();
+    let mut rounds = Vec::new();
+    for _ in 1..count {
+        let pairs = (0..count / 2)
+            .filter_map(|i| match (&circle[i], &circle[count - 1 - i]) {
+                (Some(a), Some(b)) => Some((a.clone(), b.clone())),
+                _ => None,
+            })
+            .collect();
+        rounds.push(pairs);
+        circle[1..].rotate_right(1);
+    }
+    rounds
+}
+
+// Players of a match that the seeds alone decide: every round-robin match and the
+// first winners round of an elimination bracket. None for matches that depend on
+// earlier results, and for match ids that do not exist.
+pub fn seeded_pairing(format: TournamentFormat, seeds: &[AgentPubKey], match_id: &str) -> Option<(AgentPubKey, AgentPubKey)> {
+    let (round, index) = parse_match_index(match_id)?;
+    match (format, match_id.get(..1)?) {
+        (TournamentFormat::RoundRobin, "R") => round_robin_rounds(seeds).get(round - 1)?.get(index - 1).cloned(),
+        (TournamentFormat::SingleElimination | TournamentFormat::DoubleElimination, "W") if round == 1 => {
+            let order = seed_order(seeds.len().next_power_of_two());
+            let a = seeds.get(*order.get(2 * (index - 1))?)?;
+            let b = seeds.get(*order.get(2 * index - 1)?)?;
+            Some((a.clone(), b.clone()))
+        }
+        _ => None,
+    }
+}
+
+// "<side><round>-<index>" with 1-based round and index.
+fn parse_match_index(match_id: &str) -> Option<(usize, usize)> {
+    let (round, index) = match_id.get(1..)?.split_once('-')?;
+    let (round, index) = (round.parse::<usize>().ok()?, index.parse::<usize>().ok()?);
+    (round > 0 && index > 0).then_some((round, index))
+}
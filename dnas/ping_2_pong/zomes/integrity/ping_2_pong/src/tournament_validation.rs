@@ -0,0 +1,283 @@
+// ping_2_pong/dnas/ping_2_pong/zomes/integrity/ping_2_pong/src/tournament_validation.rs
+use hdi::prelude::*;
+use crate::game::Game;
+use crate::game_validation::original_action_of;
+use crate::match_result::MatchResult;
+use crate::tournament::{round_robin_rounds, Tournament, TournamentFormat, TournamentGameTag, TournamentResult, TournamentStatus};
+use crate::LinkTypes;
+use core::time::Duration;
+use std::ops::{Add, Sub};
+
+pub const MIN_TOURNAMENT_PLAYERS: u32 = 2;
+pub const MAX_TOURNAMENT_PLAYERS: u32 = 64;
+const MAX_TOURNAMENT_NAME_LENGTH: usize = 64;
+
+// Validate creation of a Tournament entry.
+pub fn validate_create_tournament(
+    action: &TypedAction<CreateData>,
+    tournament: Tournament,
+) -> ExternResult<ValidateCallbackResult> {
+    // 1. Check Author: The organiser creates the tournament.
+    if tournament.organiser != *action.author() {
+        return Ok(ValidateCallbackResult::Invalid("Tournament organiser must be the author".into()));
+    }
+
+    // 2. Check Name and Size
+    let name = tournament.name.trim();
+    if name.is_empty() || name.chars().count() > MAX_TOURNAMENT_NAME_LENGTH {
+        return Ok(ValidateCallbackResult::Invalid(format!(
+            "Tournament name must be 1-{} characters", MAX_TOURNAMENT_NAME_LENGTH
+        )));
+    }
+    if !(MIN_TOURNAMENT_PLAYERS..=MAX_TOURNAMENT_PLAYERS).contains(&tournament.max_players) {
+        return Ok(ValidateCallbackResult::Invalid(format!(
+            "Tournament size must be between {} and {} players", MIN_TOURNAMENT_PLAYERS, MAX_TOURNAMENT_PLAYERS
+        )));
+    }
+
+    // 3. Check Initial State: Open for registration, not yet seeded.
+    if tournament.status != TournamentStatus::Registration
+        || !tournament.seeds.is_empty()
+        || tournament.winner.is_some()
+        || !tournament.registrations.is_empty()
+        || !tournament.deciding_results.is_empty()
+    {
+        return Ok(ValidateCallbackResult::Invalid(
+            "Tournament must be created open for registration, without seeds, registrations or results".into(),
+        ));
+    }
+
+    // 4. Check Timestamp plausibility
+    let action_time = action.timestamp();
+    let five_minutes = Duration::from_secs(300);
+    let lower_bound = action_time.sub(five_minutes)
+        .map_err(|e| wasm_error!(WasmErrorInner::Guest(format!("Timestamp subtraction error: {}", e))))?;
+    let upper_bound = action_time.add(five_minutes)
+        .map_err(|e| wasm_error!(WasmErrorInner::Guest(format!("Timestamp addition error: {}", e))))?;
+    if tournament.created_at < lower_bound || tournament.created_at > upper_bound {
+        return Ok(ValidateCallbackResult::Invalid(
+            "Tournament created_at timestamp is too far from action timestamp".into(),
+        ));
+    }
+
+    Ok(ValidateCallbackResult::Valid)
+}
+
+// Validate updating a Tournament entry against the revision it replaces.
+pub fn validate_update_tournament(
+    action: &SignedActionHashed,
+    updated: Tournament,
+    original: &Tournament,
+) -> ExternResult<ValidateCallbackResult> {
+    // 1. Check Author: Only the organiser manages the tournament.
+    if action.action().author() != &original.organiser {
+        return Ok(ValidateCallbackResult::Invalid("Only the organiser can update a tournament".into()));
+    }
+
+    // 2. Check Immutable Fields
+    if updated.name != original.name
+        || updated.organiser != original.organiser
+        || updated.format != original.format
+        || updated.max_players != original.max_players
+        || updated.created_at != original.created_at
+    {
+        return Ok(ValidateCallbackResult::Invalid("Tournament settings cannot change after creation".into()));
+    }
+
+    let Action::Update(update) = action.action() else {
+        return Ok(ValidateCallbackResult::Invalid("A tournament can only be changed by updating it".into()));
+    };
+    let tournament_id = original_action_of(&update.original_action_address)?;
+
+    // 3. Check Status Transition
+    match (original.status, updated.status) {
+        (TournamentStatus::Registration, TournamentStatus::InProgress) => {
+            let count = updated.seeds.len() as u32;
+            if count < MIN_TOURNAMENT_PLAYERS || count > updated.max_players {
+                return Ok(ValidateCallbackResult::Invalid(format!(
+                    "Tournament needs between {} and {} seeded players to start", MIN_TOURNAMENT_PLAYERS, updated.max_players
+                )));
+            }
+            let mut unique = updated.seeds.clone();
+            unique.sort();
+            unique.dedup();
+            if unique.len() != updated.seeds.len() {
+                return Ok(ValidateCallbackResult::Invalid("A player can only be seeded once".into()));
+            }
+            if updated.winner.is_some() || !updated.deciding_results.is_empty() {
+                return Ok(ValidateCallbackResult::Invalid("A starting tournament cannot have a winner".into()));
+            }
+            if let Err(reason) = check_registrations(&tournament_id, &updated)? {
+                return Ok(ValidateCallbackResult::Invalid(reason));
+            }
+        }
+        (TournamentStatus::InProgress, TournamentStatus::Completed) => {
+            if updated.seeds != original.seeds || updated.registrations != original.registrations {
+                return Ok(ValidateCallbackResult::Invalid("Seeds cannot change once the tournament has started".into()));
+            }
+            let winner = match &updated.winner {
+                Some(winner) if updated.seeds.contains(winner) => winner,
+                _ => {
+                    return Ok(ValidateCallbackResult::Invalid(
+                        "A completed tournament must name a seeded player as winner".into(),
+                    ))
+                }
+            };
+            if let Err(reason) = check_deciding_results(&tournament_id, &updated, winner)? {
+                return Ok(ValidateCallbackResult::Invalid(reason));
+            }
+        }
+        (TournamentStatus::Registration, TournamentStatus::Cancelled)
+        | (TournamentStatus::InProgress, TournamentStatus::Cancelled) => {
+            if updated.seeds != original.seeds
+                || updated.registrations != original.registrations
+                || updated.winner.is_some()
+                || !updated.deciding_results.is_empty()
+            {
+                return Ok(ValidateCallbackResult::Invalid("Cancelling cannot change seeds or set a winner".into()));
+            }
+        }
+        (from, to) => {
+            return Ok(ValidateCallbackResult::Invalid(format!(
+                "Invalid tournament status transition from {:?} to {:?}", from, to
+            )));
+        }
+    }
+
+    Ok(ValidateCallbackResult::Valid)
+}
+
+// Every seed must have registered: `registrations[i]` is the TournamentToParticipants
+// link of `seeds[i]` on this tournament.
+fn check_registrations(tournament_id: &ActionHash, tournament: &Tournament) -> ExternResult<Result<(), String>> {
+    if tournament.registrations.len() != tournament.seeds.len() {
+        return Ok(Err("Each seed must cite their registration".to_string()));
+    }
+    for (seed, registration) in tournament.seeds.iter().zip(&tournament.registrations) {
+        let record = must_get_valid_record(registration.clone())?;
+        let Action::CreateLink(create_link) = record.action() else {
+            return Ok(Err("A cited registration is not a link".to_string()));
+        };
+        if !matches!(LinkTypes::from_type(create_link.zome_index, create_link.link_type)?, Some(LinkTypes::TournamentToParticipants)) {
+            return Ok(Err("A cited registration is not a TournamentToParticipants link".to_string()));
+        }
+        if create_link.base_address.clone().into_action_hash().as_ref() != Some(tournament_id) {
+            return Ok(Err("A cited registration is for another tournament".to_string()));
+        }
+        if create_link.target_address.clone().into_agent_pub_key().as_ref() != Some(seed) {
+            return Ok(Err("A seed did not register for the tournament".to_string()));
+        }
+    }
+    Ok(Ok(()))
+}
+
+// The winner must follow from the cited results: winning the final of a single
+// elimination bracket; winning the reset grand final, or both the winners-bracket
+// final and the grand final, of a double elimination bracket; topping the round-robin
+// standings (3 points per win, 1 per draw, then wins, then seed order) over results
+// for every match.
+fn check_deciding_results(tournament_id: &ActionHash, tournament: &Tournament, winner: &AgentPubKey) -> ExternResult<Result<(), String>> {
+    let mut outcomes: Vec<(String, Option<AgentPubKey>)> = Vec::new();
+    for cited in &tournament.deciding_results {
+        let (match_id, match_winner) = match cited_outcome(tournament_id, cited)? {
+            Ok(outcome) => outcome,
+            Err(reason) => return Ok(Err(reason)),
+        };
+        if outcomes.iter().any(|(id, _)| *id == match_id) {
+            return Ok(Err(format!("Match {} is cited more than once", match_id)));
+        }
+        outcomes.push((match_id, match_winner));
+    }
+    let won = |match_id: &str| outcomes.iter().any(|(id, w)| id == match_id && w.as_ref() == Some(winner));
+
+    let final_id = format!("W{}-1", tournament.seeds.len().next_power_of_two().trailing_zeros());
+    let decided = match tournament.format {
+        TournamentFormat::SingleElimination => won(&final_id),
+        TournamentFormat::DoubleElimination => won("GF2") || (won(&final_id) && won("GF")),
+        TournamentFormat::RoundRobin => {
+            if outcomes.len() != round_robin_rounds(&tournament.seeds).iter().map(Vec::len).sum::<usize>() {
+                return Ok(Err("A round robin is decided by a result for every match".to_string()));
+            }
+            // (points, wins) per seed, in seed order.
+            let mut standings: Vec<(u32, u32)> = vec![(0, 0); tournament.seeds.len()];
+            for (round, pairs) in round_robin_rounds(&tournament.seeds).iter().enumerate() {
+                for (index, (a, b)) in pairs.iter().enumerate() {
+                    let match_id = format!("R{}-{}", round + 1, index + 1);
+                    let Some((_, match_winner)) = outcomes.iter().find(|(id, _)| *id == match_id) else {
+                        return Ok(Err(format!("No result was cited for match {}", match_id)));
+                    };
+                    let seed_index = |player: &AgentPubKey| tournament.seeds.iter().position(|seed| seed == player);
+                    match match_winner {
+                        Some(w) if w == a || w == b => {
+                            if let Some(i) = seed_index(w) {
+                                standings[i].0 += 3;
+                                standings[i].1 += 1;
+                            }
+                        }
+                        Some(_) => return Ok(Err(format!("Match {} was not won by one of its players", match_id))),
+                        None => {
+                            for player in [a, b] {
+                                if let Some(i) = seed_index(player) {
+                                    standings[i].0 += 1;
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            // Ties keep seed order: the first seed with the best record tops the table.
+            let top = standings
+                .iter()
+                .enumerate()
+                .fold(None::<(usize, (u32, u32))>, |best, (i, &record)| match best {
+                    Some((_, best_record)) if best_record >= record => best,
+                    _ => Some((i, record)),
+                })
+                .map(|(i, _)| &tournament.seeds[i]);
+            top == Some(winner)
+        }
+    };
+    if !decided {
+        return Ok(Err("The cited results do not make the winner the champion".to_string()));
+    }
+    Ok(Ok(()))
+}
+
+// The match id and winner (None for a draw) of a cited result. The link must be a
+// TournamentToGames link of this tournament and the result that of its game.
+fn cited_outcome(tournament_id: &ActionHash, cited: &TournamentResult) -> ExternResult<Result<(String, Option<AgentPubKey>), String>> {
+    let link_record = must_get_valid_record(cited.game_link.clone())?;
+    let Action::CreateLink(create_link) = link_record.action() else {
+        return Ok(Err("A cited tournament game is not a link".to_string()));
+    };
+    if !matches!(LinkTypes::from_type(create_link.zome_index, create_link.link_type)?, Some(LinkTypes::TournamentToGames))
+        || create_link.base_address.clone().into_action_hash().as_ref() != Some(tournament_id)
+    {
+        return Ok(Err("A cited tournament game is not a game of this tournament".to_string()));
+    }
+    let Some(game_id) = create_link.target_address.clone().into_action_hash() else {
+        return Ok(Err("A cited tournament game does not target a Game".to_string()));
+    };
+    let Ok(tag) = TournamentGameTag::try_from(SerializedBytes::from(UnsafeBytes::from(create_link.tag.0.clone()))) else {
+        return Ok(Err("A cited tournament game link has no TournamentGameTag".to_string()));
+    };
+
+    let record = must_get_valid_record(cited.result.clone())?;
+    let match_winner = if let Ok(Some(result)) = record.entry().to_app_option::<MatchResult>() {
+        if result.game_id != game_id {
+            return Ok(Err(format!("The result cited for match {} is for another game", tag.match_id)));
+        }
+        result.winner
+    } else if let Ok(Some(game)) = record.entry().to_app_option::<Game>() {
+        let Some(evidence) = game.forfeit else {
+            return Ok(Err(format!("The game cited for match {} was not forfeited", tag.match_id)));
+        };
+        if original_action_of(&cited.result)? != game_id {
+            return Ok(Err(format!("The forfeit cited for match {} is for another game", tag.match_id)));
+        }
+        Some(evidence.winner)
+    } else {
+        return Ok(Err("Tournament results must cite MatchResults or forfeited Games".to_string()));
+    };
+    Ok(Ok((tag.match_id, match_winner)))
+}
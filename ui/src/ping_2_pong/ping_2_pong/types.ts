@@ -7,31 +7,77 @@ export type AgentPubKeyB64 = string;
 // will be handled when the signal is processed in the UI (e.g., in App.svelte).
 export type HdkTimestamp = number;
 
-export interface GlobalChatMessageSignal {
-  type: "GlobalChatMessage";
+// A chat message as kept in the chat store.
+export interface GlobalChatMessage {
+  message_hash: string; // Base64 original ChatMessage ActionHash
   timestamp: HdkTimestamp; // Milliseconds since epoch
   sender: AgentPubKeyB64;
   content: string;
+  edited: boolean;
 }
 
-// You might also want to define the payload separately if it's used elsewhere
-export interface ChatMessagePayloadU { // U for UI
-  timestamp: HdkTimestamp;
-  sender: AgentPubKeyB64;
+// From ping_2_pong_integrity/src/chat.rs (enum ChatChannel)
+// We need AgentPubKey from @holochain/client for this type in UI.
+import type { ActionHash, AgentPubKey } from '@holochain/client';
+
+export type ChatChannel =
+  | "Global"
+  | { Game: ActionHash }
+  | { Direct: [AgentPubKey, AgentPubKey] };
+
+// From the coordinator's chat.rs (struct ReactionCount)
+export interface ReactionCount {
+  emoji: string;
+  count: number;
+  reacted_by_me: boolean;
+}
+
+// From the coordinator's chat.rs (struct ChatMessageView); timestamps in microseconds
+export interface ChatMessageView {
+  message_hash: ActionHash;
+  channel: ChatChannel;
+  sender: AgentPubKey;
+  timestamp: number;
   content: string;
+  edited_at: number | null;
+  deleted: boolean;
+  reactions: ReactionCount[];
 }
 
-// And then the signal could be:
-// export interface GlobalChatMessageSignal {
-//   type: "GlobalChatMessage";
-//   payload: ChatMessagePayloadU;
-// }
-// However, the current task asks for a flat structure for GlobalChatMessageSignal.
-// Sticking to the specified structure.
+// Signal::ChatMessage; the timestamp is in microseconds
+export interface ChatMessageSignal {
+  type: "ChatMessage";
+  channel: ChatChannel;
+  message_hash: ActionHash;
+  timestamp: number;
+  sender: AgentPubKey;
+  content: string;
+}
+
+// Signal::ChatMessageChanged: refetch the message with get_chat_message
+export interface ChatMessageChangedSignal {
+  type: "ChatMessageChanged";
+  channel: ChatChannel;
+  message_hash: ActionHash;
+  changed_by: AgentPubKey;
+}
+
+// Signal::MatchResultProposal: Player 1's signed preflight, passed back as is to accept_match_result
+export interface MatchResultProposalSignal {
+  type: "MatchResultProposal";
+  game_id: ActionHash;
+  proposer: AgentPubKey;
+  proposal: unknown;
+}
+
+// Signal::MatchResultCommitted (local only): record our Score with create_score
+export interface MatchResultCommittedSignal {
+  type: "MatchResultCommitted";
+  game_id: ActionHash;
+  match_result_hash: ActionHash;
+}
 
 // From ping_2_pong_integrity/src/player.rs (struct Player)
-// We need AgentPubKey from @holochain/client for this type in UI.
-import type { AgentPubKey } from '@holochain/client';
 
 export interface Player {
   player_name: string;
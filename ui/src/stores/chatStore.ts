@@ -1,21 +1,47 @@
 import { writable } from 'svelte/store';
-import type { GlobalChatMessageSignal } from '../ping_2_pong/ping_2_pong/types';
+import { encodeHashToBase64 } from '@holochain/client';
+import type { ActionHash } from '@holochain/client';
+import type { ChatMessageSignal, ChatMessageView, GlobalChatMessage } from '../ping_2_pong/ping_2_pong/types';
 
 const MAX_CHAT_MESSAGES = 100; // Define a maximum number of messages to store
 
-export const globalChatMessages = writable<GlobalChatMessageSignal[]>([]);
+export const globalChatMessages = writable<GlobalChatMessage[]>([]);
 
-export function addChatMessage(newMessage: GlobalChatMessageSignal) {
+// Holochain timestamps are microseconds since the epoch.
+function toMillis(micros: number): number {
+    return Math.floor(micros / 1000);
+}
+
+export function chatMessageFromView(view: ChatMessageView): GlobalChatMessage {
+    return {
+        message_hash: encodeHashToBase64(view.message_hash),
+        timestamp: toMillis(view.timestamp),
+        sender: encodeHashToBase64(view.sender),
+        content: view.content,
+        edited: view.edited_at !== null,
+    };
+}
+
+export function chatMessageFromSignal(signal: ChatMessageSignal): GlobalChatMessage {
+    return {
+        message_hash: encodeHashToBase64(signal.message_hash),
+        timestamp: toMillis(signal.timestamp),
+        sender: encodeHashToBase64(signal.sender),
+        content: signal.content,
+        edited: false,
+    };
+}
+
+export function isGlobalChannel(channel: unknown): boolean {
+    return channel === "Global";
+}
+
+export function addChatMessage(newMessage: GlobalChatMessage) {
     globalChatMessages.update(messages => {
-        // Prevent duplicate message additions (same sender, same content, within 2 seconds)
-        const isDuplicate = messages.some(
-            m => m.sender === newMessage.sender &&
-                 m.content === newMessage.content &&
-                 Math.abs((m.timestamp || 0) - (newMessage.timestamp || 0)) < 2000
-        );
-        if (isDuplicate) return messages;
-
-        const updatedMessages = [...messages, newMessage];
+        // The same message can arrive both as a signal and in the history.
+        if (messages.some(m => m.message_hash === newMessage.message_hash)) return messages;
+
+        const updatedMessages = [...messages, newMessage].sort((a, b) => a.timestamp - b.timestamp);
         if (updatedMessages.length > MAX_CHAT_MESSAGES) {
             return updatedMessages.slice(updatedMessages.length - MAX_CHAT_MESSAGES);
         }
@@ -23,6 +49,19 @@ export function addChatMessage(newMessage: GlobalChatMessageSignal) {
     });
 }
 
+// Applies an edit or deletion fetched with get_chat_message (null once hidden).
+export function replaceChatMessage(messageHash: ActionHash, view: ChatMessageView | null) {
+    const hashB64 = encodeHashToBase64(messageHash);
+    globalChatMessages.update(messages => {
+        if (!view || view.deleted) return messages.filter(m => m.message_hash !== hashB64);
+        return messages.map(m => (m.message_hash === hashB64 ? chatMessageFromView(view) : m));
+    });
+}
+
+export function setChatMessages(views: ChatMessageView[]) {
+    globalChatMessages.set(views.filter(view => !view.deleted).map(chatMessageFromView).slice(-MAX_CHAT_MESSAGES));
+}
+
 export function clearChatMessages() {
     globalChatMessages.set([]);
 }